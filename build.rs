@@ -15,38 +15,49 @@
  *  You should have received a copy of the GNU General Public License
  *  along with this program.  If not, see <http://www.gnu.org/licenses/>.
  */
+#[cfg(feature = "frontend-gl")]
 extern crate gl_generator;
 
+#[cfg(feature = "frontend-gl")]
 use gl_generator::{Registry, Api, Profile, Fallbacks, GlobalGenerator};
+#[cfg(feature = "frontend-gl")]
 use std::env;
+#[cfg(feature = "frontend-gl")]
 use std::fs::File;
+#[cfg(feature = "frontend-gl")]
 use std::path::Path;
 
 
-#[cfg(target_os = "macos")]
+#[cfg(all(feature = "frontend-gl", target_os = "macos"))]
 fn register_gl_api(file: &mut File) {
     Registry::new(Api::Gl, (3, 3), Profile::Core, Fallbacks::All, [])
         .write_bindings(GlobalGenerator, file)
         .unwrap();
 }
 
-#[cfg(target_os = "windows")]
+#[cfg(all(feature = "frontend-gl", target_os = "windows"))]
 fn register_gl_api(file: &mut File) {
     Registry::new(Api::Gl, (4, 6), Profile::Core, Fallbacks::All, [])
         .write_bindings(GlobalGenerator, file)
         .unwrap();
 }
 
-#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+#[cfg(all(feature = "frontend-gl", not(any(target_os = "macos", target_os = "windows"))))]
 fn register_gl_api(file: &mut File) {
     Registry::new(Api::Gl, (4, 6), Profile::Core, Fallbacks::All, [])
         .write_bindings(GlobalGenerator, file)
         .unwrap();
 }
 
+/// Generate the OpenGL bindings for the graphical frontend.
+#[cfg(feature = "frontend-gl")]
 fn main() {
     let dest = env::var("OUT_DIR").unwrap();
     let mut file = File::create(&Path::new(&dest).join("gl_bindings.rs")).unwrap();
 
     register_gl_api(&mut file);
 }
+
+/// The headless library does not need any OpenGL bindings.
+#[cfg(not(feature = "frontend-gl"))]
+fn main() {}
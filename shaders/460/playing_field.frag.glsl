@@ -1,6 +1,7 @@
 #version 460 core
 
 in vec2 tex_coord;
+in float alpha;
 
 uniform sampler2D tex;
 
@@ -8,5 +9,6 @@ out vec4 frag_color;
 
 
 void main() {
-    frag_color = texture(tex, tex_coord);
+    vec4 color = texture(tex, tex_coord);
+    frag_color = vec4(color.rgb, alpha * color.a);
 }
@@ -0,0 +1,411 @@
+/*
+ *  Googly Blocks is a video game.
+ *  Copyright (C) 2018,2019,2020  Christopher Blanchard
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use crate::input::{
+    InputAction,
+};
+use crate::timer::{
+    Interval,
+};
+
+use std::fmt;
+use std::str;
+use std::time::Duration;
+
+
+/// The frame rate that auto shift delays given in frames are counted at.
+pub const FRAMES_PER_SECOND: u64 = 60;
+
+/// The default time a direction is held before the block starts shifting on its own.
+pub const DEFAULT_DELAYED_AUTO_SHIFT: AutoShiftDelay = AutoShiftDelay::Frames(10);
+
+/// The default time between shifts once the block shifts on its own.
+pub const DEFAULT_AUTO_REPEAT_RATE: AutoShiftDelay = AutoShiftDelay::Frames(2);
+
+/// The default pause in auto shifting after a rotation.
+pub const DEFAULT_DAS_CUT_DELAY: AutoShiftDelay = AutoShiftDelay::Frames(0);
+
+/// The longest auto shift delay in milliseconds.
+pub const MAX_AUTO_SHIFT_MILLISECONDS: u64 = 5000;
+
+/// The longest auto shift delay in frames.
+pub const MAX_AUTO_SHIFT_FRAMES: u64 = MAX_AUTO_SHIFT_MILLISECONDS * FRAMES_PER_SECOND / 1000;
+
+/// A delay of the auto shift, given either in milliseconds or in frames.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AutoShiftDelay {
+    Milliseconds(u64),
+    Frames(u64),
+}
+
+impl AutoShiftDelay {
+    /// The length of the delay. A delay given in frames is rounded to the
+    /// nearest millisecond.
+    pub fn interval(&self) -> Interval {
+        match *self {
+            AutoShiftDelay::Milliseconds(millis) => Interval::Milliseconds(millis),
+            AutoShiftDelay::Frames(frames) => {
+                let millis = frames.saturating_mul(1000).saturating_add(FRAMES_PER_SECOND / 2) / FRAMES_PER_SECOND;
+                Interval::Milliseconds(millis)
+            }
+        }
+    }
+}
+
+impl fmt::Display for AutoShiftDelay {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AutoShiftDelay::Milliseconds(millis) => write!(formatter, "{}ms", millis),
+            AutoShiftDelay::Frames(frames) => write!(formatter, "{}f", frames),
+        }
+    }
+}
+
+impl str::FromStr for AutoShiftDelay {
+    type Err = String;
+
+    /// Parse a delay such as `167`, `167ms`, or `10f`. A delay without a unit
+    /// is in milliseconds. A delay can be at most `MAX_AUTO_SHIFT_MILLISECONDS`
+    /// milliseconds or `MAX_AUTO_SHIFT_FRAMES` frames long.
+    fn from_str(st: &str) -> Result<AutoShiftDelay, String> {
+        let invalid = || format!("Invalid auto shift delay: {}", st);
+        let delay = if let Some(millis) = st.strip_suffix("ms") {
+            millis.parse::<u64>().map(AutoShiftDelay::Milliseconds).map_err(|_| invalid())?
+        } else if let Some(frames) = st.strip_suffix('f') {
+            frames.parse::<u64>().map(AutoShiftDelay::Frames).map_err(|_| invalid())?
+        } else {
+            st.parse::<u64>().map(AutoShiftDelay::Milliseconds).map_err(|_| invalid())?
+        };
+        let too_long = match delay {
+            AutoShiftDelay::Milliseconds(millis) => millis > MAX_AUTO_SHIFT_MILLISECONDS,
+            AutoShiftDelay::Frames(frames) => frames > MAX_AUTO_SHIFT_FRAMES,
+        };
+        if too_long {
+            return Err(format!(
+                "An auto shift delay must be at most {}ms or {}f: {}", 
+                MAX_AUTO_SHIFT_MILLISECONDS, MAX_AUTO_SHIFT_FRAMES, st
+            ));
+        }
+
+        Ok(delay)
+    }
+}
+
+/// The delays of the auto shift.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AutoShiftSpec {
+    /// How long a direction has to be held before the block starts shifting
+    /// on its own.
+    pub delayed_auto_shift: Interval,
+    /// The time between shifts once the block shifts on its own. A rate of zero
+    /// shifts the block straight to the wall.
+    pub auto_repeat_rate: Interval,
+    /// How long auto shifting pauses after the player rotates the block.
+    pub das_cut_delay: Interval,
+}
+
+impl AutoShiftSpec {
+    /// Construct the auto shift delays from the delays in the game settings.
+    pub fn from_delays(
+        delayed_auto_shift: AutoShiftDelay, 
+        auto_repeat_rate: AutoShiftDelay, 
+        das_cut_delay: AutoShiftDelay) -> AutoShiftSpec {
+        AutoShiftSpec {
+            delayed_auto_shift: delayed_auto_shift.interval(),
+            auto_repeat_rate: auto_repeat_rate.interval(),
+            das_cut_delay: das_cut_delay.interval(),
+        }
+    }
+}
+
+/// The direction the player shifts the block in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShiftDirection {
+    Left,
+    Right,
+}
+
+/// How far the block should shift.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Shift {
+    /// Shift the block by a number of columns.
+    Columns(usize),
+    /// Shift the block as far as it goes.
+    ToWall,
+}
+
+fn duration(interval: Interval) -> Duration {
+    match interval {
+        Interval::Milliseconds(millis) => Duration::from_millis(millis),
+    }
+}
+
+/// The delayed auto shift moves the block one column when the player presses
+/// a direction, and once the direction has been held for the delay, keeps
+/// moving it at the auto repeat rate. The direction pressed last wins.
+pub struct AutoShift {
+    delayed_auto_shift: Duration,
+    auto_repeat_rate: Duration,
+    das_cut_delay: Duration,
+    /// The direction that is charging, if any.
+    direction: Option<ShiftDirection>,
+    /// How long the direction has been held.
+    charge: Duration,
+    /// The number of times the block has shifted on its own since the direction
+    /// started charging.
+    repeats: u128,
+    /// The time left before auto shifting picks up again after a rotation.
+    cut: Duration,
+}
+
+impl AutoShift {
+    /// Construct a new auto shift.
+    pub fn new(spec: AutoShiftSpec) -> AutoShift {
+        AutoShift {
+            delayed_auto_shift: duration(spec.delayed_auto_shift),
+            auto_repeat_rate: duration(spec.auto_repeat_rate),
+            das_cut_delay: duration(spec.das_cut_delay),
+            direction: None,
+            charge: Duration::from_millis(0),
+            repeats: 0,
+            cut: Duration::from_millis(0),
+        }
+    }
+
+    /// The direction that is charging, if any.
+    #[inline]
+    pub fn direction(&self) -> Option<ShiftDirection> {
+        self.direction
+    }
+
+    fn charge(&mut self, direction: ShiftDirection) {
+        self.direction = Some(direction);
+        self.charge = Duration::from_millis(0);
+        self.repeats = 0;
+    }
+
+    /// Press a direction. The block shifts one column right away, and the
+    /// direction starts charging.
+    pub fn press(&mut self, direction: ShiftDirection) -> Shift {
+        self.charge(direction);
+
+        Shift::Columns(1)
+    }
+
+    /// Keep a direction held down for the elapsed time. A direction that is held
+    /// without a press, such as one pressed during a line clear, starts charging
+    /// without shifting the block.
+    pub fn hold(&mut self, direction: ShiftDirection, elapsed: Duration) -> Shift {
+        match self.direction {
+            Some(charging) if charging == direction => {}
+            Some(_) => return Shift::Columns(0),
+            None => {
+                self.charge(direction);
+                return Shift::Columns(0);
+            }
+        }
+
+        if self.cut > elapsed {
+            self.cut -= elapsed;
+            return Shift::Columns(0);
+        }
+        self.charge += elapsed - self.cut;
+        self.cut = Duration::from_millis(0);
+        if self.charge < self.delayed_auto_shift {
+            return Shift::Columns(0);
+        }
+        if self.auto_repeat_rate.as_millis() == 0 {
+            return Shift::ToWall;
+        }
+
+        let repeats = 1 + (self.charge - self.delayed_auto_shift).as_millis() / self.auto_repeat_rate.as_millis();
+        let shifts = repeats - self.repeats;
+        self.repeats = repeats;
+
+        Shift::Columns(shifts as usize)
+    }
+
+    /// Release a direction. The direction stops charging.
+    pub fn release(&mut self, direction: ShiftDirection) {
+        if self.direction == Some(direction) {
+            self.direction = None;
+        }
+    }
+
+    /// Press, hold, or release a direction.
+    pub fn handle_input(&mut self, direction: ShiftDirection, action: InputAction, elapsed: Duration) -> Shift {
+        match action {
+            InputAction::Press => self.press(direction),
+            InputAction::Repeat => self.hold(direction, elapsed),
+            InputAction::Release => {
+                self.release(direction);
+                Shift::Columns(0)
+            }
+        }
+    }
+
+    /// Pause auto shifting for the DAS cut delay after the player rotates the block.
+    pub fn cut(&mut self) {
+        self.cut = self.das_cut_delay;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AutoShift,
+        AutoShiftDelay,
+        AutoShiftSpec,
+        Shift,
+        ShiftDirection,
+    };
+    use crate::timer::{
+        Interval,
+    };
+
+    use std::time::Duration;
+
+
+    const FRAME: Duration = Duration::from_millis(10);
+
+    fn auto_shift(das: u64, arr: u64, das_cut: u64) -> AutoShift {
+        AutoShift::new(AutoShiftSpec {
+            delayed_auto_shift: Interval::Milliseconds(das),
+            auto_repeat_rate: Interval::Milliseconds(arr),
+            das_cut_delay: Interval::Milliseconds(das_cut),
+        })
+    }
+
+    /// Hold a direction for a number of frames, and count how far the block shifts.
+    fn hold_frames(auto_shift: &mut AutoShift, direction: ShiftDirection, frames: usize) -> usize {
+        let mut columns = 0;
+        for _ in 0..frames {
+            match auto_shift.hold(direction, FRAME) {
+                Shift::Columns(shifted) => columns += shifted,
+                Shift::ToWall => panic!("The block should not shift to the wall"),
+            }
+        }
+
+        columns
+    }
+
+
+    /// A delay should be parsed in milliseconds or in frames.
+    #[test]
+    fn a_delay_should_be_parsed_in_milliseconds_or_frames() {
+        assert_eq!("167".parse::<AutoShiftDelay>(), Ok(AutoShiftDelay::Milliseconds(167)));
+        assert_eq!("167ms".parse::<AutoShiftDelay>(), Ok(AutoShiftDelay::Milliseconds(167)));
+        assert_eq!("10f".parse::<AutoShiftDelay>(), Ok(AutoShiftDelay::Frames(10)));
+        assert!("ten".parse::<AutoShiftDelay>().is_err());
+        assert!("10s".parse::<AutoShiftDelay>().is_err());
+        assert!("-1f".parse::<AutoShiftDelay>().is_err());
+    }
+
+    /// Every delay should parse back from its own display.
+    #[test]
+    fn a_delay_should_parse_from_its_display() {
+        let delays = [AutoShiftDelay::Milliseconds(33), AutoShiftDelay::Frames(0)];
+        for delay in delays.iter() {
+            assert_eq!(delay.to_string().parse::<AutoShiftDelay>(), Ok(*delay));
+        }
+    }
+
+    /// A delay in frames should be converted to the nearest millisecond at sixty frames a second.
+    #[test]
+    fn a_delay_in_frames_should_be_converted_to_milliseconds() {
+        assert_eq!(AutoShiftDelay::Frames(10).interval(), Interval::Milliseconds(167));
+        assert_eq!(AutoShiftDelay::Frames(2).interval(), Interval::Milliseconds(33));
+        assert_eq!(AutoShiftDelay::Frames(0).interval(), Interval::Milliseconds(0));
+        assert_eq!(AutoShiftDelay::Milliseconds(70).interval(), Interval::Milliseconds(70));
+    }
+
+    /// Pressing a direction should shift the block right away, and holding it
+    /// should not shift the block again until the delay runs out.
+    #[test]
+    fn the_first_press_should_shift_immediately_and_then_wait_for_the_delay() {
+        let mut auto_shift = auto_shift(100, 20, 0);
+
+        assert_eq!(auto_shift.press(ShiftDirection::Left), Shift::Columns(1));
+        assert_eq!(hold_frames(&mut auto_shift, ShiftDirection::Left, 9), 0);
+        assert_eq!(hold_frames(&mut auto_shift, ShiftDirection::Left, 1), 1);
+    }
+
+    /// Once the delay runs out, the block should shift once every auto repeat interval.
+    #[test]
+    fn the_block_should_shift_at_the_auto_repeat_rate() {
+        let mut auto_shift = auto_shift(100, 20, 0);
+        auto_shift.press(ShiftDirection::Right);
+        hold_frames(&mut auto_shift, ShiftDirection::Right, 10);
+
+        assert_eq!(hold_frames(&mut auto_shift, ShiftDirection::Right, 10), 5);
+        assert_eq!(auto_shift.hold(ShiftDirection::Right, Duration::from_millis(60)), Shift::Columns(3));
+    }
+
+    /// An auto repeat rate of zero should shift the block straight to the wall.
+    #[test]
+    fn an_auto_repeat_rate_of_zero_should_shift_to_the_wall() {
+        let mut auto_shift = auto_shift(100, 0, 0);
+        auto_shift.press(ShiftDirection::Right);
+
+        assert_eq!(hold_frames(&mut auto_shift, ShiftDirection::Right, 9), 0);
+        assert_eq!(auto_shift.hold(ShiftDirection::Right, FRAME), Shift::ToWall);
+    }
+
+    /// Rotating the block should pause auto shifting for the DAS cut delay.
+    #[test]
+    fn rotating_should_pause_auto_shifting_for_the_das_cut_delay() {
+        let mut auto_shift = auto_shift(100, 20, 50);
+        auto_shift.press(ShiftDirection::Left);
+        hold_frames(&mut auto_shift, ShiftDirection::Left, 10);
+        auto_shift.cut();
+
+        assert_eq!(hold_frames(&mut auto_shift, ShiftDirection::Left, 5), 0);
+        assert_eq!(hold_frames(&mut auto_shift, ShiftDirection::Left, 2), 1);
+    }
+
+    /// Releasing a direction should stop it from charging, so a new press starts over.
+    #[test]
+    fn releasing_a_direction_should_stop_it_from_charging() {
+        let mut auto_shift = auto_shift(100, 20, 0);
+        auto_shift.press(ShiftDirection::Left);
+        hold_frames(&mut auto_shift, ShiftDirection::Left, 8);
+        auto_shift.release(ShiftDirection::Left);
+
+        assert_eq!(auto_shift.direction(), None);
+        assert_eq!(auto_shift.press(ShiftDirection::Left), Shift::Columns(1));
+        assert_eq!(hold_frames(&mut auto_shift, ShiftDirection::Left, 9), 0);
+    }
+
+    /// The direction pressed last should take over from one that is still held,
+    /// and the held direction should start charging again when the other is released.
+    #[test]
+    fn the_direction_pressed_last_should_win() {
+        let mut auto_shift = auto_shift(100, 20, 0);
+        auto_shift.press(ShiftDirection::Left);
+        hold_frames(&mut auto_shift, ShiftDirection::Left, 5);
+        auto_shift.press(ShiftDirection::Right);
+
+        assert_eq!(hold_frames(&mut auto_shift, ShiftDirection::Left, 10), 0);
+        assert_eq!(auto_shift.direction(), Some(ShiftDirection::Right));
+
+        auto_shift.release(ShiftDirection::Right);
+        assert_eq!(hold_frames(&mut auto_shift, ShiftDirection::Left, 10), 0);
+        assert_eq!(auto_shift.direction(), Some(ShiftDirection::Left));
+        assert_eq!(hold_frames(&mut auto_shift, ShiftDirection::Left, 1), 1);
+    }
+}
@@ -39,6 +39,11 @@ pub enum GooglyBlockElement {
     L,
     /// The element of a I piece.
     I,
+    /// The element of a garbage row.
+    Garbage,
+    /// The element of a piece from a custom piece set, by the index of the piece
+    /// in the set.
+    Custom(usize),
 }
  
 impl GooglyBlockElement {
@@ -68,6 +73,8 @@ impl fmt::Display for GooglyBlockElement {
             S => "S",
             L => "L",
             I => "I",
+            Garbage => "G",
+            Custom(_) => "C",
         };
  
         write!(f, "{}", disp)
@@ -75,6 +82,7 @@ impl fmt::Display for GooglyBlockElement {
 }
 
 /// The data needed for moving a googly block around in the playing field.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct GooglyBlockShape {
     /// The element that composes the occupied (non-empty space) googly block
     /// cells.
@@ -88,7 +96,7 @@ pub struct GooglyBlockShape {
     /// The width of the shape in playing field cells.
     columns: usize,
     /// The placement of the non-empty cells of the shape.
-    shape: [(usize, usize); 4],
+    shape: Vec<(usize, usize)>,
 }
  
 pub struct GooglyBlockShapeIterator<'a> {
@@ -100,7 +108,7 @@ impl<'a> Iterator for GooglyBlockShapeIterator<'a> {
     type Item = (usize, usize);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index < 4 {
+        if self.index < self.shape.shape.len() {
             let item = self.shape.shape[self.index];
             self.index += 1;
             Some(item)
@@ -111,17 +119,50 @@ impl<'a> Iterator for GooglyBlockShapeIterator<'a> {
 }
  
 impl GooglyBlockShape {
+    /// Construct a shape from the cells it occupies inside a box with the given
+    /// number of rows and columns.
+    pub fn new(
+        element: GooglyBlockElement, 
+        wall_kick_distance: isize, rows: usize, columns: usize, shape: Vec<(usize, usize)>) -> GooglyBlockShape {
+
+        GooglyBlockShape {
+            element: element,
+            wall_kick_distance: wall_kick_distance,
+            rows: rows,
+            columns: columns,
+            shape: shape,
+        }
+    }
+
     pub fn iter(&self) -> GooglyBlockShapeIterator {
         GooglyBlockShapeIterator {
             index: 0,
             shape: self,
         }
     }
+
+    /// The number of cells the shape occupies.
+    #[inline]
+    pub fn cell_count(&self) -> usize {
+        self.shape.len()
+    }
+
+    /// The height of the shape in playing field cells.
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// The width of the shape in playing field cells.
+    #[inline]
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
 }
  
 impl fmt::Display for GooglyBlockShape {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut shape_matrix = [[GooglyBlockElement::EmptySpace; 4]; 4];
+        let mut shape_matrix = vec![vec![GooglyBlockElement::EmptySpace; self.columns]; self.rows];
         for (row, column) in self.iter() {
             shape_matrix[row][column] = self.element;
         }
@@ -167,6 +208,24 @@ pub enum GooglyBlockPiece {
     L,
     /// I block.
     I,
+    /// A piece from a custom piece set, by the index of the piece in the set.
+    Custom(usize),
+}
+
+impl GooglyBlockPiece {
+    /// The element the cells of this piece are made of.
+    pub fn element(self) -> GooglyBlockElement {
+        match self {
+            GooglyBlockPiece::T => GooglyBlockElement::T,
+            GooglyBlockPiece::J => GooglyBlockElement::J,
+            GooglyBlockPiece::Z => GooglyBlockElement::Z,
+            GooglyBlockPiece::O => GooglyBlockElement::O,
+            GooglyBlockPiece::S => GooglyBlockElement::S,
+            GooglyBlockPiece::L => GooglyBlockElement::L,
+            GooglyBlockPiece::I => GooglyBlockElement::I,
+            GooglyBlockPiece::Custom(index) => GooglyBlockElement::Custom(index),
+        }
+    }
 }
 
 /// A googly block consists of two parts: A piece, the kind of block that it is,
@@ -174,7 +233,7 @@ pub enum GooglyBlockPiece {
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
 pub struct GooglyBlock {
     pub piece: GooglyBlockPiece,
-    rotation: GooglyBlockRotation,
+    pub rotation: GooglyBlockRotation,
 }
 
 impl GooglyBlock {
@@ -186,227 +245,15 @@ impl GooglyBlock {
         }
     }
  
-    /// Get the shape data for a particular googly block.
-    pub fn shape(&self) -> GooglyBlockShape {
-        use self::GooglyBlockPiece::*;
-        use self::GooglyBlockRotation::*;
-        match self.piece {
-            T => match self.rotation {
-                R0 => GooglyBlockShape {
-                    shape: [(0, 0), (0, 1), (0, 2), (1, 1)],
-                    element: GooglyBlockElement::T,
-                    wall_kick_distance: 1,
-                    rows: 3,
-                    columns: 3,
-                },
-                R1 => GooglyBlockShape {
-                    shape: [(1, 0), (0, 1), (1, 1), (2, 1)],
-                    element: GooglyBlockElement::T,
-                    wall_kick_distance: 0,
-                    rows: 3,
-                    columns: 3,
-                },
-                R2 => GooglyBlockShape {
-                    shape: [(0, 1), (1, 0), (1, 1), (1, 2)],
-                    element: GooglyBlockElement::T,
-                    wall_kick_distance: 1,
-                    rows: 3,
-                    columns: 3,
-                },
-                R3 => GooglyBlockShape {
-                    shape: [(0, 1), (1, 1), (1, 2), (2, 1)],
-                    element: GooglyBlockElement::T,
-                    wall_kick_distance: 0,
-                    rows: 3,
-                    columns: 3,
-                },
-            }
-            J => match self.rotation {
-                R0 => GooglyBlockShape {
-                    shape: [(1, 0), (1, 1), (1, 2), (2, 2)],
-                    element: GooglyBlockElement::J,
-                    wall_kick_distance: 1,
-                    rows: 3,
-                    columns: 3,
-                },
-                R1 => GooglyBlockShape {
-                    shape: [(0, 1), (1, 1), (2, 1), (2, 0)],
-                    element: GooglyBlockElement::J,
-                    wall_kick_distance: 0,
-                    rows: 3,
-                    columns: 3,
-                },
-                R2 => GooglyBlockShape {
-                    shape: [(0, 0), (1, 0), (1, 1), (1, 2)],
-                    element: GooglyBlockElement::J,
-                    wall_kick_distance: 1,
-                    rows: 3,
-                    columns: 3,
-                },
-                R3 => GooglyBlockShape {
-                    shape: [(0, 1), (0, 2), (1, 1), (2, 1)],
-                    element: GooglyBlockElement::J,
-                    wall_kick_distance: 0,
-                    rows: 3,
-                    columns: 3,
-                },
-            }
-            Z => match self.rotation {
-                R0 => GooglyBlockShape {
-                    shape: [(1, 0), (1, 1), (2, 1), (2, 2)],
-                    element: GooglyBlockElement::Z,
-                    wall_kick_distance: 1,
-                    rows: 3,
-                    columns: 3,
-                },
-                R1 => GooglyBlockShape {
-                    shape: [(0, 2), (1, 1), (1, 2), (2, 1)],
-                    element: GooglyBlockElement::Z,
-                    wall_kick_distance: 0,
-                    rows: 3,
-                    columns: 3,
-                },
-                R2 => GooglyBlockShape {
-                    shape: [(1, 0), (1, 1), (2, 1), (2, 2)],
-                    element: GooglyBlockElement::Z,
-                    wall_kick_distance: 1,
-                    rows: 3,
-                    columns: 3,
-                },
-                R3 => GooglyBlockShape {
-                    shape: [(0, 2), (1, 1), (1, 2), (2, 1)],
-                    element: GooglyBlockElement::Z,
-                    wall_kick_distance: 0,
-                    rows: 3,
-                    columns: 3,
-                },        
-            }
-            O => match self.rotation {
-                R0 => GooglyBlockShape {
-                    shape: [(0, 0), (0, 1), (1, 0), (1, 1)],
-                    element: GooglyBlockElement::O,
-                    wall_kick_distance: 0,
-                    rows: 2,
-                    columns: 2,
-                },
-                R1 => GooglyBlockShape {
-                    shape: [(0, 0), (0, 1), (1, 0), (1, 1)],
-                    element: GooglyBlockElement::O,
-                    wall_kick_distance: 0,
-                    rows: 2,
-                    columns: 2,
-                },
-                R2 => GooglyBlockShape {
-                    shape: [(0, 0), (0, 1), (1, 0), (1, 1)],
-                    element: GooglyBlockElement::O,
-                    wall_kick_distance: 0,
-                    rows: 2,
-                    columns: 2,
-                },
-                R3 => GooglyBlockShape {
-                    shape: [(0, 0), (0, 1), (1, 0), (1, 1)],
-                    element: GooglyBlockElement::O,
-                    wall_kick_distance: 0,
-                    rows: 2,
-                    columns: 2,
-                },              
-            }
-            S => match self.rotation {
-                R0 => GooglyBlockShape {
-                    shape: [(1, 1), (1, 2), (2, 0), (2, 1)],
-                    element: GooglyBlockElement::S,
-                    wall_kick_distance: 1,
-                    rows: 3,
-                    columns: 3,
-                },
-                R1 => GooglyBlockShape {
-                    shape: [(0, 1), (1, 1), (1, 2), (2, 2)],
-                    element: GooglyBlockElement::S,
-                    wall_kick_distance: 0,
-                    rows: 3,
-                    columns: 3,
-                },
-                R2 => GooglyBlockShape {
-                    shape: [(1, 1), (1, 2), (2, 0), (2, 1)],
-                    element: GooglyBlockElement::S,
-                    wall_kick_distance: 1,
-                    rows: 3,
-                    columns: 3,
-                },
-                R3 => GooglyBlockShape {
-                    shape: [(0, 1), (1, 1), (1, 2), (2, 2)],
-                    element: GooglyBlockElement::S,
-                    wall_kick_distance: 0,
-                    rows: 3,
-                    columns: 3,
-                },            
-            }
-            L => match self.rotation {
-                R0 => GooglyBlockShape {
-                    shape: [(1, 0), (1, 1), (1, 2), (2, 0)],
-                    element: GooglyBlockElement::L,
-                    wall_kick_distance: 1,
-                    rows: 3,
-                    columns: 3,
-                },
-                R1 => GooglyBlockShape {
-                    shape: [(0, 0), (0, 1), (1, 1), (2, 1)],
-                    element: GooglyBlockElement::L,
-                    wall_kick_distance: 0,
-                    rows: 3,
-                    columns: 3,
-                },
-                R2 => GooglyBlockShape {
-                    shape: [(0, 2), (1, 0), (1, 1), (1, 2)],
-                    element: GooglyBlockElement::L,
-                    wall_kick_distance: 1,
-                    rows: 3,
-                    columns: 3,
-                },
-                R3 => GooglyBlockShape {
-                    shape: [(0, 1), (1, 1), (2, 1), (2, 2)],
-                    element: GooglyBlockElement::L,
-                    wall_kick_distance: 0,
-                    rows: 3,
-                    columns: 3,
-                },             
-            }
-            I => match self.rotation {
-                R0 => GooglyBlockShape {
-                    shape: [(2, 0), (2, 1), (2, 2), (2, 3)],
-                    element: GooglyBlockElement::I,
-                    wall_kick_distance: 2,
-                    rows: 4,
-                    columns: 4,
-                },
-                R1 => GooglyBlockShape {
-                    shape: [(0, 2), (1, 2), (2, 2), (3, 2)],
-                    element: GooglyBlockElement::I,
-                    wall_kick_distance: 0,
-                    rows: 4,
-                    columns: 4,
-                },
-                R2 => GooglyBlockShape {
-                    shape: [(2, 0), (2, 1), (2, 2), (2, 3)],
-                    element: GooglyBlockElement::I,
-                    wall_kick_distance: 2,
-                    rows: 4,
-                    columns: 4,
-                },
-                R3 => GooglyBlockShape {
-                    shape: [(0, 2), (1, 2), (2, 2), (3, 2)],
-                    element: GooglyBlockElement::I,
-                    wall_kick_distance: 0,
-                    rows: 4,
-                    columns: 4,
-                },           
-            }
-        }
+    /// Generate the googly block corresponding to the same piece rotated clockwise
+    /// in the playing field. This is the same as `rotate_cw`.
+    pub fn rotate(&self) -> GooglyBlock {
+        self.rotate_cw()
     }
 
-    /// Generate the a googly block corresponding to the same piece rotated counter-clockwise
+    /// Generate the googly block corresponding to the same piece rotated clockwise
     /// in the playing field.
-    pub fn rotate(&self) -> GooglyBlock {
+    pub fn rotate_cw(&self) -> GooglyBlock {
         match self.rotation {
             GooglyBlockRotation::R0 => GooglyBlock::new(self.piece, GooglyBlockRotation::R1),
             GooglyBlockRotation::R1 => GooglyBlock::new(self.piece, GooglyBlockRotation::R2),
@@ -414,12 +261,17 @@ impl GooglyBlock {
             GooglyBlockRotation::R3 => GooglyBlock::new(self.piece, GooglyBlockRotation::R0),
         }
     }
-}
 
-impl fmt::Display for GooglyBlock {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "{}", self.shape())
-    }   
+    /// Generate the googly block corresponding to the same piece rotated counter-clockwise
+    /// in the playing field.
+    pub fn rotate_ccw(&self) -> GooglyBlock {
+        match self.rotation {
+            GooglyBlockRotation::R0 => GooglyBlock::new(self.piece, GooglyBlockRotation::R3),
+            GooglyBlockRotation::R1 => GooglyBlock::new(self.piece, GooglyBlockRotation::R0),
+            GooglyBlockRotation::R2 => GooglyBlock::new(self.piece, GooglyBlockRotation::R1),
+            GooglyBlockRotation::R3 => GooglyBlock::new(self.piece, GooglyBlockRotation::R2),
+        }
+    }
 }
 
 
@@ -430,6 +282,9 @@ mod tests {
         GooglyBlockRotation,
         GooglyBlock,
     };
+    use crate::piece_set::{
+        PieceSet,
+    };
 
 
     /// Given a googly block, if we rotate it four times, it should cycle through all of its rotations. 
@@ -441,4 +296,36 @@ mod tests {
 
         assert_eq!(result, expected);
     }
+
+    /// Rotating a googly block counter-clockwise should undo rotating it clockwise.
+    #[test]
+    fn googly_block_counter_clockwise_rotations_should_undo_clockwise_rotations() {
+        let pieces = [
+            GooglyBlockPiece::T, GooglyBlockPiece::J, GooglyBlockPiece::Z, GooglyBlockPiece::O,
+            GooglyBlockPiece::S, GooglyBlockPiece::L, GooglyBlockPiece::I,
+        ];
+        let rotations = [
+            GooglyBlockRotation::R0, GooglyBlockRotation::R1, GooglyBlockRotation::R2, GooglyBlockRotation::R3,
+        ];
+        for piece in pieces.iter() {
+            for rotation in rotations.iter() {
+                let expected = GooglyBlock::new(*piece, *rotation);
+                let result = expected.rotate_cw().rotate_ccw();
+
+                assert_eq!(result, expected);
+            }
+        }
+    }
+
+    /// Rotating a T block clockwise should turn its point from facing down to facing left.
+    #[test]
+    fn rotating_a_t_block_clockwise_should_turn_it_clockwise_on_screen() {
+        let piece_set = PieceSet::standard();
+        let block = GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0);
+        let result: Vec<(usize, usize)> = piece_set.shape(block.rotate_cw()).iter().collect();
+
+        // The point of the T block sits on the left of the stem.
+        assert!(result.contains(&(1, 0)));
+        assert!(!result.contains(&(1, 2)));
+    }
 }
@@ -0,0 +1,674 @@
+/*
+ *  Googly Blocks is a video game.
+ *  Copyright (C) 2018,2019,2020  Christopher Blanchard
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use crate::playing_field::{
+    LandedBlocksGrid,
+    TopOut,
+};
+use crate::score::{
+    ScoreBoard,
+};
+use crate::timer::{
+    Interval,
+    Timer,
+};
+
+use std::fmt;
+use std::str;
+use std::time::Duration;
+
+
+/// The level a player has to reach to finish a marathon. This is above the
+/// highest starting level, so every marathon has at least one level to clear.
+pub const MARATHON_TARGET_LEVEL: usize = 20;
+
+/// The number of lines a player has to clear to finish a sprint.
+pub const SPRINT_TARGET_LINES: usize = 40;
+
+/// The length of an ultra game.
+pub const ULTRA_TIME_LIMIT: Duration = Duration::from_secs(120);
+
+/// The number of garbage rows a cheese race starts with.
+pub const CHEESE_RACE_GARBAGE_ROWS: usize = 10;
+
+/// The time between garbage rows rising in a survival game.
+pub const SURVIVAL_GARBAGE_INTERVAL: Interval = Interval::Milliseconds(8000);
+
+/// How a game mode ended.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GameModeOutcome {
+    /// The player met the goal of the game mode.
+    Victory,
+    /// The player topped out before meeting the goal of the game mode.
+    Defeat,
+}
+
+/// A labelled value a game mode shows alongside the score board.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HudField {
+    pub label: &'static str,
+    pub value: String,
+}
+
+impl HudField {
+    /// Construct a new HUD field.
+    pub fn new(label: &'static str, value: String) -> HudField {
+        HudField {
+            label: label,
+            value: value,
+        }
+    }
+}
+
+impl fmt::Display for HudField {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{} {}", self.label, self.value)
+    }
+}
+
+/// A game mode decides when a game is won or lost, and what the player needs
+/// to see to keep track of the goal.
+pub trait GameMode {
+    /// The kind of game mode.
+    fn kind(&self) -> GameModeKind;
+    /// Update the game mode with the time elapsed since the last update, the
+    /// current score board and landed blocks, and how the game topped out, if it
+    /// did. A game mode that has ended ignores any further updates.
+    fn update(
+        &mut self, 
+        elapsed: Duration, score_board: &ScoreBoard, landed_blocks: &LandedBlocksGrid, top_out: Option<TopOut>
+    );
+    /// The number of garbage rows the playing field starts with.
+    fn starting_garbage(&self) -> usize {
+        0
+    }
+    /// The number of garbage rows that should rise into the playing field since 
+    /// the last time this was called.
+    fn take_garbage(&mut self) -> usize {
+        0
+    }
+    /// How the game ended, if it is over.
+    fn outcome(&self) -> Option<GameModeOutcome>;
+    /// The fields to show alongside the score board.
+    fn hud(&self, score_board: &ScoreBoard) -> Vec<HudField>;
+}
+
+/// The game modes that can be selected in the game settings.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GameModeKind {
+    /// Play until the blocks reach the top of the playing field.
+    Endless,
+    /// Play until reaching `MARATHON_TARGET_LEVEL`.
+    Marathon,
+    /// Clear `SPRINT_TARGET_LINES` lines as fast as possible.
+    Sprint,
+    /// Score as many points as possible in `ULTRA_TIME_LIMIT`.
+    Ultra,
+    /// Dig through `CHEESE_RACE_GARBAGE_ROWS` garbage rows as fast as possible.
+    CheeseRace,
+    /// Last as long as possible while garbage rises every `SURVIVAL_GARBAGE_INTERVAL`.
+    Survival,
+}
+
+impl GameModeKind {
+    /// Construct a game mode of this kind.
+    pub fn create(self) -> Box<dyn GameMode> {
+        match self {
+            GameModeKind::Endless => Box::new(EndlessMode::new()),
+            GameModeKind::Marathon => Box::new(MarathonMode::new(MARATHON_TARGET_LEVEL)),
+            GameModeKind::Sprint => Box::new(SprintMode::new(SPRINT_TARGET_LINES)),
+            GameModeKind::Ultra => Box::new(UltraMode::new(ULTRA_TIME_LIMIT)),
+            GameModeKind::CheeseRace => Box::new(CheeseRaceMode::new(CHEESE_RACE_GARBAGE_ROWS)),
+            GameModeKind::Survival => Box::new(SurvivalMode::new(SURVIVAL_GARBAGE_INTERVAL)),
+        }
+    }
+}
+
+impl fmt::Display for GameModeKind {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let disp = match *self {
+            GameModeKind::Endless => "endless",
+            GameModeKind::Marathon => "marathon",
+            GameModeKind::Sprint => "sprint",
+            GameModeKind::Ultra => "ultra",
+            GameModeKind::CheeseRace => "cheese",
+            GameModeKind::Survival => "survival",
+        };
+        write!(formatter, "{}", disp)
+    }
+}
+
+impl str::FromStr for GameModeKind {
+    type Err = String;
+
+    fn from_str(st: &str) -> Result<GameModeKind, String> {
+        match st {
+            "endless" => Ok(GameModeKind::Endless),
+            "marathon" => Ok(GameModeKind::Marathon),
+            "sprint" => Ok(GameModeKind::Sprint),
+            "ultra" => Ok(GameModeKind::Ultra),
+            "cheese" => Ok(GameModeKind::CheeseRace),
+            "survival" => Ok(GameModeKind::Survival),
+            _ => Err(format!("Unknown game mode: {}", st)),
+        }
+    }
+}
+
+/// Format a play time as minutes, seconds, and hundredths of a second.
+pub fn format_time(time: Duration) -> String {
+    let hundredths = time.as_millis() / 10;
+    format!("{}:{:02}.{:02}", hundredths / 6000, (hundredths / 100) % 60, hundredths % 100)
+}
+
+/// The original game mode. The game goes on until the player tops out.
+pub struct EndlessMode {
+    outcome: Option<GameModeOutcome>,
+}
+
+impl EndlessMode {
+    /// Construct a new endless game.
+    pub fn new() -> EndlessMode {
+        EndlessMode {
+            outcome: None,
+        }
+    }
+}
+
+impl Default for EndlessMode {
+    fn default() -> EndlessMode {
+        EndlessMode::new()
+    }
+}
+
+impl GameMode for EndlessMode {
+    fn kind(&self) -> GameModeKind {
+        GameModeKind::Endless
+    }
+
+    fn update(
+        &mut self, 
+        _elapsed: Duration, _score_board: &ScoreBoard, _landed_blocks: &LandedBlocksGrid, top_out: Option<TopOut>
+    ) {
+        if self.outcome.is_none() && top_out.is_some() {
+            self.outcome = Some(GameModeOutcome::Defeat);
+        }
+    }
+
+    fn outcome(&self) -> Option<GameModeOutcome> {
+        self.outcome
+    }
+
+    fn hud(&self, _score_board: &ScoreBoard) -> Vec<HudField> {
+        vec![]
+    }
+}
+
+/// In a marathon the player wins by reaching the target level.
+pub struct MarathonMode {
+    target_level: usize,
+    elapsed: Duration,
+    outcome: Option<GameModeOutcome>,
+}
+
+impl MarathonMode {
+    /// Construct a new marathon that is won by reaching the target level.
+    pub fn new(target_level: usize) -> MarathonMode {
+        MarathonMode {
+            target_level: target_level,
+            elapsed: Duration::from_millis(0),
+            outcome: None,
+        }
+    }
+}
+
+impl GameMode for MarathonMode {
+    fn kind(&self) -> GameModeKind {
+        GameModeKind::Marathon
+    }
+
+    fn update(
+        &mut self, 
+        elapsed: Duration, score_board: &ScoreBoard, _landed_blocks: &LandedBlocksGrid, top_out: Option<TopOut>
+    ) {
+        if self.outcome.is_some() {
+            return;
+        }
+
+        self.elapsed += elapsed;
+        if score_board.level >= self.target_level {
+            self.outcome = Some(GameModeOutcome::Victory);
+        } else if top_out.is_some() {
+            self.outcome = Some(GameModeOutcome::Defeat);
+        }
+    }
+
+    fn outcome(&self) -> Option<GameModeOutcome> {
+        self.outcome
+    }
+
+    fn hud(&self, _score_board: &ScoreBoard) -> Vec<HudField> {
+        vec![
+            HudField::new("GOAL", format!("LEVEL {}", self.target_level)),
+            HudField::new("TIME", format_time(self.elapsed)),
+        ]
+    }
+}
+
+/// In a sprint the player races to clear the target number of lines. The clock
+/// stops when the last line is cleared.
+pub struct SprintMode {
+    target_lines: usize,
+    elapsed: Duration,
+    outcome: Option<GameModeOutcome>,
+}
+
+impl SprintMode {
+    /// Construct a new sprint that is won by clearing the target number of lines.
+    pub fn new(target_lines: usize) -> SprintMode {
+        SprintMode {
+            target_lines: target_lines,
+            elapsed: Duration::from_millis(0),
+            outcome: None,
+        }
+    }
+
+    /// The time the player has taken so far, or the final time once the sprint is over.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+impl GameMode for SprintMode {
+    fn kind(&self) -> GameModeKind {
+        GameModeKind::Sprint
+    }
+
+    fn update(
+        &mut self, 
+        elapsed: Duration, score_board: &ScoreBoard, _landed_blocks: &LandedBlocksGrid, top_out: Option<TopOut>
+    ) {
+        if self.outcome.is_some() {
+            return;
+        }
+
+        self.elapsed += elapsed;
+        if score_board.lines >= self.target_lines {
+            self.outcome = Some(GameModeOutcome::Victory);
+        } else if top_out.is_some() {
+            self.outcome = Some(GameModeOutcome::Defeat);
+        }
+    }
+
+    fn outcome(&self) -> Option<GameModeOutcome> {
+        self.outcome
+    }
+
+    fn hud(&self, score_board: &ScoreBoard) -> Vec<HudField> {
+        let lines_left = self.target_lines.saturating_sub(score_board.lines);
+        vec![
+            HudField::new("LINES LEFT", format!("{}", lines_left)),
+            HudField::new("TIME", format_time(self.elapsed)),
+        ]
+    }
+}
+
+/// In an ultra game the player scores as many points as possible before the
+/// time runs out. Surviving until the end is a victory.
+pub struct UltraMode {
+    time_limit: Duration,
+    elapsed: Duration,
+    outcome: Option<GameModeOutcome>,
+}
+
+impl UltraMode {
+    /// Construct a new ultra game that lasts for the time limit.
+    pub fn new(time_limit: Duration) -> UltraMode {
+        UltraMode {
+            time_limit: time_limit,
+            elapsed: Duration::from_millis(0),
+            outcome: None,
+        }
+    }
+
+    /// The time left before the game ends.
+    pub fn time_left(&self) -> Duration {
+        self.time_limit.checked_sub(self.elapsed).unwrap_or(Duration::from_millis(0))
+    }
+}
+
+impl GameMode for UltraMode {
+    fn kind(&self) -> GameModeKind {
+        GameModeKind::Ultra
+    }
+
+    fn update(
+        &mut self, 
+        elapsed: Duration, _score_board: &ScoreBoard, _landed_blocks: &LandedBlocksGrid, top_out: Option<TopOut>
+    ) {
+        if self.outcome.is_some() {
+            return;
+        }
+
+        self.elapsed += elapsed;
+        if top_out.is_some() {
+            self.outcome = Some(GameModeOutcome::Defeat);
+        } else if self.elapsed >= self.time_limit {
+            self.outcome = Some(GameModeOutcome::Victory);
+        }
+    }
+
+    fn outcome(&self) -> Option<GameModeOutcome> {
+        self.outcome
+    }
+
+    fn hud(&self, _score_board: &ScoreBoard) -> Vec<HudField> {
+        vec![
+            HudField::new("TIME LEFT", format_time(self.time_left())),
+        ]
+    }
+}
+
+/// In a cheese race the playing field starts with rows of garbage, and the player
+/// races to clear every one of them. The clock stops when the last garbage row
+/// is cleared.
+pub struct CheeseRaceMode {
+    garbage_rows: usize,
+    garbage_left: usize,
+    elapsed: Duration,
+    outcome: Option<GameModeOutcome>,
+}
+
+impl CheeseRaceMode {
+    /// Construct a new cheese race that starts with the given number of garbage rows.
+    pub fn new(garbage_rows: usize) -> CheeseRaceMode {
+        CheeseRaceMode {
+            garbage_rows: garbage_rows,
+            garbage_left: garbage_rows,
+            elapsed: Duration::from_millis(0),
+            outcome: None,
+        }
+    }
+
+    /// The time the player has taken so far, or the final time once the race is over.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+impl GameMode for CheeseRaceMode {
+    fn kind(&self) -> GameModeKind {
+        GameModeKind::CheeseRace
+    }
+
+    fn update(
+        &mut self, 
+        elapsed: Duration, _score_board: &ScoreBoard, landed_blocks: &LandedBlocksGrid, top_out: Option<TopOut>
+    ) {
+        if self.outcome.is_some() {
+            return;
+        }
+
+        self.elapsed += elapsed;
+        self.garbage_left = landed_blocks.garbage_rows();
+        if self.garbage_left == 0 {
+            self.outcome = Some(GameModeOutcome::Victory);
+        } else if top_out.is_some() {
+            self.outcome = Some(GameModeOutcome::Defeat);
+        }
+    }
+
+    fn starting_garbage(&self) -> usize {
+        self.garbage_rows
+    }
+
+    fn outcome(&self) -> Option<GameModeOutcome> {
+        self.outcome
+    }
+
+    fn hud(&self, _score_board: &ScoreBoard) -> Vec<HudField> {
+        vec![
+            HudField::new("GARBAGE LEFT", format!("{}", self.garbage_left)),
+            HudField::new("TIME", format_time(self.elapsed)),
+        ]
+    }
+}
+
+/// In a survival game a garbage row rises from the bottom of the playing field on 
+/// a timer, and the player lasts as long as possible. The game only ends by topping out.
+pub struct SurvivalMode {
+    garbage_timer: Timer,
+    garbage_due: usize,
+    garbage_risen: usize,
+    elapsed: Duration,
+    outcome: Option<GameModeOutcome>,
+}
+
+impl SurvivalMode {
+    /// Construct a new survival game where a garbage row rises every garbage interval.
+    pub fn new(garbage_interval: Interval) -> SurvivalMode {
+        SurvivalMode {
+            garbage_timer: Timer::new(garbage_interval),
+            garbage_due: 0,
+            garbage_risen: 0,
+            elapsed: Duration::from_millis(0),
+            outcome: None,
+        }
+    }
+}
+
+impl GameMode for SurvivalMode {
+    fn kind(&self) -> GameModeKind {
+        GameModeKind::Survival
+    }
+
+    fn update(
+        &mut self, 
+        elapsed: Duration, _score_board: &ScoreBoard, _landed_blocks: &LandedBlocksGrid, top_out: Option<TopOut>
+    ) {
+        if self.outcome.is_some() {
+            return;
+        }
+
+        if top_out.is_some() {
+            self.outcome = Some(GameModeOutcome::Defeat);
+            return;
+        }
+
+        self.elapsed += elapsed;
+        self.garbage_timer.update(elapsed);
+        if self.garbage_timer.event_triggered() {
+            self.garbage_due += 1;
+            self.garbage_timer.reset();
+        }
+    }
+
+    fn take_garbage(&mut self) -> usize {
+        let garbage = self.garbage_due;
+        self.garbage_risen += garbage;
+        self.garbage_due = 0;
+
+        garbage
+    }
+
+    fn outcome(&self) -> Option<GameModeOutcome> {
+        self.outcome
+    }
+
+    fn hud(&self, _score_board: &ScoreBoard) -> Vec<HudField> {
+        vec![
+            HudField::new("GARBAGE", format!("{}", self.garbage_risen)),
+            HudField::new("TIME", format_time(self.elapsed)),
+        ]
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CheeseRaceMode,
+        GameMode,
+        GameModeKind,
+        GameModeOutcome,
+        HudField,
+        MarathonMode,
+        SprintMode,
+        SurvivalMode,
+        UltraMode,
+    };
+    use crate::timer::{
+        Interval,
+    };
+    use crate::playing_field::{
+        LandedBlocksGrid,
+        TopOut,
+    };
+    use crate::score::{
+        ScoreBoard,
+        ScoringSystemKind,
+    };
+
+    use std::time::Duration;
+
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    fn score_board(lines: usize, level: usize) -> ScoreBoard {
+        let mut score_board = ScoreBoard::new(10, 0, ScoringSystemKind::Nes.create());
+        score_board.lines = lines;
+        score_board.level = level;
+
+        score_board
+    }
+
+
+    /// An endless game should only end when the player tops out.
+    #[test]
+    fn an_endless_game_should_only_end_by_topping_out() {
+        let mut mode = GameModeKind::Endless.create();
+        mode.update(SECOND * 3600, &score_board(1000, 100), &LandedBlocksGrid::new(20, 10), None);
+        assert_eq!(mode.outcome(), None);
+
+        mode.update(SECOND, &score_board(1000, 100), &LandedBlocksGrid::new(20, 10), Some(TopOut::BlockOut));
+        assert_eq!(mode.outcome(), Some(GameModeOutcome::Defeat));
+    }
+
+    /// A marathon should be won by reaching the target level.
+    #[test]
+    fn a_marathon_should_end_at_the_target_level() {
+        let mut mode = MarathonMode::new(15);
+        mode.update(SECOND, &score_board(140, 14), &LandedBlocksGrid::new(20, 10), None);
+        assert_eq!(mode.outcome(), None);
+
+        mode.update(SECOND, &score_board(150, 15), &LandedBlocksGrid::new(20, 10), None);
+        assert_eq!(mode.outcome(), Some(GameModeOutcome::Victory));
+    }
+
+    /// A sprint should be won by clearing the target number of lines, and the
+    /// clock should stop when it is.
+    #[test]
+    fn a_sprint_should_stop_the_clock_at_the_target_lines() {
+        let mut mode = SprintMode::new(40);
+        mode.update(SECOND * 30, &score_board(38, 3), &LandedBlocksGrid::new(20, 10), None);
+        assert_eq!(mode.outcome(), None);
+        assert_eq!(mode.hud(&score_board(38, 3))[0], HudField::new("LINES LEFT", String::from("2")));
+
+        mode.update(SECOND * 2, &score_board(41, 4), &LandedBlocksGrid::new(20, 10), None);
+        mode.update(SECOND * 5, &score_board(41, 4), &LandedBlocksGrid::new(20, 10), None);
+        assert_eq!(mode.outcome(), Some(GameModeOutcome::Victory));
+        assert_eq!(mode.elapsed(), SECOND * 32);
+    }
+
+    /// Topping out before finishing a sprint should lose the game.
+    #[test]
+    fn topping_out_during_a_sprint_should_lose_the_game() {
+        let mut mode = SprintMode::new(40);
+        mode.update(SECOND, &score_board(10, 1), &LandedBlocksGrid::new(20, 10), Some(TopOut::LockOut));
+
+        assert_eq!(mode.outcome(), Some(GameModeOutcome::Defeat));
+    }
+
+    /// An ultra game should be won by lasting until the time runs out.
+    #[test]
+    fn an_ultra_game_should_end_when_the_time_runs_out() {
+        let mut mode = UltraMode::new(SECOND * 120);
+        mode.update(SECOND * 119, &score_board(0, 0), &LandedBlocksGrid::new(20, 10), None);
+        assert_eq!(mode.outcome(), None);
+        assert_eq!(mode.hud(&score_board(0, 0)), vec![HudField::new("TIME LEFT", String::from("0:01.00"))]);
+
+        mode.update(SECOND, &score_board(0, 0), &LandedBlocksGrid::new(20, 10), None);
+        assert_eq!(mode.outcome(), Some(GameModeOutcome::Victory));
+        assert_eq!(mode.time_left(), Duration::from_millis(0));
+    }
+
+    /// Play times should be shown in minutes, seconds, and hundredths.
+    #[test]
+    fn play_times_should_be_formatted_to_the_hundredth() {
+        assert_eq!(super::format_time(Duration::from_millis(0)), "0:00.00");
+        assert_eq!(super::format_time(Duration::from_millis(83_456)), "1:23.45");
+    }
+
+    /// Game modes should be selectable by name.
+    #[test]
+    fn game_modes_should_be_parsed_from_their_names() {
+        assert_eq!("endless".parse::<GameModeKind>(), Ok(GameModeKind::Endless));
+        assert_eq!("marathon".parse::<GameModeKind>(), Ok(GameModeKind::Marathon));
+        assert_eq!("sprint".parse::<GameModeKind>(), Ok(GameModeKind::Sprint));
+        assert_eq!("ultra".parse::<GameModeKind>(), Ok(GameModeKind::Ultra));
+        assert_eq!("cheese".parse::<GameModeKind>(), Ok(GameModeKind::CheeseRace));
+        assert_eq!("survival".parse::<GameModeKind>(), Ok(GameModeKind::Survival));
+        assert!("zen".parse::<GameModeKind>().is_err());
+    }
+
+    /// A cheese race should start with garbage and be won when every garbage row
+    /// has been cleared.
+    #[test]
+    fn a_cheese_race_should_end_when_the_garbage_is_cleared() {
+        let mut mode = CheeseRaceMode::new(10);
+        let mut landed = LandedBlocksGrid::new(20, 10);
+        landed.push_garbage_rows(&[0, 1]);
+        assert_eq!(mode.starting_garbage(), 10);
+
+        mode.update(SECOND * 20, &score_board(8, 0), &landed, None);
+        assert_eq!(mode.outcome(), None);
+        assert_eq!(mode.hud(&score_board(8, 0))[0], HudField::new("GARBAGE LEFT", String::from("2")));
+
+        let landed = LandedBlocksGrid::new(20, 10);
+        mode.update(SECOND, &score_board(10, 1), &landed, None);
+        assert_eq!(mode.outcome(), Some(GameModeOutcome::Victory));
+        assert_eq!(mode.elapsed(), SECOND * 21);
+    }
+
+    /// A survival game should send a garbage row every interval until the player tops out.
+    #[test]
+    fn a_survival_game_should_send_garbage_on_a_timer() {
+        let mut mode = SurvivalMode::new(Interval::Milliseconds(5000));
+        let landed = LandedBlocksGrid::new(20, 10);
+        assert_eq!(mode.starting_garbage(), 0);
+
+        mode.update(SECOND * 4, &score_board(0, 0), &landed, None);
+        assert_eq!(mode.take_garbage(), 0);
+        mode.update(SECOND, &score_board(0, 0), &landed, None);
+        mode.update(SECOND * 5, &score_board(0, 0), &landed, None);
+        assert_eq!(mode.take_garbage(), 2);
+        assert_eq!(mode.take_garbage(), 0);
+        assert_eq!(mode.outcome(), None);
+
+        mode.update(SECOND, &score_board(0, 0), &landed, Some(TopOut::GarbageOut));
+        assert_eq!(mode.outcome(), Some(GameModeOutcome::Defeat));
+    }
+}
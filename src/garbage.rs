@@ -0,0 +1,131 @@
+/*
+ *  Googly Blocks is a video game.
+ *  Copyright (C) 2018,2019,2020  Christopher Blanchard
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use rand::{
+    Rng,
+    RngCore,
+    SeedableRng,
+};
+use rand::rngs::{
+    StdRng,
+};
+
+
+/// Mixed into the seed of a garbage generator, so that a game seeded with one 
+/// number does not draw its garbage holes and its pieces from the same stream.
+const GARBAGE_SEED_SALT: u64 = 0x9e37_79b9_7f4a_7c15;
+
+
+/// The garbage generator chooses the hole column of each garbage row. No two
+/// garbage rows in a row have their holes in the same column, so the player has
+/// to dig through each row separately.
+pub struct GarbageGen {
+    /// The inner random number generator.
+    rng: Box<dyn RngCore>,
+    /// The number of columns in the playing field.
+    columns: usize,
+    /// The hole column of the last garbage row.
+    last_hole: Option<usize>,
+}
+
+impl GarbageGen {
+    /// Construct a new garbage generator with a freshly chosen seed.
+    pub fn new(columns: usize) -> GarbageGen {
+        let seed = rand::thread_rng().gen();
+
+        GarbageGen::from_seed(seed, columns)
+    }
+
+    /// Construct a garbage generator from a seed. Two generators constructed from
+    /// the same seed choose the same holes.
+    pub fn from_seed(seed: u64, columns: usize) -> GarbageGen {
+        GarbageGen::from_rng(Box::new(StdRng::seed_from_u64(seed ^ GARBAGE_SEED_SALT)), columns)
+    }
+
+    /// Construct a garbage generator that draws from an arbitrary random number generator.
+    pub fn from_rng(rng: Box<dyn RngCore>, columns: usize) -> GarbageGen {
+        GarbageGen {
+            rng: rng,
+            columns: columns,
+            last_hole: None,
+        }
+    }
+
+    /// Choose the hole columns for the given number of garbage rows.
+    pub fn holes(&mut self, rows: usize) -> Vec<usize> {
+        (0..rows).map(|_| self.next_hole()).collect()
+    }
+
+    fn next_hole(&mut self) -> usize {
+        let hole = match self.last_hole {
+            Some(last_hole) => {
+                // Choose from every column except the last hole.
+                let hole = self.rng.gen_range(0..(self.columns - 1));
+                if hole >= last_hole { hole + 1 } else { hole }
+            }
+            None => self.rng.gen_range(0..self.columns),
+        };
+        self.last_hole = Some(hole);
+
+        hole
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        GarbageGen,
+    };
+    use rand::{
+        SeedableRng,
+    };
+    use rand::rngs::{
+        StdRng,
+    };
+
+
+    /// Every hole should lie inside the playing field, and consecutive garbage rows
+    /// should never share a hole.
+    #[test]
+    fn consecutive_garbage_rows_should_have_different_holes() {
+        let mut gen = GarbageGen::from_seed(2020, 10);
+        let holes = gen.holes(1000);
+
+        assert!(holes.iter().all(|hole| *hole < 10));
+        assert!(holes.windows(2).all(|pair| pair[0] != pair[1]));
+    }
+
+    /// Two generators with the same seed should choose the same holes.
+    #[test]
+    fn garbage_generators_with_the_same_seed_should_be_deterministic() {
+        let mut gen1 = GarbageGen::from_seed(7, 10);
+        let mut gen2 = GarbageGen::from_seed(7, 10);
+
+        assert_eq!(gen1.holes(50), gen2.holes(50));
+    }
+
+    /// A garbage generator should not draw from the same stream as a piece 
+    /// generator given the same seed.
+    #[test]
+    fn garbage_generators_should_not_reuse_the_seed_directly() {
+        let mut gen1 = GarbageGen::from_seed(7, 10);
+        let mut gen2 = GarbageGen::from_rng(Box::new(StdRng::seed_from_u64(7)), 10);
+
+        assert_ne!(gen1.holes(50), gen2.holes(50));
+    }
+}
@@ -0,0 +1,235 @@
+/*
+ *  Googly Blocks is a video game.
+ *  Copyright (C) 2018,2019,2020  Christopher Blanchard
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use crate::timer::{
+    Interval,
+};
+
+use std::fmt;
+use std::str;
+
+
+/// The frame rate of the NTSC NES in frames per second.
+pub const NES_FRAME_RATE: f64 = 60.0988;
+
+/// The number of frames it takes a piece to fall one row at each level
+/// on the NTSC NES. Levels from 29 up fall one row every frame.
+const NES_FRAMES_PER_ROW: [u64; 30] = [
+    48, 43, 38, 33, 28, 23, 18, 13, 8, 6,
+    5, 5, 5, 4, 4, 4, 3, 3, 3, 2,
+    2, 2, 2, 2, 2, 2, 2, 2, 2, 1,
+];
+
+/// The number of levels the guideline gravity formula is defined for.
+const GUIDELINE_LEVELS: usize = 20;
+
+/// A gravity curve maps the player's level to the time it takes the current
+/// block to fall one row. The curve is a table of fall intervals, one for each
+/// level starting from level zero. Levels past the end of the table fall at
+/// the last interval in the table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GravityCurve {
+    intervals: Vec<Interval>,
+}
+
+impl GravityCurve {
+    /// Construct a gravity curve from a table of fall intervals, one for each level.
+    pub fn new(intervals: Vec<Interval>) -> Result<GravityCurve, String> {
+        if intervals.is_empty() {
+            return Err(String::from("A gravity curve needs at least one level."));
+        }
+        if intervals.contains(&Interval::Milliseconds(0)) {
+            return Err(String::from("A gravity curve cannot have a fall interval of zero."));
+        }
+
+        Ok(GravityCurve {
+            intervals: intervals,
+        })
+    }
+
+    /// Construct a gravity curve that falls at the same speed at every level.
+    pub fn constant(interval: Interval) -> Result<GravityCurve, String> {
+        GravityCurve::new(vec![interval])
+    }
+
+    /// Construct a gravity curve from a table of the number of frames it takes
+    /// a block to fall one row at each level.
+    pub fn from_frames(frames_per_row: &[u64], frame_rate: f64) -> Result<GravityCurve, String> {
+        let intervals = frames_per_row.iter()
+            .map(|frames| milliseconds(*frames as f64 / frame_rate))
+            .collect();
+
+        GravityCurve::new(intervals)
+    }
+
+    /// The gravity of the NTSC NES, where the speed drops by a fixed number
+    /// of frames per level up to level nine and more slowly after that.
+    pub fn nes() -> GravityCurve {
+        GravityCurve::from_frames(&NES_FRAMES_PER_ROW, NES_FRAME_RATE).unwrap()
+    }
+
+    /// The gravity of the Tetris guideline. A block falls one row every
+    /// `(0.8 - (n - 1) * 0.007)^(n - 1)` seconds, where the guideline level `n`
+    /// starts from one.
+    pub fn guideline() -> GravityCurve {
+        let intervals = (0..GUIDELINE_LEVELS)
+            .map(|level| {
+                let n = level as f64;
+                milliseconds((0.8 - n * 0.007).powf(n))
+            })
+            .collect();
+
+        GravityCurve::new(intervals).unwrap()
+    }
+
+    /// The time it takes a block to fall one row at the given level.
+    pub fn fall_interval(&self, level: usize) -> Interval {
+        let index = usize::min(level, self.intervals.len() - 1);
+        self.intervals[index]
+    }
+}
+
+/// Convert a time in seconds to a fall interval, rounding to the nearest
+/// millisecond. Blocks fall at most one row per millisecond.
+fn milliseconds(seconds: f64) -> Interval {
+    let millis = (seconds * 1000.0).round() as u64;
+    Interval::Milliseconds(u64::max(millis, 1))
+}
+
+/// The gravity curves that can be selected in the game settings.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GravityCurveKind {
+    /// The NES frame table.
+    Nes,
+    /// The guideline formula.
+    Guideline,
+}
+
+impl GravityCurveKind {
+    /// Construct a gravity curve of this kind.
+    pub fn create(self) -> GravityCurve {
+        match self {
+            GravityCurveKind::Nes => GravityCurve::nes(),
+            GravityCurveKind::Guideline => GravityCurve::guideline(),
+        }
+    }
+}
+
+impl fmt::Display for GravityCurveKind {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let disp = match *self {
+            GravityCurveKind::Nes => "nes",
+            GravityCurveKind::Guideline => "guideline",
+        };
+        write!(formatter, "{}", disp)
+    }
+}
+
+impl str::FromStr for GravityCurveKind {
+    type Err = String;
+
+    fn from_str(st: &str) -> Result<GravityCurveKind, String> {
+        match st {
+            "nes" => Ok(GravityCurveKind::Nes),
+            "guideline" => Ok(GravityCurveKind::Guideline),
+            _ => Err(format!("Unknown gravity curve: {}", st)),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        GravityCurve,
+        GravityCurveKind,
+    };
+    use crate::timer::{
+        Interval,
+    };
+
+
+    fn millis(interval: Interval) -> u64 {
+        match interval {
+            Interval::Milliseconds(millis) => millis,
+        }
+    }
+
+
+    /// Levels past the end of a gravity curve should fall at the speed of the last level.
+    #[test]
+    fn levels_past_the_end_of_the_curve_should_use_the_last_interval() {
+        let intervals = vec![Interval::Milliseconds(500), Interval::Milliseconds(400)];
+        let curve = GravityCurve::new(intervals).unwrap();
+
+        assert_eq!(curve.fall_interval(0), Interval::Milliseconds(500));
+        assert_eq!(curve.fall_interval(1), Interval::Milliseconds(400));
+        assert_eq!(curve.fall_interval(100), Interval::Milliseconds(400));
+    }
+
+    /// A gravity curve without any levels or with a zero interval should be rejected.
+    #[test]
+    fn an_invalid_gravity_curve_should_be_rejected() {
+        assert!(GravityCurve::new(vec![]).is_err());
+        assert!(GravityCurve::new(vec![Interval::Milliseconds(500), Interval::Milliseconds(0)]).is_err());
+    }
+
+    /// The NES curve should follow the NES frame table at sixty frames per second.
+    #[test]
+    fn the_nes_curve_should_follow_the_nes_frame_table() {
+        let curve = GravityCurve::nes();
+
+        assert_eq!(curve.fall_interval(0), Interval::Milliseconds(799));
+        assert_eq!(curve.fall_interval(9), Interval::Milliseconds(100));
+        assert_eq!(curve.fall_interval(19), Interval::Milliseconds(33));
+        assert_eq!(curve.fall_interval(29), Interval::Milliseconds(17));
+        assert_eq!(curve.fall_interval(99), Interval::Milliseconds(17));
+    }
+
+    /// The guideline curve should follow the guideline gravity formula.
+    #[test]
+    fn the_guideline_curve_should_follow_the_guideline_formula() {
+        let curve = GravityCurve::guideline();
+
+        assert_eq!(curve.fall_interval(0), Interval::Milliseconds(1000));
+        assert_eq!(curve.fall_interval(1), Interval::Milliseconds(793));
+        assert_eq!(curve.fall_interval(4), Interval::Milliseconds(355));
+        assert_eq!(curve.fall_interval(99), Interval::Milliseconds(1));
+    }
+
+    /// Blocks should never fall more slowly at a higher level.
+    #[test]
+    fn the_preset_curves_should_never_slow_down() {
+        for kind in [GravityCurveKind::Nes, GravityCurveKind::Guideline].iter() {
+            let curve = kind.create();
+            for level in 0..40 {
+                assert!(
+                    millis(curve.fall_interval(level + 1)) <= millis(curve.fall_interval(level)),
+                    "{} gravity slows down at level {}", kind, level + 1
+                );
+            }
+        }
+    }
+
+    /// Gravity curves should be selectable by name.
+    #[test]
+    fn gravity_curves_should_be_parsed_from_their_names() {
+        assert_eq!("nes".parse::<GravityCurveKind>(), Ok(GravityCurveKind::Nes));
+        assert_eq!("guideline".parse::<GravityCurveKind>(), Ok(GravityCurveKind::Guideline));
+        assert!("tgm".parse::<GravityCurveKind>().is_err());
+    }
+}
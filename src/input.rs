@@ -28,8 +28,14 @@ pub enum InputKind {
     Right,
     Down,
     Exit,
-    Rotate,
+    RotateClockwise,
+    RotateCounterClockwise,
+    Hold,
+    HardDrop,
+    SonicDrop,
     StartGame,
+    ReturnToTitle,
+    Pause,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -0,0 +1,47 @@
+/*
+ *  Googly Blocks is a video game.
+ *  Copyright (C) 2018,2019,2020  Christopher Blanchard
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! The game logic for Googly Blocks.
+//!
+//! This crate contains everything needed to run a game of Googly Blocks
+//! without a window or a graphics context. The OpenGL frontend lives in the
+//! `googly-blocks` binary, which is built with the `frontend-gl` feature.
+extern crate rand;
+extern crate log;
+extern crate toml;
+
+
+pub mod auto_shift;
+pub mod block;
+pub mod input;
+pub mod flashing_state_machine;
+pub mod game_mode;
+pub mod garbage;
+pub mod gravity;
+pub mod lock_delay;
+pub mod piece_set;
+pub mod playing_field;
+pub mod playing_field_state_machine;
+pub mod timer;
+pub mod next_block;
+pub mod randomizer;
+pub mod rotation;
+pub mod score;
+pub mod title_screen_state_machine;
+pub mod simulation;
+pub mod settings;
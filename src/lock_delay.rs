@@ -0,0 +1,262 @@
+/*
+ *  Googly Blocks is a video game.
+ *  Copyright (C) 2018,2019,2020  Christopher Blanchard
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use crate::timer::{
+    Interval,
+    Timer,
+};
+
+use std::fmt;
+use std::str;
+use std::time::Duration;
+
+
+/// The number of times moving or rotating a block can restart the lock delay
+/// under the move reset policy before the block locks regardless.
+pub const MAX_MOVE_RESETS: usize = 15;
+
+/// The rules for when the lock delay of a block starts over.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LockDelayKind {
+    /// The lock delay never starts over. A block gets one lock delay for as long
+    /// as it rests on the stack.
+    Classic,
+    /// The lock delay starts over each time the block falls below the lowest row
+    /// it has reached.
+    StepReset,
+    /// The lock delay starts over each time the block moves or rotates, up to
+    /// `MAX_MOVE_RESETS` times, and each time the block falls below the lowest
+    /// row it has reached, which also refills the resets.
+    MoveReset,
+}
+
+impl fmt::Display for LockDelayKind {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let disp = match *self {
+            LockDelayKind::Classic => "classic",
+            LockDelayKind::StepReset => "step",
+            LockDelayKind::MoveReset => "move",
+        };
+        write!(formatter, "{}", disp)
+    }
+}
+
+impl str::FromStr for LockDelayKind {
+    type Err = String;
+
+    fn from_str(st: &str) -> Result<LockDelayKind, String> {
+        match st {
+            "classic" => Ok(LockDelayKind::Classic),
+            "step" => Ok(LockDelayKind::StepReset),
+            "move" => Ok(LockDelayKind::MoveReset),
+            _ => Err(format!("Unknown lock delay: {}", st)),
+        }
+    }
+}
+
+/// The lock delay is the time a block can rest on the floor or on the stack
+/// before it locks into place. The lock delay only runs while the block is
+/// resting on something.
+pub struct LockDelay {
+    kind: LockDelayKind,
+    timer: Timer,
+    /// Whether the block was resting on something at the last update.
+    grounded: bool,
+    /// The lowest row the top of the block has reached.
+    lowest_row: Option<isize>,
+    /// The number of times moving or rotating the block has restarted the lock delay.
+    move_resets: usize,
+}
+
+impl LockDelay {
+    /// Construct a new lock delay.
+    pub fn new(kind: LockDelayKind, interval: Interval) -> LockDelay {
+        LockDelay {
+            kind: kind,
+            timer: Timer::new(interval),
+            grounded: false,
+            lowest_row: None,
+            move_resets: 0,
+        }
+    }
+
+    /// The lock delay policy.
+    #[inline]
+    pub fn kind(&self) -> LockDelayKind {
+        self.kind
+    }
+
+    /// The number of times moving or rotating the current block has restarted the
+    /// lock delay.
+    #[inline]
+    pub fn move_resets(&self) -> usize {
+        self.move_resets
+    }
+
+    /// Update the lock delay with the current block's row and whether it rests
+    /// on the floor or on the stack.
+    pub fn update(&mut self, elapsed: Duration, row: isize, grounded: bool) {
+        let stepped_down = match self.lowest_row {
+            Some(lowest_row) => row > lowest_row,
+            None => true,
+        };
+        if stepped_down {
+            self.lowest_row = Some(row);
+            if self.kind != LockDelayKind::Classic {
+                self.timer.reset();
+                self.move_resets = 0;
+            }
+        }
+
+        self.grounded = grounded;
+        if grounded {
+            self.timer.update(elapsed);
+        }
+    }
+
+    /// Tell the lock delay the player moved or rotated the current block.
+    pub fn moved(&mut self) {
+        if self.kind == LockDelayKind::MoveReset && self.grounded && self.move_resets < MAX_MOVE_RESETS {
+            self.timer.reset();
+            self.move_resets += 1;
+        }
+    }
+
+    /// Determine whether the current block should lock into place.
+    #[inline]
+    pub fn is_expired(&self) -> bool {
+        self.grounded && self.timer.event_triggered()
+    }
+
+    /// Start the lock delay over for a new block.
+    pub fn reset(&mut self) {
+        self.timer.reset();
+        self.grounded = false;
+        self.lowest_row = None;
+        self.move_resets = 0;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        LockDelay,
+        LockDelayKind,
+        MAX_MOVE_RESETS,
+    };
+    use crate::timer::{
+        Interval,
+    };
+
+    use std::time::Duration;
+
+
+    const FRAME: Duration = Duration::from_millis(100);
+
+    fn lock_delay(kind: LockDelayKind) -> LockDelay {
+        LockDelay::new(kind, Interval::Milliseconds(500))
+    }
+
+
+    /// The lock delay should not run while the block is falling.
+    #[test]
+    fn the_lock_delay_should_only_run_while_the_block_is_grounded() {
+        let mut lock_delay = lock_delay(LockDelayKind::Classic);
+        for _ in 0..10 {
+            lock_delay.update(FRAME, 5, false);
+        }
+        assert!(!lock_delay.is_expired());
+
+        for _ in 0..5 {
+            lock_delay.update(FRAME, 5, true);
+        }
+        assert!(lock_delay.is_expired());
+    }
+
+    /// Under the classic policy, neither moving nor falling should restart the lock delay.
+    #[test]
+    fn the_classic_lock_delay_should_never_reset() {
+        let mut lock_delay = lock_delay(LockDelayKind::Classic);
+        for _ in 0..3 {
+            lock_delay.update(FRAME, 5, true);
+            lock_delay.moved();
+        }
+        lock_delay.update(FRAME, 6, true);
+        lock_delay.update(FRAME, 7, true);
+
+        assert!(lock_delay.is_expired());
+    }
+
+    /// Under the step reset policy, only reaching a new lowest row should restart the lock delay.
+    #[test]
+    fn the_step_reset_lock_delay_should_reset_when_the_block_falls() {
+        let mut lock_delay = lock_delay(LockDelayKind::StepReset);
+        for _ in 0..4 {
+            lock_delay.update(FRAME, 5, true);
+            lock_delay.moved();
+        }
+        lock_delay.update(FRAME, 6, true);
+        assert!(!lock_delay.is_expired());
+
+        for _ in 0..4 {
+            lock_delay.update(FRAME, 6, true);
+        }
+        assert!(lock_delay.is_expired());
+    }
+
+    /// Under the move reset policy, moving should restart the lock delay until the
+    /// block runs out of resets.
+    #[test]
+    fn the_move_reset_lock_delay_should_stop_resetting_after_the_limit() {
+        let mut lock_delay = lock_delay(LockDelayKind::MoveReset);
+        for _ in 0..MAX_MOVE_RESETS {
+            lock_delay.update(FRAME, 5, true);
+            lock_delay.moved();
+            assert!(!lock_delay.is_expired());
+        }
+        assert_eq!(lock_delay.move_resets(), MAX_MOVE_RESETS);
+
+        for _ in 0..5 {
+            lock_delay.update(FRAME, 5, true);
+            lock_delay.moved();
+        }
+        assert!(lock_delay.is_expired());
+    }
+
+    /// Reaching a new lowest row should refill the move resets.
+    #[test]
+    fn falling_to_a_new_row_should_refill_the_move_resets() {
+        let mut lock_delay = lock_delay(LockDelayKind::MoveReset);
+        for _ in 0..MAX_MOVE_RESETS {
+            lock_delay.update(FRAME, 5, true);
+            lock_delay.moved();
+        }
+        lock_delay.update(FRAME, 6, true);
+
+        assert_eq!(lock_delay.move_resets(), 0);
+    }
+
+    /// Lock delay policies should be selectable by name.
+    #[test]
+    fn lock_delays_should_be_parsed_from_their_names() {
+        assert_eq!("classic".parse::<LockDelayKind>(), Ok(LockDelayKind::Classic));
+        assert_eq!("step".parse::<LockDelayKind>(), Ok(LockDelayKind::StepReset));
+        assert_eq!("move".parse::<LockDelayKind>(), Ok(LockDelayKind::MoveReset));
+        assert!("infinite".parse::<LockDelayKind>().is_err());
+    }
+}
@@ -18,11 +18,10 @@
 extern crate glfw;
 extern crate bmfa;
 extern crate cglinalg;
-extern crate toml;
 extern crate log;
-extern crate rand;
 extern crate file_logger;
 extern crate tex_atlas;
+extern crate googly_blocks;
 
 
 mod gl {
@@ -31,17 +30,8 @@ mod gl {
 
 #[macro_use]
 mod macros;
-mod block;
-mod input;
-mod flashing_state_machine;
 mod mesh;
 mod gl_backend;
-mod playing_field;
-mod playing_field_state_machine;
-mod timer;
-mod next_block;
-mod score;
-mod title_screen_state_machine;
 
 use gl_backend as glb;
 
@@ -71,53 +61,82 @@ use mesh::{
 use tex_atlas::{
     TextureAtlas2D
 };
-use block::{
+use googly_blocks::auto_shift::{
+    AutoShiftSpec,
+};
+use googly_blocks::block::{
     GooglyBlock, 
     GooglyBlockPiece, 
     GooglyBlockElement, 
+    GooglyBlockRotation,
 };
-use input::{
+use googly_blocks::input::{
     Input,
     InputAction,
     InputKind,
 };
-use flashing_state_machine::{
+use googly_blocks::flashing_state_machine::{
     FlashAnimationState,
     FlashAnimationStateMachine,
     FlashAnimationStateMachineSpec,
 };
-use playing_field::{
-    BlockPosition, 
+use googly_blocks::game_mode::{
+    GameMode,
+    GameModeOutcome,
+    HudField,
+};
+use googly_blocks::garbage::{
+    GarbageGen,
+};
+use googly_blocks::piece_set::{
+    PieceDefinition,
+    PieceSet,
+};
+use googly_blocks::playing_field;
+use googly_blocks::playing_field::{
     PlayingFieldContext,
     PlayingFieldContextSpec,
 };
-use playing_field_state_machine::{
+use googly_blocks::playing_field_state_machine::{
     PlayingFieldTimerSpec,
     PlayingFieldStateMachineSpec,
     PlayingFieldStateMachine,
 };
-use next_block::{
+use googly_blocks::next_block::{
     NextBlockCell
 };
-use score::{
+use googly_blocks::score::{
+    CustomScoringSystem,
+    LineClear,
     ScoreBoard,
+    ScoringSystemKind,
     Statistics,
 };
-use timer::{
+use googly_blocks::timer::{
     Interval,
 };
-use title_screen_state_machine::{
+use googly_blocks::title_screen_state_machine::{
     TitleScreenStateMachine,
     TitleScreenStateMachineSpec,
 };
+use googly_blocks::settings::{
+    GameSettings,
+};
+use googly_blocks::{
+    flashing_state_machine,
+    playing_field_state_machine,
+};
 
+use std::env;
 use std::io;
 use std::mem;
+use std::process;
 use std::ptr;
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::time::Duration;
 use std::collections::hash_map::HashMap;
+use std::collections::HashSet;
 
 
 // OpenGL extension constants.
@@ -132,6 +151,8 @@ const TEXT_COLOR: [f32; 4] = [
 const CLEAR_COLOR: [f32; 4] = [0.2_f32, 0.2_f32, 0.2_f32, 1.0_f32];
 // Default value for the depth buffer.
 const CLEAR_DEPTH: [f32; 4] = [1.0_f32, 1.0_f32, 1.0_f32, 1.0_f32];
+// The number of lines the player clears to go up a level.
+const LINES_PER_LEVEL: usize = 20;
 
 
 /// Load texture image into the GPU.
@@ -1051,18 +1072,14 @@ struct PieceMeshes {
     i: ObjMesh,
 }
 
-fn generate_texture_coords_block(atlas: &TextureAtlas2D, name: &str) -> Vec<[f32; 2]> {
+fn generate_texture_coords_block(atlas: &TextureAtlas2D, name: &str, blocks: usize) -> Vec<[f32; 2]> {
     let corners: tex_atlas::BoundingBoxCornersTexCoords = atlas.by_texture_name_corners_uv(name).unwrap();
     let top_left = [corners.top_left.u, corners.top_left.v];
     let bottom_left = [corners.bottom_left.u, corners.bottom_left.v];
     let top_right = [corners.top_right.u, corners.top_right.v];
     let bottom_right = [corners.bottom_right.u, corners.bottom_right.v];
-    let tex_coords: Vec<[f32; 2]> = vec![
-        bottom_left, top_right, top_left, bottom_left, bottom_right, top_right,
-        bottom_left, top_right, top_left, bottom_left, bottom_right, top_right,
-        bottom_left, top_right, top_left, bottom_left, bottom_right, top_right,
-        bottom_left, top_right, top_left, bottom_left, bottom_right, top_right,
-    ];
+    let block_tex_coords = [bottom_left, top_right, top_left, bottom_left, bottom_right, top_right];
+    let tex_coords: Vec<[f32; 2]> = (0..blocks).flat_map(|_| block_tex_coords.iter().cloned()).collect();
 
     tex_coords
 }
@@ -1078,7 +1095,7 @@ fn create_geometry_t_piece(atlas: &TextureAtlas2D) -> ObjMesh {
         [ 0.5, 0.5], [1.0, 1.0], [ 0.5, 1.0],
         [ 0.5, 0.5], [1.0, 0.5], [ 1.0, 1.0],        
     ];
-    let tex_coords = generate_texture_coords_block(atlas, "t_piece");
+    let tex_coords = generate_texture_coords_block(atlas, "t_piece", 4);
 
     ObjMesh::new(points, tex_coords)
 }
@@ -1094,7 +1111,7 @@ fn create_geometry_j_piece(atlas: &TextureAtlas2D) -> ObjMesh {
         [ 0.5, 0.0], [1.0, 0.5], [ 0.5, 0.5],
         [ 0.5, 0.0], [1.0, 0.0], [ 1.0, 0.5],       
     ];
-    let tex_coords = generate_texture_coords_block(atlas, "j_piece");
+    let tex_coords = generate_texture_coords_block(atlas, "j_piece", 4);
 
     ObjMesh::new(points, tex_coords)
 }
@@ -1110,7 +1127,7 @@ fn create_geometry_z_piece(atlas: &TextureAtlas2D) -> ObjMesh {
         [ 0.5, 0.0], [1.0, 0.5], [ 0.5, 0.5],
         [ 0.5, 0.0], [1.0, 0.0], [ 1.0, 0.5],
     ];
-    let tex_coords = generate_texture_coords_block(atlas, "z_piece"); 
+    let tex_coords = generate_texture_coords_block(atlas, "z_piece", 4); 
 
     ObjMesh::new(points, tex_coords)
 }
@@ -1126,7 +1143,7 @@ fn create_geometry_o_piece(atlas: &TextureAtlas2D) -> ObjMesh {
         [0.5, 0.0], [1.0, 0.5], [0.5, 0.5],
         [0.5, 0.0], [1.0, 0.0], [1.0, 0.5],        
     ];
-    let tex_coords = generate_texture_coords_block(atlas, "o_piece");
+    let tex_coords = generate_texture_coords_block(atlas, "o_piece", 4);
 
     ObjMesh::new(points, tex_coords)
 }
@@ -1142,7 +1159,7 @@ fn create_geometry_s_piece(atlas: &TextureAtlas2D) -> ObjMesh {
         [ 0.5, 0.5], [1.0, 1.0], [ 0.5, 1.0],
         [ 0.5, 0.5], [1.0, 0.5], [ 1.0, 1.0],        
     ];
-    let tex_coords = generate_texture_coords_block(atlas, "s_piece");
+    let tex_coords = generate_texture_coords_block(atlas, "s_piece", 4);
 
     ObjMesh::new(points, tex_coords)
 }
@@ -1158,7 +1175,7 @@ fn create_geometry_l_piece(atlas: &TextureAtlas2D) -> ObjMesh {
         [ 0.5, 0.0], [1.0, 0.5], [ 0.5, 0.5],
         [ 0.5, 0.0], [1.0, 0.0], [ 1.0, 0.5],        
     ];
-    let tex_coords = generate_texture_coords_block(atlas, "l_piece");
+    let tex_coords = generate_texture_coords_block(atlas, "l_piece", 4);
 
     ObjMesh::new(points, tex_coords)
 }
@@ -1174,11 +1191,52 @@ fn create_geometry_i_piece(atlas: &TextureAtlas2D) -> ObjMesh {
         [ 0.5, 0.0], [ 1.0, 0.5], [ 0.5, 0.5],
         [ 0.5, 0.0], [ 1.0, 0.0], [ 1.0, 0.5],        
     ];
-    let tex_coords = generate_texture_coords_block(atlas, "i_piece");
+    let tex_coords = generate_texture_coords_block(atlas, "i_piece", 4);
 
     ObjMesh::new(points, tex_coords)
 }
 
+/// Generate the mesh for a piece from a custom piece set in its spawn state. Each
+/// block is half a unit wide, and the piece is centered the same way the
+/// tetromino meshes are.
+fn create_geometry_custom_piece(atlas: &TextureAtlas2D, definition: &PieceDefinition) -> ObjMesh {
+    let shape = definition.shape(GooglyBlockRotation::R0);
+    let min_row = shape.iter().map(|(row, _)| row).min().unwrap_or(0);
+    let max_row = shape.iter().map(|(row, _)| row).max().unwrap_or(0);
+    let min_column = shape.iter().map(|(_, column)| column).min().unwrap_or(0);
+    let max_column = shape.iter().map(|(_, column)| column).max().unwrap_or(0);
+    let width = (max_column - min_column + 1) as f32;
+    let height = (max_row - min_row + 1) as f32;
+    let mut points: Vec<[f32; 2]> = vec![];
+    for (row, column) in shape.iter() {
+        let left = 0.25 - 0.25 * width + 0.5 * (column - min_column) as f32;
+        let top = 0.5 + 0.25 * height - 0.5 * (row - min_row) as f32;
+        let right = left + 0.5;
+        let bottom = top - 0.5;
+        points.push([left, bottom]);
+        points.push([right, top]);
+        points.push([left, top]);
+        points.push([left, bottom]);
+        points.push([right, bottom]);
+        points.push([right, top]);
+    }
+    let tex_coords = generate_texture_coords_block(atlas, &definition.texture, shape.cell_count());
+
+    ObjMesh::new(points, tex_coords)
+}
+
+/// Determine whether a piece looks like one of the seven tetrominoes, so it can
+/// be drawn with that tetromino's mesh.
+fn is_standard_piece(standard_piece_set: &PieceSet, definition: &PieceDefinition) -> bool {
+    match standard_piece_set.definition(definition.piece) {
+        Some(standard) => {
+            standard.texture == definition.texture &&
+                standard.shape(GooglyBlockRotation::R0) == definition.shape(GooglyBlockRotation::R0)
+        }
+        None => false,
+    }
+}
+
 fn create_block_texture_atlas() -> TextureAtlas2D {
     let source = include_asset!("block_textures.atlas");
     let multi_atlas = tex_atlas::load_from_memory(source).unwrap().multi_atlas;
@@ -1330,24 +1388,58 @@ struct PieceUniformsData {
     trans_mat: Matrix4<f32>,
 }
 
-fn create_uniforms_next_piece_panel(
-    block: GooglyBlock, scale: u32, viewport_width: u32, viewport_height: u32) -> PieceUniformsData {
+/// The size in pixels of a block in the given slot of the next piece panel. The
+/// first upcoming piece is drawn at full size, and the rest of the queue is drawn
+/// at half size.
+fn next_piece_panel_scale(slot: usize) -> u32 {
+    if slot == 0 { 50 } else { 25 }
+}
+
+/// The offset that centers a piece mesh in a piece box drawn at full size.
+fn piece_panel_offset(piece: GooglyBlockPiece) -> (f32, f32) {
+    // FIXME: MAGIC NUMBERS IN USE HERE.
+    match piece {
+        GooglyBlockPiece::T => (0.0,    0.0),
+        GooglyBlockPiece::J => (0.0,    0.0),
+        GooglyBlockPiece::Z => (0.0,    0.0),
+        GooglyBlockPiece::O => (-0.025, 0.0),
+        GooglyBlockPiece::S => (0.0,    0.0),
+        GooglyBlockPiece::L => (0.0,    0.0),
+        GooglyBlockPiece::I => (0.03,   0.05),
+        GooglyBlockPiece::Custom(_) => (0.0, 0.0),
+    }
+}
+
+/// The position of the given slot of the next piece panel. The first upcoming
+/// piece sits in the next piece box, and the rest of the queue is stacked 
+/// vertically beside the UI panel.
+fn next_piece_panel_translation(piece: GooglyBlockPiece, slot: usize) -> Vector3<f32> {
+    // FIXME: MAGIC NUMBERS IN USE HERE.
+    let (offset_x, offset_y) = piece_panel_offset(piece);
+    if slot == 0 {
+        Vector3::new(0.525 + offset_x, 0.43 + offset_y, 0.0)
+    } else {
+        let y = 0.75 - 0.22 * ((slot - 1) as f32);
+        Vector3::new(0.80 + 0.5 * offset_x, y + 0.5 * offset_y, 0.0)
+    }
+}
 
+/// The position of the held piece, to the left of the UI panel.
+fn hold_piece_panel_translation(piece: GooglyBlockPiece) -> Vector3<f32> {
     // FIXME: MAGIC NUMBERS IN USE HERE.
+    let (offset_x, offset_y) = piece_panel_offset(piece);
+    Vector3::new(-0.92 + 0.5 * offset_x, 0.62 + 0.5 * offset_y, 0.0)
+}
+
+fn create_uniforms_piece(
+    scale: u32, translation: Vector3<f32>, viewport_width: u32, viewport_height: u32) -> PieceUniformsData {
+
     let block_width = 2.0 * (scale as f32 / viewport_width as f32);
     let block_height = 2.0 * (scale as f32 / viewport_height as f32);
     let gui_scale_mat = Matrix4::from_affine_nonuniform_scale(
         block_width, block_height, 1.0
     );
-    let trans_mat = match block.piece {
-        GooglyBlockPiece::T => Matrix4::from_affine_translation(&Vector3::new(0.525, 0.43, 0.0)),
-        GooglyBlockPiece::J => Matrix4::from_affine_translation(&Vector3::new(0.525, 0.43, 0.0)),
-        GooglyBlockPiece::Z => Matrix4::from_affine_translation(&Vector3::new(0.525, 0.43, 0.0)),
-        GooglyBlockPiece::O => Matrix4::from_affine_translation(&Vector3::new(0.50,  0.43, 0.0)),
-        GooglyBlockPiece::S => Matrix4::from_affine_translation(&Vector3::new(0.525, 0.43, 0.0)),
-        GooglyBlockPiece::L => Matrix4::from_affine_translation(&Vector3::new(0.525, 0.43, 0.0)),
-        GooglyBlockPiece::I => Matrix4::from_affine_translation(&Vector3::new(0.555, 0.48, 0.0)),
-    };
+    let trans_mat = Matrix4::from_affine_translation(&translation);
 
     PieceUniformsData {
         gui_scale_mat: gui_scale_mat,
@@ -1355,6 +1447,23 @@ fn create_uniforms_next_piece_panel(
     }
 }
 
+fn create_uniforms_next_piece_panel(
+    block: GooglyBlock, slot: usize, viewport_width: u32, viewport_height: u32) -> PieceUniformsData {
+
+    let scale = next_piece_panel_scale(slot);
+    let translation = next_piece_panel_translation(block.piece, slot);
+
+    create_uniforms_piece(scale, translation, viewport_width, viewport_height)
+}
+
+fn create_uniforms_hold_piece_panel(
+    block: GooglyBlock, viewport_width: u32, viewport_height: u32) -> PieceUniformsData {
+
+    let translation = hold_piece_panel_translation(block.piece);
+
+    create_uniforms_piece(25, translation, viewport_width, viewport_height)
+}
+
 fn send_to_gpu_piece_uniforms(sp: GLuint, uniforms: &PieceUniformsData) {
     let gui_scale_mat_loc = unsafe {
         gl::GetUniformLocation(sp, glb::gl_str("m_gui_scale").as_ptr())
@@ -1390,10 +1499,16 @@ struct GLNextPiecePanel {
     s_handle: NextPiecePanelHandle,
     l_handle: NextPiecePanelHandle,
     i_handle: NextPiecePanelHandle,
+    /// The meshes for pieces that do not look like one of the seven tetrominoes.
+    custom_handles: HashMap<GooglyBlockPiece, NextPiecePanelHandle>,
+    custom_vertex_counts: HashMap<GooglyBlockPiece, GLint>,
 }
 
 impl GLNextPiecePanel {
     fn handle(&self, piece: GooglyBlockPiece) -> NextPiecePanelHandle {
+        if let Some(handle) = self.custom_handles.get(&piece) {
+            return *handle;
+        }
         match piece {
             GooglyBlockPiece::T => self.t_handle, 
             GooglyBlockPiece::J => self.j_handle,
@@ -1402,16 +1517,40 @@ impl GLNextPiecePanel {
             GooglyBlockPiece::S => self.s_handle,
             GooglyBlockPiece::L => self.l_handle,
             GooglyBlockPiece::I => self.i_handle,
+            GooglyBlockPiece::Custom(_) => panic!("No mesh was generated for the piece {:?}.", piece),
+        }
+    }
+
+    /// The number of vertices in the mesh of a piece.
+    fn vertex_count(&self, piece: GooglyBlockPiece) -> GLint {
+        match self.custom_vertex_counts.get(&piece) {
+            Some(vertex_count) => *vertex_count,
+            None => 3 * 8,
         }
     }
 }
 
-fn create_next_piece_panel_buffer(gl_context: &mut glb::GLState, atlas: &TextureAtlas2D, uniforms: &PieceUniformsData) -> GLNextPiecePanel {
+fn create_next_piece_panel_buffer(
+    gl_context: &mut glb::GLState, 
+    atlas: &TextureAtlas2D, piece_set: &PieceSet, uniforms: &PieceUniformsData) -> GLNextPiecePanel {
+
     let shader_source = create_shaders_next_piece_panel();
     let sp = send_to_gpu_shaders_next_piece_panel(gl_context, shader_source);
     let tex = send_to_gpu_textures_next_piece_panel(atlas);
     let meshes = create_geometry_next_piece_panel(atlas);
     let handles = send_to_gpu_geometry_next_panel(sp, &meshes);
+    let standard_piece_set = PieceSet::standard();
+    let mut custom_handles = HashMap::new();
+    let mut custom_vertex_counts = HashMap::new();
+    for definition in piece_set.definitions().iter() {
+        if !is_standard_piece(&standard_piece_set, definition) {
+            let mesh = create_geometry_custom_piece(atlas, definition);
+            let handle = create_buffers_geometry_piece_mesh(sp);
+            send_to_gpu_geometry_piece_mesh(handle, &mesh);
+            custom_handles.insert(definition.piece, handle);
+            custom_vertex_counts.insert(definition.piece, mesh.len() as GLint);
+        }
+    }
     send_to_gpu_uniforms_next_piece_panel(sp, uniforms);
 
     GLNextPiecePanel {
@@ -1424,32 +1563,114 @@ fn create_next_piece_panel_buffer(gl_context: &mut glb::GLState, atlas: &Texture
         s_handle: handles.s,
         l_handle: handles.l,
         i_handle: handles.i,
+        custom_handles: custom_handles,
+        custom_vertex_counts: custom_vertex_counts,
     }
 }
 
 struct NextPiecePanel {
-    current_block: GooglyBlock,
+    blocks: Vec<GooglyBlock>,
     buffer: GLNextPiecePanel,
 }
 
 impl NextPiecePanel {
-    fn update(&mut self, block: GooglyBlock) {
-        self.current_block = block;
+    fn update(&mut self, blocks: Vec<GooglyBlock>) {
+        self.blocks = blocks;
     }
 }
 
 struct NextPiecePanelSpec<'a> {
-    block: GooglyBlock,
+    blocks: Vec<GooglyBlock>,
     atlas: &'a TextureAtlas2D,
+    piece_set: &'a PieceSet,
 }
 
 fn load_next_piece_panel(
     game: &mut glb::GLState,
     spec: NextPiecePanelSpec, uniforms: &PieceUniformsData) -> NextPiecePanel {
     
-    let buffer = create_next_piece_panel_buffer(game, spec.atlas, uniforms);
+    let buffer = create_next_piece_panel_buffer(game, spec.atlas, spec.piece_set, uniforms);
     NextPiecePanel {
-        current_block: spec.block,
+        blocks: spec.blocks,
+        buffer: buffer,
+    }
+}
+
+/// The hold panel shows the block the player has put aside. It draws with the 
+/// next piece panel's meshes.
+struct HoldPiecePanel {
+    block: Option<GooglyBlock>,
+    label: TextBuffer,
+}
+
+impl HoldPiecePanel {
+    fn update(&mut self, block: Option<GooglyBlock>) {
+        self.block = block;
+    }
+}
+
+fn load_hold_piece_panel(mut label: TextBuffer) -> HoldPiecePanel {
+    let placement = AbsolutePlacement { x: -0.97, y: 0.80 };
+    label.write(b"HOLD", placement).unwrap();
+    label.send_to_gpu().unwrap();
+
+    HoldPiecePanel {
+        block: None,
+        label: label,
+    }
+}
+
+/// The clear message panel names the last line clear or T-spin the player made.
+struct ClearMessagePanel {
+    lines: Vec<String>,
+    buffer: TextBuffer,
+}
+
+impl ClearMessagePanel {
+    fn update(&mut self, lines: Vec<String>) {
+        if lines != self.lines {
+            self.buffer.clear();
+            for (i, line) in lines.iter().enumerate() {
+                let placement = AbsolutePlacement { x: -0.97, y: 0.40 - 0.07 * (i as f32) };
+                self.buffer.write(line.as_bytes(), placement).unwrap();
+            }
+            self.buffer.send_to_gpu().unwrap();
+            self.lines = lines;
+        }
+    }
+}
+
+fn load_clear_message_panel(buffer: TextBuffer) -> ClearMessagePanel {
+    ClearMessagePanel {
+        lines: vec![],
+        buffer: buffer,
+    }
+}
+
+/// The game mode panel shows the fields the game mode uses to track the
+/// player's progress toward its goal.
+struct GameModePanel {
+    fields: Vec<HudField>,
+    buffer: TextBuffer,
+}
+
+impl GameModePanel {
+    fn update(&mut self, fields: Vec<HudField>) {
+        if fields != self.fields {
+            self.buffer.clear();
+            for (i, field) in fields.iter().enumerate() {
+                let placement = AbsolutePlacement { x: -0.97, y: -0.60 - 0.07 * (i as f32) };
+                self.buffer.write(format!("{}", field).as_bytes(), placement).unwrap();
+            }
+            self.buffer.send_to_gpu().unwrap();
+            self.fields = fields;
+        }
+    }
+}
+
+fn load_game_mode_panel(buffer: TextBuffer) -> GameModePanel {
+    GameModePanel {
+        fields: vec![],
         buffer: buffer,
     }
 }
@@ -1908,10 +2129,13 @@ fn send_to_gpu_shaders_playing_field(game: &mut glb::GLState, source: ShaderSour
 
 fn create_geometry_playing_field(rows: usize, columns: usize) -> ObjMesh {
     let mut vertices: Vec<[GLfloat; 2]> = vec![];
-    let width = 0.1;
-    let height = 0.1;
-    let top_left_x = -0.5;
-    let top_left_y = 1.0;
+    // Size the cells so the playing field fits in the same box as the standard
+    // ten by twenty field, and centre the field in that box.
+    let cell_size = f32::min(1.0 / columns as f32, 2.0 / rows as f32);
+    let width = cell_size;
+    let height = cell_size;
+    let top_left_x = -0.5 * width * columns as f32;
+    let top_left_y = 0.5 * height * rows as f32;
     for row in 0..rows {
         for column in 0..columns {
             let row_f32 = row as f32;
@@ -1949,8 +2173,10 @@ struct PlayingFieldBuffers {
     vao: GLuint,
     v_pos_vbo: GLuint,
     v_tex_vbo: GLuint,
+    v_alpha_vbo: GLuint,
     v_pos_loc: GLuint,
     v_tex_loc: GLuint,
+    v_alpha_loc: GLuint,
 }
 
 fn create_buffers_geometry_playing_field(sp: GLuint) -> PlayingFieldBuffers {
@@ -1966,6 +2192,12 @@ fn create_buffers_geometry_playing_field(sp: GLuint) -> PlayingFieldBuffers {
     debug_assert!(v_tex_loc > -1);
     let v_tex_loc = v_tex_loc as GLuint;
 
+    let v_alpha_loc = unsafe {
+        gl::GetAttribLocation(sp, glb::gl_str("v_alpha").as_ptr())
+    };
+    debug_assert!(v_alpha_loc > -1);
+    let v_alpha_loc = v_alpha_loc as GLuint;
+
     let mut v_pos_vbo = 0;
     unsafe {
         gl::GenBuffers(1, &mut v_pos_vbo);
@@ -1978,6 +2210,12 @@ fn create_buffers_geometry_playing_field(sp: GLuint) -> PlayingFieldBuffers {
     }
     debug_assert!(v_tex_vbo > 0);
 
+    let mut v_alpha_vbo = 0;
+    unsafe {
+        gl::GenBuffers(1, &mut v_alpha_vbo);
+    }
+    debug_assert!(v_alpha_vbo > 0);
+
     let mut vao = 0;
     unsafe {
         gl::GenVertexArrays(1, &mut vao);
@@ -1990,16 +2228,21 @@ fn create_buffers_geometry_playing_field(sp: GLuint) -> PlayingFieldBuffers {
         gl::VertexAttribPointer(v_pos_loc, 2, gl::FLOAT, gl::FALSE, 0, ptr::null());
         gl::BindBuffer(gl::ARRAY_BUFFER, v_tex_vbo);
         gl::VertexAttribPointer(v_tex_loc, 2, gl::FLOAT, gl::FALSE, 0, ptr::null());
+        gl::BindBuffer(gl::ARRAY_BUFFER, v_alpha_vbo);
+        gl::VertexAttribPointer(v_alpha_loc, 1, gl::FLOAT, gl::FALSE, 0, ptr::null());
         gl::EnableVertexAttribArray(v_pos_loc);
         gl::EnableVertexAttribArray(v_tex_loc);
+        gl::EnableVertexAttribArray(v_alpha_loc);
     }
 
     PlayingFieldBuffers {
         vao: vao,
         v_pos_vbo: v_pos_vbo,
         v_tex_vbo: v_tex_vbo,
+        v_alpha_vbo: v_alpha_vbo,
         v_pos_loc: v_pos_loc,
         v_tex_loc: v_tex_loc,
+        v_alpha_loc: v_alpha_loc,
     }
 }
 
@@ -2024,8 +2267,11 @@ fn send_to_gpu_geometry_playing_field(handle: PlayingFieldBuffers, mesh: &ObjMes
         gl::VertexAttribPointer(handle.v_pos_loc, 2, gl::FLOAT, gl::FALSE, 0, ptr::null());
         gl::BindBuffer(gl::ARRAY_BUFFER, handle.v_tex_vbo);
         gl::VertexAttribPointer(handle.v_tex_loc, 2, gl::FLOAT, gl::FALSE, 0, ptr::null());
+        gl::BindBuffer(gl::ARRAY_BUFFER, handle.v_alpha_vbo);
+        gl::VertexAttribPointer(handle.v_alpha_loc, 1, gl::FLOAT, gl::FALSE, 0, ptr::null());
         gl::EnableVertexAttribArray(handle.v_pos_loc);
         gl::EnableVertexAttribArray(handle.v_tex_loc);
+        gl::EnableVertexAttribArray(handle.v_alpha_loc);
     }
 }
 
@@ -2053,8 +2299,8 @@ fn generate_quad(atlas: &TextureAtlas2D, name: &str) -> TextureQuad {
     TextureQuad::new(top_left, bottom_left, bottom_right, top_right)
 }
 
-fn create_textures_playing_field(atlas: &TextureAtlas2D) -> GooglyBlockElementTextureAtlas {
-    let tex_coords = [
+fn create_textures_playing_field(atlas: &TextureAtlas2D, piece_set: &PieceSet) -> GooglyBlockElementTextureAtlas {
+    let mut tex_coords: HashMap<GooglyBlockElement, TextureQuad> = [
         (GooglyBlockElement::EmptySpace, generate_quad(atlas, "empty_space")),
         (GooglyBlockElement::T, generate_quad(atlas, "t_piece")),
         (GooglyBlockElement::J, generate_quad(atlas, "j_piece")),
@@ -2062,8 +2308,12 @@ fn create_textures_playing_field(atlas: &TextureAtlas2D) -> GooglyBlockElementTe
         (GooglyBlockElement::O, generate_quad(atlas, "o_piece")),
         (GooglyBlockElement::S, generate_quad(atlas, "s_piece")),
         (GooglyBlockElement::L, generate_quad(atlas, "l_piece")),
-        (GooglyBlockElement::I, generate_quad(atlas, "i_piece"))
+        (GooglyBlockElement::I, generate_quad(atlas, "i_piece")),
+        (GooglyBlockElement::Garbage, generate_quad(atlas, "z_piece"))
     ].iter().map(|elem| *elem).collect();
+    for definition in piece_set.definitions().iter() {
+        tex_coords.insert(definition.piece.element(), generate_quad(atlas, &definition.texture));
+    }
     GooglyBlockElementTextureAtlas::new(atlas.clone(), tex_coords)
 }
 
@@ -2142,21 +2392,30 @@ struct PlayingFieldHandle {
     vao: GLuint,
     v_pos_vbo: GLuint,
     v_tex_vbo: GLuint,
+    v_alpha_vbo: GLuint,
     tex: GLuint,
     v_pos_loc: GLuint,
     v_tex_loc: GLuint,
+    v_alpha_loc: GLuint,
 }
 
 impl PlayingFieldHandle {
-    fn write(&mut self, tex_coords: &[[TextureQuad; 10]; 20]) -> io::Result<usize> {
+    fn write(&mut self, tex_coords: &[TextureQuad], alphas: &[QuadAlpha]) -> io::Result<usize> {
         unsafe {
             gl::BindBuffer(gl::ARRAY_BUFFER, self.v_tex_vbo);
             gl::BufferData(
                 gl::ARRAY_BUFFER,
-                (mem::size_of::<TextureQuad>() * tex_coords[0].len() * tex_coords.len()) as GLsizeiptr,
+                (mem::size_of::<TextureQuad>() * tex_coords.len()) as GLsizeiptr,
                 tex_coords.as_ptr() as *const GLvoid,
                 gl::DYNAMIC_DRAW
             );
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.v_alpha_vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (mem::size_of::<QuadAlpha>() * alphas.len()) as GLsizeiptr,
+                alphas.as_ptr() as *const GLvoid,
+                gl::DYNAMIC_DRAW
+            );
         }
         let bytes_written = mem::size_of::<GLfloat>() * tex_coords.len();
         
@@ -2178,19 +2437,53 @@ impl TextureQuad {
     }
 }
 
+/// The opacity of each vertex of a cell in the playing field.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct QuadAlpha {
+    inner: [f32; 6],
+}
+
+impl QuadAlpha {
+    #[inline]
+    fn new(alpha: f32) -> QuadAlpha {
+        QuadAlpha {
+            inner: [alpha; 6],
+        }
+    }
+}
+
+/// The opacity of the ghost piece showing where the current block will land.
+const GHOST_PIECE_ALPHA: f32 = 0.3;
+/// The opacity of garbage, which sets it apart from the blocks the player placed.
+const GARBAGE_ALPHA: f32 = 0.5;
+
 struct PlayingField {
-    tex_coords: [[TextureQuad; 10]; 20],
+    rows: usize,
+    columns: usize,
+    /// The texture coordinates of each cell, one row after another.
+    tex_coords: Vec<TextureQuad>,
+    /// The opacity of each cell, one row after another.
+    alphas: Vec<QuadAlpha>,
     handle: PlayingFieldHandle,
     atlas: HashMap<GooglyBlockElement, TextureQuad>,
+    show_ghost_piece: bool,
 }
 
 impl PlayingField {
-    fn new(handle: PlayingFieldHandle, atlas: &GooglyBlockElementTextureAtlas) -> PlayingField {
+    fn new(
+        handle: PlayingFieldHandle, 
+        atlas: &GooglyBlockElementTextureAtlas, 
+        rows: usize, columns: usize, show_ghost_piece: bool) -> PlayingField {
+        
         let quad = TextureQuad::new([0_f32, 0_f32], [0_f32, 0_f32], [0_f32, 0_f32], [0_f32, 0_f32]);
         PlayingField {
-            tex_coords: [[quad; 10]; 20],
+            rows: rows,
+            columns: columns,
+            tex_coords: vec![quad; rows * columns],
+            alphas: vec![QuadAlpha::new(1.0); rows * columns],
             handle: handle,
             atlas: atlas.coords.clone(),
+            show_ghost_piece: show_ghost_piece,
         }
     }
 
@@ -2201,19 +2494,34 @@ impl PlayingField {
             for column in 0..columns {
                 let element = playing_field.landed_blocks.get(row as isize, column as isize).unwrap();
                 let quad = self.atlas[&element];
-                self.tex_coords[row][column] = quad;
+                let alpha = if element == GooglyBlockElement::Garbage { GARBAGE_ALPHA } else { 1.0 };
+                self.tex_coords[row * columns + column] = quad;
+                self.alphas[row * columns + column] = QuadAlpha::new(alpha);
             } 
         }
 
-        let shape = playing_field.current_block.shape();
+        let shape = playing_field.current_shape();
+        let quad = self.atlas[&shape.element];
+        if self.show_ghost_piece {
+            let landing_position = playing_field.landing_position();
+            for (shape_row, shape_column) in shape.iter() {
+                let row = landing_position.row + shape_row as isize;
+                let column = landing_position.column + shape_column as isize;
+                if let Some(index) = self.index(row, column) {
+                    self.tex_coords[index] = quad;
+                    self.alphas[index] = QuadAlpha::new(GHOST_PIECE_ALPHA);
+                }
+            }
+        }
+
         let top_left_row = playing_field.current_position.row;
         let top_left_column = playing_field.current_position.column;
-        let quad = self.atlas[&shape.element];
         for (shape_row, shape_column) in shape.iter() {
             let row = top_left_row + shape_row as isize;
             let column = top_left_column + shape_column as isize;
-            if row >= 0 && column >= 0 {
-                self.tex_coords[row as usize][column as usize] = quad;
+            if let Some(index) = self.index(row, column) {
+                self.tex_coords[index] = quad;
+                self.alphas[index] = QuadAlpha::new(1.0);
             }
         }
 
@@ -2223,11 +2531,26 @@ impl PlayingField {
     }
 
     fn send_to_gpu(&mut self) -> io::Result<usize> {
-        self.handle.write(&self.tex_coords)?;
+        self.handle.write(&self.tex_coords, &self.alphas)?;
         let tex_coords_written = 6 * self.tex_coords.len();
 
         Ok(tex_coords_written)
     }
+
+    /// The index of a playing field cell in the buffers, if the cell is visible.
+    fn index(&self, row: isize, column: isize) -> Option<usize> {
+        if row >= 0 && (row as usize) < self.rows && column >= 0 && (column as usize) < self.columns {
+            Some(row as usize * self.columns + column as usize)
+        } else {
+            None
+        }
+    }
+
+    /// The number of vertices in the playing field geometry.
+    #[inline]
+    fn vertex_count(&self) -> usize {
+        6 * self.rows * self.columns
+    }
 }
 
 fn load_playing_field(game: &mut glb::GLState, spec: PlayingFieldHandleSpec, uniforms: PlayingFieldUniforms) -> PlayingFieldHandle {
@@ -2244,9 +2567,11 @@ fn load_playing_field(game: &mut glb::GLState, spec: PlayingFieldHandleSpec, uni
         vao: handle.vao,
         v_pos_vbo: handle.v_pos_vbo,
         v_tex_vbo: handle.v_tex_vbo,
+        v_alpha_vbo: handle.v_alpha_vbo,
         tex: tex,
         v_pos_loc: handle.v_pos_loc,
         v_tex_loc: handle.v_tex_loc,
+        v_alpha_loc: handle.v_alpha_loc,
     }
 }
 
@@ -2376,6 +2701,12 @@ impl TextBuffer {
         Ok((st.len(), point_count))
     }
 
+    /// The number of vertices currently written to the buffer.
+    #[inline]
+    fn vertex_count(&self) -> i32 {
+        (self.points.len() / 2) as i32
+    }
+
     fn send_to_gpu(&mut self) -> io::Result<(usize, usize)> {
         self.buffer.write(&self.points, &self.tex_coords)?;
         let points_written = self.points.len();
@@ -2397,6 +2728,8 @@ struct TextPanelSpec {
     lines_placement: AbsolutePlacement,
     level_placement: AbsolutePlacement,
     tetrises_placement: AbsolutePlacement,
+    combo_placement: AbsolutePlacement,
+    back_to_back_placement: AbsolutePlacement,
     t_placement: AbsolutePlacement,
     j_placement: AbsolutePlacement,
     z_placement: AbsolutePlacement,
@@ -2457,12 +2790,29 @@ impl TextElement4 {
     }
 }
 
+/// A text element showing whether a switch is on or off.
+struct TextElementSwitch {
+    content: [u8; 3],
+    placement: AbsolutePlacement,
+}
+
+impl TextElementSwitch {
+    #[inline]
+    fn write(&mut self, value: bool) {
+        self.content = if value { *b" ON" } else { *b"OFF" };
+    }
+}
+
 struct TextPanel {
     buffer: TextBuffer,
+    /// The labels of the values the UI panel texture has no label for.
+    labels: TextBuffer,
     score: TextElement7,
     level: TextElement4,
     tetrises: TextElement4,
     lines: TextElement4,
+    combo: TextElement4,
+    back_to_back: TextElementSwitch,
     t_pieces: TextElement4,
     j_pieces: TextElement4,
     z_pieces: TextElement4,
@@ -2479,6 +2829,8 @@ impl TextPanel {
         self.buffer.write(&self.level.content, self.level.placement).unwrap();
         self.buffer.write(&self.tetrises.content, self.tetrises.placement).unwrap();
         self.buffer.write(&self.lines.content, self.lines.placement).unwrap();
+        self.buffer.write(&self.combo.content, self.combo.placement).unwrap();
+        self.buffer.write(&self.back_to_back.content, self.back_to_back.placement).unwrap();
         self.buffer.write(&self.t_pieces.content, self.t_pieces.placement).unwrap();
         self.buffer.write(&self.j_pieces.content, self.j_pieces.placement).unwrap();
         self.buffer.write(&self.z_pieces.content, self.z_pieces.placement).unwrap();
@@ -2505,6 +2857,14 @@ impl TextPanel {
         self.tetrises.write(tetrises);
     }
 
+    fn update_combo(&mut self, combo: usize) {
+        self.combo.write(combo);
+    }
+
+    fn update_back_to_back(&mut self, back_to_back: bool) {
+        self.back_to_back.write(back_to_back);
+    }
+
     fn update_t_pieces(&mut self, t_pieces: usize) {
         self.t_pieces.write(t_pieces);
     }
@@ -2656,11 +3016,21 @@ fn create_text_buffer(
 }
 
 fn load_text_panel(gl_state: Rc<RefCell<glb::GLState>>, spec: &TextPanelSpec, uniforms: TextPanelUniforms) -> TextPanel {
-    let buffer = create_text_buffer(gl_state, spec.atlas.clone(), spec.scale_px, uniforms);
+    let buffer = create_text_buffer(gl_state.clone(), spec.atlas.clone(), spec.scale_px, uniforms);
+    let mut labels = create_text_buffer(gl_state, spec.atlas.clone(), spec.scale_px / 2.0, uniforms);
+    let combo_label_placement = AbsolutePlacement { x: spec.combo_placement.x, y: spec.combo_placement.y + 0.10 };
+    labels.write(b"COMBO", combo_label_placement).unwrap();
+    let back_to_back_label_placement = AbsolutePlacement { 
+        x: spec.back_to_back_placement.x, y: spec.back_to_back_placement.y + 0.10
+    };
+    labels.write(b"BACK-TO-BACK", back_to_back_label_placement).unwrap();
+    labels.send_to_gpu().unwrap();
     let score = TextElement7 { content: [0; 7], placement: spec.score_placement };
     let lines =  TextElement4 { content: [0; 4], placement: spec.lines_placement };
     let level =  TextElement4 { content: [0; 4], placement: spec.level_placement };
     let tetrises = TextElement4 { content: [0; 4], placement: spec.tetrises_placement };
+    let combo = TextElement4 { content: [0; 4], placement: spec.combo_placement };
+    let back_to_back = TextElementSwitch { content: [0; 3], placement: spec.back_to_back_placement };
     let t_pieces = TextElement4 { content: [0; 4], placement: spec.t_placement };
     let j_pieces = TextElement4 { content: [0; 4], placement: spec.j_placement };
     let z_pieces = TextElement4 { content: [0; 4], placement: spec.z_placement };
@@ -2671,10 +3041,13 @@ fn load_text_panel(gl_state: Rc<RefCell<glb::GLState>>, spec: &TextPanelSpec, un
 
     TextPanel {
         buffer: buffer,
+        labels: labels,
         score: score,
         level: level,
         tetrises: tetrises,
         lines: lines,
+        combo: combo,
+        back_to_back: back_to_back,
         t_pieces: t_pieces,
         j_pieces: j_pieces,
         z_pieces: z_pieces,
@@ -2729,6 +3102,10 @@ struct UI {
     ui_panel: UIPanel,
     text_panel: TextPanel,
     next_piece_panel: NextPiecePanel,
+    hold_piece_panel: HoldPiecePanel,
+    clear_message_panel: ClearMessagePanel,
+    game_mode_panel: GameModePanel,
+    perfect_clear_panel: TextBuffer,
 }
 
 impl UI {
@@ -2752,12 +3129,39 @@ impl UI {
         self.text_panel.update_tetrises(tetrises);
     }
 
+    fn update_combo(&mut self, combo: usize) {
+        self.text_panel.update_combo(combo);
+    }
+
+    fn update_back_to_back(&mut self, back_to_back: bool) {
+        self.text_panel.update_back_to_back(back_to_back);
+    }
+
     fn update_statistics(&mut self, statistics: &Statistics) {
         self.text_panel.update_statistics(statistics);
     }
 
-    fn update_next_piece(&mut self, block: GooglyBlock) {
-        self.next_piece_panel.update(block);
+    fn update_next_pieces(&mut self, blocks: Vec<GooglyBlock>) {
+        self.next_piece_panel.update(blocks);
+    }
+
+    fn update_hold_piece(&mut self, block: Option<GooglyBlock>) {
+        self.hold_piece_panel.update(block);
+    }
+
+    fn update_clear_message(&mut self, last_clear: Option<LineClear>) {
+        let mut lines = vec![];
+        if let Some(last_clear) = last_clear {
+            lines.push(format!("{}", last_clear));
+            if last_clear.perfect_clear {
+                lines.push(String::from("PERFECT CLEAR"));
+            }
+        }
+        self.clear_message_panel.update(lines);
+    }
+
+    fn update_game_mode(&mut self, fields: Vec<HudField>) {
+        self.game_mode_panel.update(fields);
     }
 }
 
@@ -2782,12 +3186,19 @@ impl GameTitleScreenState {
                     _ => {}
                 }
             }
+            InputKind::Left => {
+                title_screen.select_previous_level(input.action, elapsed_milliseconds);
+            }
+            InputKind::Right => {
+                title_screen.select_next_level(input.action, elapsed_milliseconds);
+            }
             _ => {}
         }
     }
 
     fn update(&self, context: &mut GameContext, elapsed_milliseconds: Duration) -> GameState {
-        let mut title_screen = context.title_screen.borrow_mut();
+        let title_screen_ref = context.title_screen.clone();
+        let mut title_screen = title_screen_ref.borrow_mut();
         if title_screen.blink_state.is_disabled() {
             title_screen.blink_state.enable();
         }
@@ -2796,6 +3207,7 @@ impl GameTitleScreenState {
             title_screen.transition_timer.update(elapsed_milliseconds);
             if title_screen.transition_timer.event_triggered() {
                 title_screen.blink_state.disable();
+                context.start_game(title_screen.starting_level());
                 return GameState::Playing(GamePlayingState::new());
             }
         }
@@ -2819,6 +3231,11 @@ impl GamePlayingState {
                 let mut exiting = context.exiting.borrow_mut();
                 *exiting = true;
             }
+            InputKind::Pause => {
+                if input.action == InputAction::Press {
+                    context.paused = true;
+                }
+            }
             _ => {
                 let playing_field_state_machine = context.playing_field_state_machine.borrow_mut();
                 playing_field_state_machine.handle_input(input, elapsed_milliseconds);
@@ -2831,9 +3248,36 @@ impl GamePlayingState {
         if exiting {
             return GameState::Exiting(GameExitingState::new());
         }
+        // The game pauses itself when the player switches to another window.
+        if !context.gl.borrow().window.is_focused() {
+            context.paused = true;
+        }
+        if context.paused {
+            return GameState::Paused(GamePausedState::new());
+        }
 
         let mut playing_field_state_machine = context.playing_field_state_machine.borrow_mut();
         playing_field_state_machine.update(elapsed_milliseconds);
+        let mut game_mode = context.game_mode.borrow_mut();
+        game_mode.update(
+            elapsed_milliseconds, 
+            &context.score_board.borrow(), 
+            &context.playing_field_state.borrow().landed_blocks, 
+            playing_field_state_machine.top_out()
+        );
+        let garbage = game_mode.take_garbage();
+        if garbage > 0 {
+            playing_field_state_machine.add_garbage(&context.garbage_gen.holes(garbage));
+        }
+        match game_mode.outcome() {
+            Some(GameModeOutcome::Victory) => {
+                return GameState::Victory(GameVictoryState::new());
+            }
+            Some(GameModeOutcome::Defeat) => {
+                return GameState::GameOver(GameGameOverState::new());
+            }
+            None => {}
+        }
         if playing_field_state_machine.is_game_over() {
             return GameState::GameOver(GameGameOverState::new());
         }
@@ -2842,6 +3286,47 @@ impl GamePlayingState {
     }    
 }
 
+/// The paused state freezes the game. None of the playing field timers, the
+/// flash animations, or the game mode clock advance until the player resumes.
+#[derive(Copy, Clone)]
+struct GamePausedState {}
+
+impl GamePausedState {
+    fn new() -> GamePausedState {
+        GamePausedState {}
+    }
+
+    fn handle_input(&self, context: &mut GameContext, input: Input, _elapsed_milliseconds: Duration) {
+        match input.kind {
+            InputKind::Exit => {
+                let mut exiting = context.exiting.borrow_mut();
+                *exiting = true;
+            }
+            InputKind::Pause => {
+                if input.action == InputAction::Press {
+                    context.paused = false;
+                }
+            }
+            _ => context.handle_restart_input(input),
+        }
+    }
+
+    fn update(&self, context: &mut GameContext, _elapsed_milliseconds: Duration) -> GameState {
+        let exiting = *context.exiting.borrow();
+        if exiting {
+            return GameState::Exiting(GameExitingState::new());
+        }
+        if let Some(state) = context.restart() {
+            return state;
+        }
+        if !context.paused {
+            return GameState::Playing(GamePlayingState::new());
+        }
+
+        GameState::Paused(*self)
+    }
+}
+
 #[derive(Copy, Clone)]
 struct GameGameOverState {}
 
@@ -2856,21 +3341,58 @@ impl GameGameOverState {
                 let mut exiting = context.exiting.borrow_mut();
                 *exiting = true;
             }
-            _ => {}
+            _ => context.handle_restart_input(input),
         }
     }
 
     fn update(&self, context: &mut GameContext, elapsed_milliseconds: Duration) -> GameState {
         let exiting = *context.exiting.borrow();
         if exiting {
-            GameState::Exiting(GameExitingState::new())
-        } else {
-            let mut flashing_state_machine = context.flashing_state_machine.borrow_mut();
-            flashing_state_machine.disable();
+            return GameState::Exiting(GameExitingState::new());
+        }
+        if let Some(state) = context.restart() {
+            return state;
+        }
 
-            GameState::GameOver(*self)
+        let mut flashing_state_machine = context.flashing_state_machine.borrow_mut();
+        flashing_state_machine.disable();
+
+        GameState::GameOver(*self)
+    }
+}
+
+#[derive(Copy, Clone)]
+struct GameVictoryState {}
+
+impl GameVictoryState {
+    fn new() -> GameVictoryState {
+        GameVictoryState {}
+    }
+
+    fn handle_input(&self, context: &mut GameContext, input: Input, _elapsed_milliseconds: Duration) {
+        match input.kind {
+            InputKind::Exit => {
+                let mut exiting = context.exiting.borrow_mut();
+                *exiting = true;
+            }
+            _ => context.handle_restart_input(input),
         }
     }
+
+    fn update(&self, context: &mut GameContext, _elapsed_milliseconds: Duration) -> GameState {
+        let exiting = *context.exiting.borrow();
+        if exiting {
+            return GameState::Exiting(GameExitingState::new());
+        }
+        if let Some(state) = context.restart() {
+            return state;
+        }
+
+        let mut flashing_state_machine = context.flashing_state_machine.borrow_mut();
+        flashing_state_machine.disable();
+
+        GameState::Victory(*self)
+    }
 }
 
 
@@ -2898,7 +3420,9 @@ impl GameExitingState {
 enum GameState {
     TitleScreen(GameTitleScreenState),
     Playing(GamePlayingState),
+    Paused(GamePausedState),
     GameOver(GameGameOverState),
+    Victory(GameVictoryState),
     Exiting(GameExitingState),
 }
 
@@ -2920,7 +3444,9 @@ impl GameStateMachine {
         match self.state {
             GameState::TitleScreen(s) => s.handle_input(&mut context, input, elapsed_milliseconds),
             GameState::Playing(s) => s.handle_input(&mut context, input, elapsed_milliseconds),
+            GameState::Paused(s) => s.handle_input(&mut context, input, elapsed_milliseconds),
             GameState::GameOver(s) => s.handle_input(&mut context, input, elapsed_milliseconds),
+            GameState::Victory(s) => s.handle_input(&mut context, input, elapsed_milliseconds),
             GameState::Exiting(s) => s.handle_input(&mut context, input, elapsed_milliseconds),
         }
     }
@@ -2930,7 +3456,9 @@ impl GameStateMachine {
         self.state = match self.state {
             GameState::TitleScreen(s) => s.update(&mut context, elapsed_milliseconds),
             GameState::Playing(s) => s.update(&mut context, elapsed_milliseconds),
+            GameState::Paused(s) => s.update(&mut context, elapsed_milliseconds),
             GameState::GameOver(s) => s.update(&mut context, elapsed_milliseconds),
+            GameState::Victory(s) => s.update(&mut context, elapsed_milliseconds),
             GameState::Exiting(s) => s.update(&mut context, elapsed_milliseconds),
         };
 
@@ -2939,6 +3467,15 @@ impl GameStateMachine {
 }
 
 
+/// What the player chose to do after a game ends.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Restart {
+    /// Play again right away with the same settings and starting level.
+    Retry,
+    /// Go back to the title screen.
+    TitleScreen,
+}
+
 struct GameContext {
     gl: Rc<RefCell<glb::GLState>>,
     playing_field_state: Rc<RefCell<PlayingFieldContext>>,
@@ -2947,8 +3484,102 @@ struct GameContext {
     statistics: Rc<RefCell<Statistics>>,
     score_board: Rc<RefCell<ScoreBoard>>,
     flashing_state_machine: Rc<RefCell<FlashAnimationStateMachine>>,
+    perfect_clear_flashing_state_machine: Rc<RefCell<FlashAnimationStateMachine>>,
+    game_mode: Rc<RefCell<Box<dyn GameMode>>>,
+    garbage_gen: GarbageGen,
     exiting: Rc<RefCell<bool>>,
     title_screen: Rc<RefCell<TitleScreenStateMachine>>,
+    settings: GameSettings,
+    piece_set: Rc<PieceSet>,
+    /// The level the last game started at.
+    starting_level: usize,
+    restart: Option<Restart>,
+    paused: bool,
+}
+
+impl GameContext {
+    /// Set up a fresh game starting at the given level. Everything left over from
+    /// the last game is thrown away: the playing field, the next block queue, the
+    /// statistics, the score board, the game mode, the flash animations, and the 
+    /// playing field state machine with all of its timers.
+    fn start_game(&mut self, starting_level: usize) {
+        self.starting_level = starting_level;
+        self.paused = false;
+        let mut next_block_cell = create_next_block_cell(&self.settings, &self.piece_set);
+        let starting_block = next_block_cell.current_block();
+        next_block_cell.update();
+        self.garbage_gen = match next_block_cell.seed() {
+            Some(seed) => GarbageGen::from_seed(seed, self.settings.columns),
+            None => GarbageGen::new(self.settings.columns),
+        };
+        let playing_field_context_spec = PlayingFieldContextSpec {
+            starting_block: starting_block,
+            starting_positions: playing_field::starting_positions(&self.piece_set, self.settings.columns),
+            piece_set: self.piece_set.clone(),
+            rotation_system: self.settings.rotation_system,
+            rows: self.settings.rows,
+            columns: self.settings.columns,
+        };
+        *self.playing_field_state.borrow_mut() = PlayingFieldContext::new(playing_field_context_spec);
+        *self.next_block.borrow_mut() = next_block_cell;
+        *self.statistics.borrow_mut() = Statistics::new();
+        self.flashing_state_machine.borrow_mut().disable();
+        self.perfect_clear_flashing_state_machine.borrow_mut().disable();
+
+        let game_mode = self.settings.game_mode.create();
+        let holes = self.garbage_gen.holes(game_mode.starting_garbage());
+        self.playing_field_state.borrow_mut().landed_blocks.push_garbage_rows(&holes);
+        *self.game_mode.borrow_mut() = game_mode;
+        *self.score_board.borrow_mut() = ScoreBoard::new(
+            LINES_PER_LEVEL, starting_level, self.settings.scoring_system.create()
+        );
+        let playing_field_state_machine_spec = PlayingFieldStateMachineSpec {
+            timers: create_timer_spec_playing_field(&self.settings),
+            flashing_state_machine: self.flashing_state_machine.clone(),
+            perfect_clear_flashing_state_machine: self.perfect_clear_flashing_state_machine.clone(),
+            playing_field_context: self.playing_field_state.clone(),
+            next_block: self.next_block.clone(),
+            statistics: self.statistics.clone(),
+            score_board: self.score_board.clone(),
+        };
+        *self.playing_field_state_machine.borrow_mut() = playing_field_state_machine::create(
+            playing_field_state_machine_spec
+        );
+    }
+
+    /// Listen for the player choosing to play again once a game has ended, or
+    /// while the game is paused.
+    fn handle_restart_input(&mut self, input: Input) {
+        if input.action != InputAction::Press {
+            return;
+        }
+        match input.kind {
+            InputKind::StartGame => {
+                self.restart = Some(Restart::Retry);
+            }
+            InputKind::ReturnToTitle => {
+                self.restart = Some(Restart::TitleScreen);
+            }
+            _ => {}
+        }
+    }
+
+    /// Start over if the player chose to play again, returning the state the game
+    /// moves to.
+    fn restart(&mut self) -> Option<GameState> {
+        match self.restart.take() {
+            Some(Restart::Retry) => {
+                let starting_level = self.starting_level;
+                self.start_game(starting_level);
+                Some(GameState::Playing(GamePlayingState::new()))
+            }
+            Some(Restart::TitleScreen) => {
+                self.title_screen.borrow_mut().reset();
+                Some(GameState::TitleScreen(GameTitleScreenState::new()))
+            }
+            None => None,
+        }
+    }
 }
 
 struct RendererContext {
@@ -2959,6 +3590,8 @@ struct RendererContext {
     background: BackgroundPanel,
     playing_field_background: PlayingFieldBackgroundPanel,
     game_over: GameOverPanel,
+    message_panel: TextBuffer,
+    pause_panel: TextBuffer,
 }
 
 impl RendererContext {
@@ -3034,47 +3667,98 @@ impl RendererContext {
         send_to_gpu_uniforms_ui_panel(self.ui.ui_panel.sp, uniforms);
     }
 
-    fn update_uniforms_next_piece_panel(&mut self) {
+    fn update_uniforms_next_piece_panel(&mut self, block: GooglyBlock, slot: usize) {
         let (viewport_width, viewport_height) = self.get_framebuffer_size();
-        let scale = 50;
-        // FIXME: MAGIC NUMBERS IN USE.
-        let gui_scale_x = 2.0 * (scale as f32) / (viewport_width as f32);
-        let gui_scale_y = 2.0 * (scale as f32) / (viewport_height as f32);
-        let gui_scale_mat = Matrix4::from_affine_nonuniform_scale(
-            gui_scale_x, 
-            gui_scale_y, 
-            1.0
+        let uniforms = create_uniforms_next_piece_panel(
+            block, slot, viewport_width as u32, viewport_height as u32
         );
-        let trans_mat = match self.game_context.borrow().next_block.borrow().current_block().piece {
-            GooglyBlockPiece::T => Matrix4::from_affine_translation(
-                &Vector3::new(0.525, 0.43, 0.0)
-            ),
-            GooglyBlockPiece::J => Matrix4::from_affine_translation(
-                &Vector3::new(0.525, 0.43, 0.0)
-            ),
-            GooglyBlockPiece::Z => Matrix4::from_affine_translation(
-                &Vector3::new(0.525, 0.43, 0.0)
-            ),
-            GooglyBlockPiece::O => Matrix4::from_affine_translation(
-                &Vector3::new(0.50,  0.43, 0.0)
-            ),
-            GooglyBlockPiece::S => Matrix4::from_affine_translation(
-                &Vector3::new(0.525, 0.43, 0.0)
-            ),
-            GooglyBlockPiece::L => Matrix4::from_affine_translation(
-                &Vector3::new(0.525, 0.43, 0.0)
-            ),
-            GooglyBlockPiece::I => Matrix4::from_affine_translation(
-                &Vector3::new(0.555, 0.48, 0.0)
-            ),
-        };
-        let uniforms = PieceUniformsData { 
-            gui_scale_mat: gui_scale_mat, 
-            trans_mat: trans_mat 
-        };
         send_to_gpu_uniforms_next_piece_panel(self.ui.next_piece_panel.buffer.sp, &uniforms);
     }
 
+    fn render_next_piece_panel(&mut self) {
+        let blocks = self.ui.next_piece_panel.blocks.clone();
+        for (slot, block) in blocks.iter().enumerate() {
+            self.update_uniforms_next_piece_panel(*block, slot);
+            unsafe {
+                gl::UseProgram(self.ui.next_piece_panel.buffer.sp);
+                gl::Disable(gl::DEPTH_TEST);
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, self.ui.next_piece_panel.buffer.tex);
+                gl::BindVertexArray(self.ui.next_piece_panel.buffer.handle(block.piece).vao);
+                gl::DrawArrays(gl::TRIANGLES, 0, self.ui.next_piece_panel.buffer.vertex_count(block.piece));
+            }
+        }
+    }
+
+    fn render_clear_message_panel(&mut self) {
+        unsafe {
+            gl::UseProgram(self.ui.clear_message_panel.buffer.buffer.sp);
+            gl::Disable(gl::DEPTH_TEST);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, self.ui.clear_message_panel.buffer.buffer.tex);
+            gl::BindVertexArray(self.ui.clear_message_panel.buffer.buffer.vao);
+            gl::DrawArrays(gl::TRIANGLES, 0, self.ui.clear_message_panel.buffer.vertex_count());
+        }
+    }
+
+    fn render_game_mode_panel(&mut self) {
+        unsafe {
+            gl::UseProgram(self.ui.game_mode_panel.buffer.buffer.sp);
+            gl::Disable(gl::DEPTH_TEST);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, self.ui.game_mode_panel.buffer.buffer.tex);
+            gl::BindVertexArray(self.ui.game_mode_panel.buffer.buffer.vao);
+            gl::DrawArrays(gl::TRIANGLES, 0, self.ui.game_mode_panel.buffer.vertex_count());
+        }
+    }
+
+    fn render_perfect_clear_panel(&mut self) {
+        let is_flashing = {
+            let game_context = self.game_context.borrow();
+            let perfect_clear_flashing_state_machine = game_context.perfect_clear_flashing_state_machine.borrow();
+            perfect_clear_flashing_state_machine.state == FlashAnimationState::Light
+        };
+        if is_flashing {
+            unsafe {
+                gl::UseProgram(self.ui.perfect_clear_panel.buffer.sp);
+                gl::Disable(gl::DEPTH_TEST);
+                gl::Enable(gl::BLEND);
+                gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, self.ui.perfect_clear_panel.buffer.tex);
+                gl::BindVertexArray(self.ui.perfect_clear_panel.buffer.vao);
+                gl::DrawArrays(gl::TRIANGLES, 0, self.ui.perfect_clear_panel.vertex_count());
+                gl::Disable(gl::BLEND);
+            }
+        }
+    }
+
+    fn render_hold_piece_panel(&mut self) {
+        unsafe {
+            gl::UseProgram(self.ui.hold_piece_panel.label.buffer.sp);
+            gl::Disable(gl::DEPTH_TEST);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, self.ui.hold_piece_panel.label.buffer.tex);
+            gl::BindVertexArray(self.ui.hold_piece_panel.label.buffer.vao);
+            gl::DrawArrays(gl::TRIANGLES, 0, self.ui.hold_piece_panel.label.vertex_count());
+        }
+        if let Some(block) = self.ui.hold_piece_panel.block {
+            let (viewport_width, viewport_height) = self.get_framebuffer_size();
+            let uniforms = create_uniforms_hold_piece_panel(
+                block, viewport_width as u32, viewport_height as u32
+            );
+            send_to_gpu_uniforms_next_piece_panel(self.ui.next_piece_panel.buffer.sp, &uniforms);
+            unsafe {
+                gl::UseProgram(self.ui.next_piece_panel.buffer.sp);
+                gl::Disable(gl::DEPTH_TEST);
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, self.ui.next_piece_panel.buffer.tex);
+                gl::BindVertexArray(self.ui.next_piece_panel.buffer.handle(block.piece).vao);
+                gl::DrawArrays(gl::TRIANGLES, 0, self.ui.next_piece_panel.buffer.vertex_count(block.piece));
+            }
+        }
+    }
+
     fn update_uniforms_playing_field(&mut self) {
         let viewport = self.viewport_dimensions();
         let scale = 488;
@@ -3229,11 +3913,35 @@ impl RendererTitleScreenState {
         }
     }
 
+    fn update_level_select(&self, context: &mut RendererContext) {
+        let starting_level = context.game_context.borrow().title_screen.borrow().starting_level();
+        let level_text = format!("< LEVEL {} >", starting_level);
+        context.message_panel.clear();
+        context.message_panel.write(level_text.as_bytes(), AbsolutePlacement { x: -0.17, y: -0.52 }).unwrap();
+        context.message_panel.send_to_gpu().unwrap();
+    }
+
+    fn render_level_select(&self, context: &mut RendererContext) {
+        unsafe {
+            gl::UseProgram(context.message_panel.buffer.sp);
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, context.message_panel.buffer.tex);
+            gl::BindVertexArray(context.message_panel.buffer.vao);
+            gl::DrawArrays(gl::TRIANGLES, 0, context.message_panel.vertex_count());
+            gl::Disable(gl::BLEND);
+        }
+    }
+
     fn render(&self, context: &mut RendererContext) {
         self.update_uniforms_background(context);
         self.render_background(context);
         self.update_uniforms_start_prompt(context);
         self.render_start_prompt(context);
+        self.update_level_select(context);
+        self.render_level_select(context);
     }
 }
 
@@ -3296,19 +4004,38 @@ impl RendererPlayingState {
 
     fn update_ui(&self, context: &mut RendererContext) {
         context.update_uniforms_ui_panel();
-        context.update_uniforms_next_piece_panel();
         let game_context = context.game_context.borrow();
         let score_board = game_context.score_board.borrow();
         context.ui.update_score(score_board.score);
         context.ui.update_lines(score_board.lines);
         context.ui.update_level(score_board.level);
         context.ui.update_tetrises(score_board.tetrises);
+        context.ui.update_combo(score_board.combo);
+        context.ui.update_back_to_back(score_board.back_to_back);
+        context.ui.update_clear_message(score_board.last_clear);
+        context.ui.update_game_mode(game_context.game_mode.borrow().hud(&score_board));
         context.ui.update_statistics(&game_context.statistics.borrow());
-        context.ui.update_next_piece(game_context.next_block.borrow().current_block());
+        let next_block = game_context.next_block.borrow();
+        let next_blocks = (0..next_block.preview_count()).filter_map(|n| next_block.peek(n)).collect();
+        context.ui.update_next_pieces(next_blocks);
+        context.ui.update_hold_piece(game_context.playing_field_state.borrow().held_block);
         context.ui.update_panel();   
     }
 
     fn render_ui(&self, context: &mut RendererContext) {
+        self.render_ui_panel(context);
+        context.render_next_piece_panel();
+        context.render_hold_piece_panel();
+        context.render_clear_message_panel();
+        context.render_game_mode_panel();
+        unsafe {
+            gl::Disable(gl::BLEND);
+        }
+    }
+
+    /// Render the UI panel with the score and the statistics, leaving blending
+    /// on for the panels drawn over it.
+    fn render_ui_panel(&self, context: &mut RendererContext) {
         // Render the game board. We turn off depth testing to do so since this is
         // a 2D scene using 3D abstractions. Otherwise Z-Buffering would prevent us
         // from rendering the game board.
@@ -3327,19 +4054,10 @@ impl RendererPlayingState {
             gl::ActiveTexture(gl::TEXTURE0);
             gl::BindTexture(gl::TEXTURE_2D, context.ui.text_panel.buffer.buffer.tex);
             gl::BindVertexArray(context.ui.text_panel.buffer.buffer.vao);
-            gl::DrawArrays(gl::TRIANGLES, 0, 47 * 6);
+            gl::DrawArrays(gl::TRIANGLES, 0, context.ui.text_panel.buffer.vertex_count());
 
-            gl::UseProgram(context.ui.next_piece_panel.buffer.sp);
-            gl::Disable(gl::DEPTH_TEST);
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, context.ui.next_piece_panel.buffer.tex);
-            gl::BindVertexArray(
-                context.ui.next_piece_panel.buffer.handle(
-                    context.game_context.borrow().next_block.borrow().current_block().piece
-                ).vao
-            );
-            gl::DrawArrays(gl::TRIANGLES, 0, 3 * 8);
-            gl::Disable(gl::BLEND);
+            gl::BindVertexArray(context.ui.text_panel.labels.buffer.vao);
+            gl::DrawArrays(gl::TRIANGLES, 0, context.ui.text_panel.labels.vertex_count());
         }
     }
 
@@ -3360,7 +4078,7 @@ impl RendererPlayingState {
             gl::ActiveTexture(gl::TEXTURE0);
             gl::BindTexture(gl::TEXTURE_2D, context.playing_field.handle.tex);
             gl::BindVertexArray(context.playing_field.handle.vao);
-            gl::DrawArrays(gl::TRIANGLES, 0, 2 * 6 * 20 * 10);
+            gl::DrawArrays(gl::TRIANGLES, 0, context.playing_field.vertex_count() as GLint);
             gl::Disable(gl::BLEND);
         }        
     }
@@ -3374,8 +4092,14 @@ impl RendererPlayingState {
         // flashing state machine.
         let game_context = context.game_context.borrow();
         let flashing_state_machine = game_context.flashing_state_machine.borrow();
+        let perfect_clear_flashing_state_machine = game_context.perfect_clear_flashing_state_machine.borrow();
+        let flashing_state = if perfect_clear_flashing_state_machine.is_disabled() {
+            flashing_state_machine.state
+        } else {
+            perfect_clear_flashing_state_machine.state
+        };
         let flashing_state_handle = context.playing_field_background.handle;
-        let handle = match flashing_state_machine.state {
+        let handle = match flashing_state {
             FlashAnimationState::Light => flashing_state_handle.light,
             FlashAnimationState::Dark => flashing_state_handle.dark,
             FlashAnimationState::Disabled => flashing_state_handle.default,
@@ -3404,8 +4128,51 @@ impl RendererPlayingState {
         self.render_ui(context);
         self.update_playing_field(context);
         self.render_playing_field(context);
+        context.render_perfect_clear_panel();
     }
 }
+/// The pause screen hides the playing field and the upcoming pieces, so the
+/// player cannot pause to plan their next moves, and shows the pause menu over
+/// the empty playing field.
+#[derive(Copy, Clone)]
+struct RendererPausedState {}
+
+impl RendererPausedState {
+    fn render_pause_panel(&self, context: &mut RendererContext) {
+        unsafe {
+            gl::UseProgram(context.pause_panel.buffer.sp);
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, context.pause_panel.buffer.tex);
+            gl::BindVertexArray(context.pause_panel.buffer.vao);
+            gl::DrawArrays(gl::TRIANGLES, 0, context.pause_panel.vertex_count());
+            gl::Disable(gl::BLEND);
+        }
+    }
+
+    fn render(&self, context: &mut RendererContext) {
+        let playing = RendererPlayingState {};
+        playing.clear_framebuffer(context);
+        playing.clear_depth_buffer(context);
+        playing.update_viewport(context);
+        playing.update_background(context);
+        playing.render_background(context);
+        playing.update_title_background(context);
+        playing.render_title_background(context);
+        playing.update_playing_field_background(context);
+        playing.render_playing_field_background(context);
+        playing.update_ui(context);
+        playing.render_ui_panel(context);
+        context.render_game_mode_panel();
+        unsafe {
+            gl::Disable(gl::BLEND);
+        }
+        self.render_pause_panel(context);
+    }
+}
+
 #[derive(Copy, Clone)]
 struct RendererGameOverState {}
 
@@ -3464,15 +4231,21 @@ impl RendererGameOverState {
 
     fn update_ui(&self, context: &mut RendererContext) {
         context.update_uniforms_ui_panel();
-        context.update_uniforms_next_piece_panel();
         let game_context = context.game_context.borrow();
         let score_board = game_context.score_board.borrow();
         context.ui.update_score(score_board.score);
         context.ui.update_lines(score_board.lines);
         context.ui.update_level(score_board.level);
         context.ui.update_tetrises(score_board.tetrises);
+        context.ui.update_combo(score_board.combo);
+        context.ui.update_back_to_back(score_board.back_to_back);
+        context.ui.update_clear_message(score_board.last_clear);
+        context.ui.update_game_mode(game_context.game_mode.borrow().hud(&score_board));
         context.ui.update_statistics(&game_context.statistics.borrow());
-        context.ui.update_next_piece(game_context.next_block.borrow().current_block());
+        let next_block = game_context.next_block.borrow();
+        let next_blocks = (0..next_block.preview_count()).filter_map(|n| next_block.peek(n)).collect();
+        context.ui.update_next_pieces(next_blocks);
+        context.ui.update_hold_piece(game_context.playing_field_state.borrow().held_block);
         context.ui.update_panel();   
     }
 
@@ -3495,18 +4268,16 @@ impl RendererGameOverState {
             gl::ActiveTexture(gl::TEXTURE0);
             gl::BindTexture(gl::TEXTURE_2D, context.ui.text_panel.buffer.buffer.tex);
             gl::BindVertexArray(context.ui.text_panel.buffer.buffer.vao);
-            gl::DrawArrays(gl::TRIANGLES, 0, 47 * 6);
+            gl::DrawArrays(gl::TRIANGLES, 0, context.ui.text_panel.buffer.vertex_count());
 
-            gl::UseProgram(context.ui.next_piece_panel.buffer.sp);
-            gl::Disable(gl::DEPTH_TEST);
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, context.ui.next_piece_panel.buffer.tex);
-            gl::BindVertexArray(
-                context.ui.next_piece_panel.buffer.handle(
-                    context.game_context.borrow().next_block.borrow().current_block().piece
-                ).vao
-            );
-            gl::DrawArrays(gl::TRIANGLES, 0, 3 * 8);
+            gl::BindVertexArray(context.ui.text_panel.labels.buffer.vao);
+            gl::DrawArrays(gl::TRIANGLES, 0, context.ui.text_panel.labels.vertex_count());
+        }
+        context.render_next_piece_panel();
+        context.render_hold_piece_panel();
+        context.render_clear_message_panel();
+        context.render_game_mode_panel();
+        unsafe {
             gl::Disable(gl::BLEND);
         }
     }
@@ -3528,7 +4299,7 @@ impl RendererGameOverState {
             gl::ActiveTexture(gl::TEXTURE0);
             gl::BindTexture(gl::TEXTURE_2D, context.playing_field.handle.tex);
             gl::BindVertexArray(context.playing_field.handle.vao);
-            gl::DrawArrays(gl::TRIANGLES, 0, 2 * 6 * 20 * 10);
+            gl::DrawArrays(gl::TRIANGLES, 0, context.playing_field.vertex_count() as GLint);
             gl::Disable(gl::BLEND);
         }        
     }
@@ -3559,8 +4330,14 @@ impl RendererGameOverState {
         // flashing state machine.
         let game_context = context.game_context.borrow();
         let flashing_state_machine = game_context.flashing_state_machine.borrow();
+        let perfect_clear_flashing_state_machine = game_context.perfect_clear_flashing_state_machine.borrow();
+        let flashing_state = if perfect_clear_flashing_state_machine.is_disabled() {
+            flashing_state_machine.state
+        } else {
+            perfect_clear_flashing_state_machine.state
+        };
         let flashing_state_handle = context.playing_field_background.handle;
-        let handle = match flashing_state_machine.state {
+        let handle = match flashing_state {
             FlashAnimationState::Light => flashing_state_handle.light,
             FlashAnimationState::Dark => flashing_state_handle.dark,
             FlashAnimationState::Disabled => flashing_state_handle.default,
@@ -3576,6 +4353,42 @@ impl RendererGameOverState {
         }
     }
 
+    fn update_game_over_text(&self, context: &mut RendererContext) {
+        let seed = context.game_context.borrow().next_block.borrow().seed();
+        let max_combo = context.game_context.borrow().statistics.borrow().max_combo;
+        let top_out = context.game_context.borrow().playing_field_state_machine.borrow().top_out();
+        context.message_panel.clear();
+        if let Some(top_out) = top_out {
+            let top_out_text = format!("{}", top_out);
+            let placement = AbsolutePlacement { x: -0.22, y: -0.30 };
+            context.message_panel.write(top_out_text.as_bytes(), placement).unwrap();
+        }
+        if let Some(seed) = seed {
+            let seed_text = format!("SEED {}", seed);
+            let placement = AbsolutePlacement { x: -0.22, y: -0.40 };
+            context.message_panel.write(seed_text.as_bytes(), placement).unwrap();
+        }
+        let max_combo_text = format!("MAX COMBO {}", max_combo);
+        let placement = AbsolutePlacement { x: -0.22, y: -0.50 };
+        context.message_panel.write(max_combo_text.as_bytes(), placement).unwrap();
+        write_restart_text(context, -0.70);
+        context.message_panel.send_to_gpu().unwrap();
+    }
+
+    fn render_game_over_text(&self, context: &mut RendererContext) {
+        unsafe {
+            gl::UseProgram(context.message_panel.buffer.sp);
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, context.message_panel.buffer.tex);
+            gl::BindVertexArray(context.message_panel.buffer.vao);
+            gl::DrawArrays(gl::TRIANGLES, 0, context.message_panel.vertex_count());
+            gl::Disable(gl::BLEND);
+        }
+    }
+
     fn render(&self, context: &mut RendererContext) {
         self.clear_framebuffer(context);
         self.clear_depth_buffer(context);
@@ -3592,6 +4405,67 @@ impl RendererGameOverState {
         self.render_playing_field(context);
         self.update_game_over_panel(context);
         self.render_game_over_panel(context);
+        self.update_game_over_text(context);
+        self.render_game_over_text(context);
+    }
+}
+
+/// Tell the player how to play again once a game has ended.
+fn write_restart_text(context: &mut RendererContext, y: f32) {
+    let placement = AbsolutePlacement { x: -0.22, y: y };
+    context.message_panel.write(b"ENTER TO RETRY", placement).unwrap();
+    let placement = AbsolutePlacement { x: -0.22, y: y - 0.10 };
+    context.message_panel.write(b"BACKSPACE FOR TITLE", placement).unwrap();
+}
+
+/// The victory screen shows the final playing field without the game over
+/// banner, along with the player's results in the game mode.
+#[derive(Copy, Clone)]
+struct RendererVictoryState {}
+
+impl RendererVictoryState {
+    fn update_victory_text(&self, context: &mut RendererContext) {
+        let (mode_text, fields, seed) = {
+            let game_context = context.game_context.borrow();
+            let game_mode = game_context.game_mode.borrow();
+            let mode_text = format!("{} CLEAR", game_mode.kind()).to_uppercase();
+            let fields = game_mode.hud(&game_context.score_board.borrow());
+            let seed = game_context.next_block.borrow().seed();
+            (mode_text, fields, seed)
+        };
+        context.message_panel.clear();
+        let placement = AbsolutePlacement { x: -0.22, y: 0.10 };
+        context.message_panel.write(mode_text.as_bytes(), placement).unwrap();
+        for (i, field) in fields.iter().enumerate() {
+            let placement = AbsolutePlacement { x: -0.22, y: -0.10 * (i as f32) };
+            context.message_panel.write(format!("{}", field).as_bytes(), placement).unwrap();
+        }
+        if let Some(seed) = seed {
+            let seed_text = format!("SEED {}", seed);
+            let placement = AbsolutePlacement { x: -0.22, y: -0.10 * (fields.len() as f32) };
+            context.message_panel.write(seed_text.as_bytes(), placement).unwrap();
+        }
+        write_restart_text(context, -0.70);
+        context.message_panel.send_to_gpu().unwrap();
+    }
+
+    fn render(&self, context: &mut RendererContext) {
+        let game_over = RendererGameOverState {};
+        game_over.clear_framebuffer(context);
+        game_over.clear_depth_buffer(context);
+        game_over.update_viewport(context);
+        game_over.update_background(context);
+        game_over.render_background(context);
+        game_over.update_title_background(context);
+        game_over.render_title_background(context);
+        game_over.update_playing_field_background(context);
+        game_over.render_playing_field_background(context);
+        game_over.update_ui(context);
+        game_over.render_ui(context);
+        game_over.update_playing_field(context);
+        game_over.render_playing_field(context);
+        self.update_victory_text(context);
+        game_over.render_game_over_text(context);
     }
 }
 
@@ -3605,7 +4479,9 @@ impl RendererExitingState {
 enum RendererState {
     TitleScreen(RendererTitleScreenState),
     Playing(RendererPlayingState),
+    Paused(RendererPausedState),
     GameOver(RendererGameOverState),
+    Victory(RendererVictoryState),
     Exiting(RendererExitingState),
 }
 
@@ -3626,7 +4502,9 @@ impl RendererStateMachine {
         self.state = match game_state {
             GameState::TitleScreen(_) => RendererState::TitleScreen(RendererTitleScreenState {}),
             GameState::Playing(_) => RendererState::Playing(RendererPlayingState {}),
+            GameState::Paused(_) => RendererState::Paused(RendererPausedState {}),
             GameState::GameOver(_) => RendererState::GameOver(RendererGameOverState {}),
+            GameState::Victory(_) => RendererState::Victory(RendererVictoryState {}),
             GameState::Exiting(_) => RendererState::Exiting(RendererExitingState {}),
         }
     }
@@ -3635,7 +4513,9 @@ impl RendererStateMachine {
         match self.state {
             RendererState::TitleScreen(s) => s.render(&mut self.context),
             RendererState::Playing(s) => s.render(&mut self.context),
+            RendererState::Paused(s) => s.render(&mut self.context),
             RendererState::GameOver(s) => s.render(&mut self.context),
+            RendererState::Victory(s) => s.render(&mut self.context),
             RendererState::Exiting(s) => s.render(&mut self.context),
         }
     }
@@ -3647,10 +4527,46 @@ struct ViewportDimensions {
     height: i32,
 }
 
+/// The keyboard state keeps track of which keys were held down on the previous
+/// frame, so that a key that was just pressed can be told apart from a key that
+/// is being held down.
+struct KeyboardState {
+    held_keys: HashSet<Key>,
+}
+
+impl KeyboardState {
+    fn new() -> KeyboardState {
+        KeyboardState {
+            held_keys: HashSet::new(),
+        }
+    }
+
+    /// Determine the input action for a key from its state on the current frame.
+    fn update(&mut self, key: Key, action: Action) -> Option<InputAction> {
+        match action {
+            Action::Press | Action::Repeat => {
+                if self.held_keys.insert(key) {
+                    Some(InputAction::Press)
+                } else {
+                    Some(InputAction::Repeat)
+                }
+            }
+            Action::Release => {
+                if self.held_keys.remove(&key) {
+                    Some(InputAction::Release)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
 struct Game {
     context: Rc<RefCell<GameContext>>,
     state_machine: GameStateMachine,
     renderer_state_machine: RendererStateMachine,
+    keyboard: KeyboardState,
 }
 
 impl Game {
@@ -3690,6 +4606,18 @@ impl Game {
         self.context.borrow().gl.borrow().window.get_key(key)
     }
 
+    #[inline]
+    fn settings(&self) -> GameSettings {
+        self.context.borrow().settings.clone()
+    }
+
+    /// Get the input action for a key, telling apart a fresh key press from a
+    /// key that is being held down.
+    fn get_key_input(&mut self, key: Key) -> Option<InputAction> {
+        let action = self.get_key(key);
+        self.keyboard.update(key, action)
+    }
+
     #[inline]
     fn viewport_dimensions(&self) -> ViewportDimensions {
         let (width, height) = {
@@ -3751,7 +4679,32 @@ fn init_gl(width: u32, height: u32) -> glb::GLState {
     gl_state
 }
 
-fn init_game() -> Game {
+/// The timers for moving, dropping, and clearing blocks in the playing field.
+fn create_timer_spec_playing_field(settings: &GameSettings) -> PlayingFieldTimerSpec {
+    PlayingFieldTimerSpec {
+        gravity: settings.gravity.create(),
+        lock_delay_interval: Interval::Milliseconds(500),
+        lock_delay: settings.lock_delay,
+        auto_shift: AutoShiftSpec::from_delays(
+            settings.delayed_auto_shift, settings.auto_repeat_rate, settings.das_cut_delay
+        ),
+        down_hold_interval: Interval::Milliseconds(35),
+        rotate_interval: Interval::Milliseconds(100),
+        clearing_interval: Interval::Milliseconds(60),
+    }
+}
+
+/// Create the next block queue for a new game. A game played with a seed deals
+/// the same pieces every time it is played.
+fn create_next_block_cell(settings: &GameSettings, piece_set: &PieceSet) -> NextBlockCell {
+    let pieces = piece_set.pieces();
+    match settings.seed {
+        Some(seed) => NextBlockCell::from_seed(seed, settings.randomizer, &pieces, settings.preview_count),
+        None => NextBlockCell::new(settings.randomizer, &pieces, settings.preview_count),
+    }
+}
+
+fn init_game(settings: GameSettings, piece_set: Rc<PieceSet>) -> Game {
     init_logger("googly-blocks.log");
     info!("BEGIN LOG");
     info!("build version: ??? ?? ???? ??:??:??");
@@ -3805,6 +4758,8 @@ fn init_game() -> Game {
         level_placement: AbsolutePlacement { x: 0.50, y: -0.21 },
         lines_placement: AbsolutePlacement { x: 0.50, y: -0.54 },
         tetrises_placement: AbsolutePlacement { x: 0.50, y: -0.87 },
+        combo_placement: AbsolutePlacement { x: -0.97, y: 0.02 },
+        back_to_back_placement: AbsolutePlacement { x: -0.97, y: -0.28 },
         t_placement: AbsolutePlacement { x: -0.41, y:  0.62 },
         j_placement: AbsolutePlacement { x: -0.41, y:  0.38 },
         z_placement: AbsolutePlacement { x: -0.41, y:  0.15 },
@@ -3815,25 +4770,47 @@ fn init_game() -> Game {
         scale_px: 48.0,
     };
     let text_panel = load_text_panel(gl_context.clone(), &text_panel_spec, text_panel_uniforms);
-    let mut next_block_cell = NextBlockCell::new();
+    let message_panel = create_text_buffer(gl_context.clone(), font_atlas.clone(), 32.0, text_panel_uniforms);
+    let mut next_block_cell = create_next_block_cell(&settings, &piece_set);
     let starting_block = next_block_cell.current_block();
     next_block_cell.update();
-    let next_block = next_block_cell.current_block();
+    let next_blocks = (0..next_block_cell.preview_count()).filter_map(|n| next_block_cell.peek(n)).collect();
     let next_piece_panel_spec = NextPiecePanelSpec {
-        block: next_block,
+        blocks: next_blocks,
         atlas: &block_texture_atlas,
+        piece_set: &piece_set,
     };
-    let next_piece_panel_uniforms = create_uniforms_next_piece_panel(next_block, 50, width, height);
+    let next_piece_panel_uniforms = create_uniforms_next_piece_panel(next_block_cell.current_block(), 0, width, height);
     let next_piece_panel = {
         let mut context = gl_context.borrow_mut();
         load_next_piece_panel(&mut *context, next_piece_panel_spec, &next_piece_panel_uniforms)
     };
+    let hold_piece_label = create_text_buffer(gl_context.clone(), font_atlas.clone(), 32.0, text_panel_uniforms);
+    let hold_piece_panel = load_hold_piece_panel(hold_piece_label);
+    let clear_message_buffer = create_text_buffer(gl_context.clone(), font_atlas.clone(), 24.0, text_panel_uniforms);
+    let clear_message_panel = load_clear_message_panel(clear_message_buffer);
+    let game_mode_buffer = create_text_buffer(gl_context.clone(), font_atlas.clone(), 24.0, text_panel_uniforms);
+    let game_mode_panel = load_game_mode_panel(game_mode_buffer);
+    let mut perfect_clear_panel = create_text_buffer(gl_context.clone(), font_atlas.clone(), 40.0, text_panel_uniforms);
+    perfect_clear_panel.write(b"PERFECT CLEAR", AbsolutePlacement { x: -0.30, y: 0.10 }).unwrap();
+    perfect_clear_panel.send_to_gpu().unwrap();
+    let mut pause_panel = create_text_buffer(gl_context.clone(), font_atlas.clone(), 32.0, text_panel_uniforms);
+    pause_panel.write(b"PAUSED", AbsolutePlacement { x: -0.22, y: 0.20 }).unwrap();
+    pause_panel.write(b"P TO RESUME", AbsolutePlacement { x: -0.22, y: 0.00 }).unwrap();
+    pause_panel.write(b"ENTER TO RESTART", AbsolutePlacement { x: -0.22, y: -0.10 }).unwrap();
+    pause_panel.write(b"BACKSPACE FOR TITLE", AbsolutePlacement { x: -0.22, y: -0.20 }).unwrap();
+    pause_panel.write(b"ESCAPE TO QUIT", AbsolutePlacement { x: -0.22, y: -0.30 }).unwrap();
+    pause_panel.send_to_gpu().unwrap();
     let ui = UI { 
         ui_panel: ui_panel,
         text_panel: text_panel,
         next_piece_panel: next_piece_panel,
+        hold_piece_panel: hold_piece_panel,
+        clear_message_panel: clear_message_panel,
+        game_mode_panel: game_mode_panel,
+        perfect_clear_panel: perfect_clear_panel,
     };
-    let block_element_atlas = create_textures_playing_field(&block_texture_atlas);
+    let block_element_atlas = create_textures_playing_field(&block_texture_atlas, &piece_set);
     let playing_field_background_spec = PlayingFieldBackgroundSpec {
         width: 250,
         height: 500,
@@ -3845,35 +4822,37 @@ fn init_game() -> Game {
     };
     let playing_field_uniforms = create_uniforms_playing_field(488, viewport_width as u32, viewport_height as u32);
     let playing_field_spec = PlayingFieldHandleSpec {
-        rows: 20,
-        columns: 10,
+        rows: settings.rows,
+        columns: settings.columns,
         atlas: &block_element_atlas,
     };
     let playing_field_handle = {
         let mut context = gl_context.borrow_mut();
         load_playing_field(&mut *context, playing_field_spec, playing_field_uniforms)
     };
-    let starting_positions: HashMap<GooglyBlockPiece, BlockPosition> = [
-        (GooglyBlockPiece::T, BlockPosition::new(-3, 4)),
-        (GooglyBlockPiece::J, BlockPosition::new(-3, 4)), 
-        (GooglyBlockPiece::Z, BlockPosition::new(-3, 4)),
-        (GooglyBlockPiece::O, BlockPosition::new(-3, 4)), 
-        (GooglyBlockPiece::S, BlockPosition::new(-3, 4)), 
-        (GooglyBlockPiece::L, BlockPosition::new(-3, 4)),
-        (GooglyBlockPiece::I, BlockPosition::new(-3, 3)),
-    ].iter().map(|elem| *elem).collect();
+    let starting_positions = playing_field::starting_positions(&piece_set, settings.columns);
     let playing_field_context_spec = PlayingFieldContextSpec {
         starting_block: starting_block,
         starting_positions: starting_positions,
+        piece_set: piece_set.clone(),
+        rotation_system: settings.rotation_system,
+        rows: settings.rows,
+        columns: settings.columns,
     };
     let playing_field_context = Rc::new(RefCell::new(
         PlayingFieldContext::new(playing_field_context_spec)
     ));
-    let playing_field = PlayingField::new(playing_field_handle, &block_element_atlas);
+    let playing_field = PlayingField::new(
+        playing_field_handle, &block_element_atlas, settings.rows, settings.columns, settings.ghost_piece
+    );
     
+    let garbage_gen = match next_block_cell.seed() {
+        Some(seed) => GarbageGen::from_seed(seed, settings.columns),
+        None => GarbageGen::new(settings.columns),
+    };
     let next_block_cell_ref = Rc::new(RefCell::new(next_block_cell));
     let statistics = Rc::new(RefCell::new(Statistics::new()));
-    let score_board = Rc::new(RefCell::new(ScoreBoard::new(20)));
+    let score_board = Rc::new(RefCell::new(ScoreBoard::new(LINES_PER_LEVEL, 0, settings.scoring_system.create())));
     let game_over_panel_spec = GameOverPanelSpec {
         width: 300,
         height: 178,
@@ -3888,6 +4867,7 @@ fn init_game() -> Game {
         transition_interval: Interval::Milliseconds(2000),
         pressed_interval: Interval::Milliseconds(100),
         unpressed_interval: Interval::Milliseconds(500),
+        level_select_interval: Interval::Milliseconds(150),
     };
     let title_screen = Rc::new(RefCell::new(
         TitleScreenStateMachine::new(title_screen_state_machine_spec)
@@ -3911,15 +4891,7 @@ fn init_game() -> Game {
     };
     let exiting = Rc::new(RefCell::new(false));
 
-    let timer_spec = PlayingFieldTimerSpec {
-        fall_interval: Interval::Milliseconds(500),
-        collision_interval: Interval::Milliseconds(500),
-        left_hold_interval: Interval::Milliseconds(70),
-        right_hold_interval: Interval::Milliseconds(70),
-        down_hold_interval: Interval::Milliseconds(35),
-        rotate_interval: Interval::Milliseconds(100),
-        clearing_interval: Interval::Milliseconds(60),
-    };
+    let timer_spec = create_timer_spec_playing_field(&settings);
     let flash_timer_spec = FlashAnimationStateMachineSpec {
         flash_switch_interval: Interval::Milliseconds(50),
         flash_stop_interval: Interval::Milliseconds(500),
@@ -3927,9 +4899,17 @@ fn init_game() -> Game {
     let flashing_state_machine = Rc::new(RefCell::new(
         flashing_state_machine::create(flash_timer_spec)
     ));
+    let perfect_clear_flash_timer_spec = FlashAnimationStateMachineSpec {
+        flash_switch_interval: Interval::Milliseconds(100),
+        flash_stop_interval: Interval::Milliseconds(1500),
+    };
+    let perfect_clear_flashing_state_machine = Rc::new(RefCell::new(
+        flashing_state_machine::create(perfect_clear_flash_timer_spec)
+    ));
     let playing_field_state_machine_spec = PlayingFieldStateMachineSpec {
         timers: timer_spec,
         flashing_state_machine: flashing_state_machine.clone(),
+        perfect_clear_flashing_state_machine: perfect_clear_flashing_state_machine.clone(),
         playing_field_context: playing_field_context.clone(),
         next_block: next_block_cell_ref.clone(),
         statistics: statistics.clone(),
@@ -3945,8 +4925,16 @@ fn init_game() -> Game {
         score_board: score_board,
         next_block: next_block_cell_ref,
         flashing_state_machine: flashing_state_machine,
+        perfect_clear_flashing_state_machine: perfect_clear_flashing_state_machine,
+        game_mode: Rc::new(RefCell::new(settings.game_mode.create())),
+        garbage_gen: garbage_gen,
         exiting: exiting,
         title_screen: title_screen,
+        settings: settings,
+        piece_set: piece_set,
+        starting_level: 0,
+        restart: None,
+        paused: false,
     }));
     let initial_game_state = GameState::TitleScreen(GameTitleScreenState::new());
     let state_machine = GameStateMachine::new(context.clone(), initial_game_state);
@@ -3958,6 +4946,8 @@ fn init_game() -> Game {
         game_over: game_over,
         playing_field_background: playing_field_background,
         title_screen: title_screen_handle,
+        message_panel: message_panel,
+        pause_panel: pause_panel,
     };
     let initial_renderer_state = RendererState::TitleScreen(RendererTitleScreenState {});
     let renderer_state_machine = RendererStateMachine::new(
@@ -3969,14 +4959,64 @@ fn init_game() -> Game {
         context: context,
         state_machine: state_machine,
         renderer_state_machine: renderer_state_machine,
+        keyboard: KeyboardState::new(),
     };
     game.init_gpu();
 
     game
 }
 
+/// Load the piece set the game is played with, and check that it can be played
+/// on the playing field and drawn with the block textures.
+fn load_piece_set(settings: &GameSettings) -> Result<PieceSet, String> {
+    let piece_set = match settings.piece_set {
+        Some(ref path) => PieceSet::load(path)?,
+        None => PieceSet::standard(),
+    };
+    piece_set.check_fits(settings.rows, settings.columns)?;
+    let atlas = create_block_texture_atlas();
+    for definition in piece_set.definitions().iter() {
+        if atlas.by_texture_name_corners_uv(&definition.texture).is_none() {
+            return Err(format!("Piece {} has an unknown texture: {}", definition.name, definition.texture));
+        }
+    }
+
+    Ok(piece_set)
+}
+
+/// Load the custom scoring system the game is scored with, if the settings 
+/// name one. Otherwise the game is scored with the chosen scoring system.
+fn load_scoring_system(settings: &GameSettings) -> Result<ScoringSystemKind, String> {
+    match settings.scoring_file {
+        Some(ref path) => CustomScoringSystem::load(path).map(ScoringSystemKind::Custom),
+        None => Ok(settings.scoring_system),
+    }
+}
+
 fn main() {
-    let mut game = init_game();
+    let mut settings = match GameSettings::from_args(env::args().skip(1)) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Usage: googly-blocks [--mode <endless|marathon|sprint|ultra|cheese|survival>] [--seed <SEED>] [--randomizer <classic|nes|tgm|bag>] [--preview <1-6>] [--rotation <classic|srs>] [--gravity <nes|guideline>] [--scoring <nes|guideline>] [--scoring-file <FILE>] [--lock-delay <classic|step|move>] [--width <4-20>] [--height <10-40>] [--pieces <FILE>] [--das <DELAY>] [--arr <DELAY>] [--das-cut <DELAY>] [--sonic-drop] [--no-ghost]");
+            process::exit(1);
+        }
+    };
+    let piece_set = match load_piece_set(&settings) {
+        Ok(piece_set) => Rc::new(piece_set),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+    settings.scoring_system = match load_scoring_system(&settings) {
+        Ok(scoring_system) => scoring_system,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+    let mut game = init_game(settings, piece_set);
     while !game.window_should_close() {
         let elapsed_milliseconds = game.update_timers();
 
@@ -3992,50 +5032,119 @@ fn main() {
             }
             _ => {}
         }
-        match game.get_key(Key::Left) {
+        match game.get_key_input(Key::Left) {
+            Some(action) => {
+                let input = Input::new(InputKind::Left, action);
+                game.handle_input(input, elapsed_milliseconds);
+            }
+            None => {}
+        }
+        match game.get_key_input(Key::Right) {
+            Some(action) => {
+                let input = Input::new(InputKind::Right, action);
+                game.handle_input(input, elapsed_milliseconds);
+            }
+            None => {}
+        }
+        match game.get_key(Key::Down) {
             Action::Press => {
-                let input = Input::new(InputKind::Left, InputAction::Repeat);
+                let input = Input::new(InputKind::Down, InputAction::Press);
                 game.handle_input(input, elapsed_milliseconds);
             }
             Action::Repeat => {
-                let input = Input::new(InputKind::Left, InputAction::Repeat);
+                let input = Input::new(InputKind::Down, InputAction::Repeat);
                 game.handle_input(input, elapsed_milliseconds);
             }
             _ => {}
         }
-        match game.get_key(Key::Right) {
+        match game.get_key(Key::Up) {
             Action::Press => {
-                let input = Input::new(InputKind::Right, InputAction::Repeat);
+                let input = Input::new(InputKind::RotateClockwise, InputAction::Press);
                 game.handle_input(input, elapsed_milliseconds);
             }
             Action::Repeat => {
-                let input = Input::new(InputKind::Right, InputAction::Repeat);
+                let input = Input::new(InputKind::RotateClockwise, InputAction::Repeat);
                 game.handle_input(input, elapsed_milliseconds);
             }
             _ => {}
         }
-        match game.get_key(Key::Down) {
+        match game.get_key(Key::X) {
             Action::Press => {
-                let input = Input::new(InputKind::Down, InputAction::Press);
+                let input = Input::new(InputKind::RotateClockwise, InputAction::Press);
                 game.handle_input(input, elapsed_milliseconds);
             }
             Action::Repeat => {
-                let input = Input::new(InputKind::Down, InputAction::Repeat);
+                let input = Input::new(InputKind::RotateClockwise, InputAction::Repeat);
                 game.handle_input(input, elapsed_milliseconds);
             }
             _ => {}
         }
-        match game.get_key(Key::Up) {
+        match game.get_key(Key::Z) {
+            Action::Press => {
+                let input = Input::new(InputKind::RotateCounterClockwise, InputAction::Press);
+                game.handle_input(input, elapsed_milliseconds);
+            }
+            Action::Repeat => {
+                let input = Input::new(InputKind::RotateCounterClockwise, InputAction::Repeat);
+                game.handle_input(input, elapsed_milliseconds);
+            }
+            _ => {}
+        }
+        match game.get_key(Key::LeftControl) {
             Action::Press => {
-                let input = Input::new(InputKind::Rotate, InputAction::Press);
+                let input = Input::new(InputKind::RotateCounterClockwise, InputAction::Press);
                 game.handle_input(input, elapsed_milliseconds);
             }
             Action::Repeat => {
-                let input = Input::new(InputKind::Rotate, InputAction::Repeat);
+                let input = Input::new(InputKind::RotateCounterClockwise, InputAction::Repeat);
                 game.handle_input(input, elapsed_milliseconds);
             }
             _ => {}
         }
+        match game.get_key_input(Key::Space) {
+            Some(action) => {
+                let input = Input::new(InputKind::HardDrop, action);
+                game.handle_input(input, elapsed_milliseconds);
+            }
+            None => {}
+        }
+        if game.settings().sonic_drop {
+            match game.get_key_input(Key::V) {
+                Some(action) => {
+                    let input = Input::new(InputKind::SonicDrop, action);
+                    game.handle_input(input, elapsed_milliseconds);
+                }
+                None => {}
+            }
+        }
+        match game.get_key_input(Key::C) {
+            Some(action) => {
+                let input = Input::new(InputKind::Hold, action);
+                game.handle_input(input, elapsed_milliseconds);
+            }
+            None => {}
+        }
+        match game.get_key_input(Key::LeftShift) {
+            Some(action) => {
+                let input = Input::new(InputKind::Hold, action);
+                game.handle_input(input, elapsed_milliseconds);
+            }
+            None => {}
+        }
+        match game.get_key_input(Key::P) {
+            Some(action) => {
+                let input = Input::new(InputKind::Pause, action);
+                game.handle_input(input, elapsed_milliseconds);
+            }
+            None => {}
+        }
+        match game.get_key_input(Key::Backspace) {
+            Some(action) => {
+                let input = Input::new(InputKind::ReturnToTitle, action);
+                game.handle_input(input, elapsed_milliseconds);
+            }
+            None => {}
+        }
         match game.get_key(Key::Enter) {
             Action::Press => {
                 let input = Input::new(InputKind::StartGame, InputAction::Press);
@@ -15,103 +15,255 @@
  *  You should have received a copy of the GNU General Public License
  *  along with this program.  If not, see <http://www.gnu.org/licenses/>.
  */
-use rand::prelude as rng;
-use rand::distributions::{
-    Distribution, 
-    Uniform
+use rand::{
+    Rng,
+    RngCore,
+    SeedableRng,
+};
+use rand::rngs::{
+    StdRng,
+};
+use log::{
+    info
 };
 use crate::block::{
     GooglyBlockPiece,
     GooglyBlockRotation,
     GooglyBlock,   
 };
+use crate::randomizer::{
+    Randomizer,
+    RandomizerKind,
+};
+
+use std::collections::VecDeque;
 
 
+/// The smallest number of upcoming blocks the next block cell can hold.
+pub const MIN_PREVIEW_COUNT: usize = 1;
+/// The largest number of upcoming blocks the next block cell can hold.
+pub const MAX_PREVIEW_COUNT: usize = 6;
+
 /// The block generator that pseudorandomly generates the next block for the 
 /// next block panel in the game.
 struct NextBlockGen {
     /// The inner random number generator.
-    rng: rng::ThreadRng,
-    /// The probability distribution for choosing the next block.
-    between: Uniform<u32>,
-    /// The last block generated.
-    last_block: GooglyBlock,
-    /// The table of possible blocks that can be generated.
-    table: [GooglyBlock; 7],
+    rng: Box<dyn RngCore>,
+    /// The strategy for choosing the next block.
+    randomizer: Box<dyn Randomizer>,
 }
 
 impl NextBlockGen {
-    /// Construct a new block generator. Here, we choose each block to have the default rotation 
-    /// state of R0 since that is what the panel displays.
-    fn new() -> NextBlockGen {
-        let table = [
-            GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0),
-            GooglyBlock::new(GooglyBlockPiece::J, GooglyBlockRotation::R0),
-            GooglyBlock::new(GooglyBlockPiece::Z, GooglyBlockRotation::R0),
-            GooglyBlock::new(GooglyBlockPiece::O, GooglyBlockRotation::R0),
-            GooglyBlock::new(GooglyBlockPiece::S, GooglyBlockRotation::R0),
-            GooglyBlock::new(GooglyBlockPiece::L, GooglyBlockRotation::R0),
-            GooglyBlock::new(GooglyBlockPiece::I, GooglyBlockRotation::R0),
-        ];
-        let mut rng = rng::thread_rng();
-        let between = Uniform::new_inclusive(0, 6);
-        let random = between.sample(&mut rng) as usize;
-        let last_block = table[random];
-
+    /// Construct a new block generator.
+    fn new(rng: Box<dyn RngCore>, randomizer: Box<dyn Randomizer>) -> NextBlockGen {
         NextBlockGen {
             rng: rng,
-            between: between,
-            last_block: last_block,
-            table: table,
+            randomizer: randomizer,
         }
     }
 
-    /// Generate the next block.
+    /// Generate the next block. Here, we choose each block to have the default rotation 
+    /// state of R0 since that is what the panel displays.
     fn next(&mut self) -> GooglyBlock {
-        let mut block = self.table[self.between.sample(&mut self.rng) as usize];
-        let mut gas = 0;
-        // We perform a bounded iteration over the random number generator
-        // to reduce the probability of generating long runs of the same pieces.
-        // The gas parameter exists to guarantee that the loop terminates.
-        while (gas < 8) && (block == self.last_block) {
-            let random = self.between.sample(&mut self.rng) as usize;
-            block = self.table[random];
-            gas += 1;
-        }
-        self.last_block = block;
-        
-        block
+        let piece = self.randomizer.next(&mut *self.rng);
+
+        GooglyBlock::new(piece, GooglyBlockRotation::R0)
     }
 }
 
-/// The next block cell holds the next block in the window, which is also the 
-/// next block that will be generated for the player.
+/// The next block cell holds the queue of upcoming blocks shown in the next block 
+/// panel. The front of the queue is the next block that will be given to the player.
 pub struct NextBlockCell {
     /// The inner block generator.
     gen: NextBlockGen,
-    /// The current block given to the player.
-    block: GooglyBlock,
+    /// The upcoming blocks, in the order they will be given to the player.
+    blocks: VecDeque<GooglyBlock>,
+    /// The seed for the block generator, if the cell was constructed from one.
+    seed: Option<u64>,
 }
 
 impl NextBlockCell {
-    pub fn new() -> NextBlockCell {
-        let mut gen = NextBlockGen::new();
-        let block = gen.next();
+    /// Construct a new next block cell with a freshly chosen seed that deals 
+    /// the given pieces.
+    pub fn new(randomizer: RandomizerKind, pieces: &[GooglyBlockPiece], preview_count: usize) -> NextBlockCell {
+        let seed = rand::thread_rng().gen();
+
+        NextBlockCell::from_seed(seed, randomizer, pieces, preview_count)
+    }
+
+    /// Construct a next block cell from a seed. Two cells constructed from the 
+    /// same seed, randomizer, and pieces generate the same sequence of blocks.
+    pub fn from_seed(
+        seed: u64, randomizer: RandomizerKind, pieces: &[GooglyBlockPiece], preview_count: usize) -> NextBlockCell {
+
+        info!("Next block seed: {}", seed);
+        info!("Next block randomizer: {}", randomizer);
+        let rng = Box::new(StdRng::seed_from_u64(seed));
+        let mut cell = NextBlockCell::from_rng(rng, randomizer.create(pieces), preview_count);
+        cell.seed = Some(seed);
+
+        cell
+    }
+
+    /// Construct a next block cell that draws from an arbitrary random number
+    /// generator. The number of previewed blocks is clamped to lie between 
+    /// `MIN_PREVIEW_COUNT` and `MAX_PREVIEW_COUNT`.
+    pub fn from_rng(
+        rng: Box<dyn RngCore>, randomizer: Box<dyn Randomizer>, preview_count: usize) -> NextBlockCell {
+        
+        let preview_count = usize::max(MIN_PREVIEW_COUNT, usize::min(preview_count, MAX_PREVIEW_COUNT));
+        let mut gen = NextBlockGen::new(rng, randomizer);
+        let blocks = (0..preview_count).map(|_| gen.next()).collect();
         
         NextBlockCell {
             gen: gen,
-            block: block,
+            blocks: blocks,
+            seed: None,
         }
     }
 
-    /// Generate the next block.
+    /// The seed the block generator was constructed from, if any.
+    #[inline]
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// The number of upcoming blocks in the queue.
+    #[inline]
+    pub fn preview_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Remove the block at the front of the queue and generate a new block at the back.
     pub fn update(&mut self) {
-        self.block = self.gen.next();
+        self.blocks.pop_front();
+        self.blocks.push_back(self.gen.next());
     }
 
     /// Get the current block.
     #[inline]
     pub fn current_block(&self) -> GooglyBlock {
-        self.block
+        self.blocks[0]
+    }
+
+    /// Look at the block `n` places from the front of the queue, if the queue 
+    /// is long enough to hold it.
+    #[inline]
+    pub fn peek(&self, n: usize) -> Option<GooglyBlock> {
+        self.blocks.get(n).copied()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        NextBlockCell,
+        MAX_PREVIEW_COUNT,
+        MIN_PREVIEW_COUNT,
+    };
+    use crate::block::{
+        GooglyBlock,
+        GooglyBlockPiece,
+    };
+    use crate::randomizer::{
+        RandomizerKind,
+    };
+    use rand::rngs::{
+        mock,
+    };
+
+
+    const PIECES: [GooglyBlockPiece; 7] = [
+        GooglyBlockPiece::T, GooglyBlockPiece::J, GooglyBlockPiece::Z, GooglyBlockPiece::O,
+        GooglyBlockPiece::S, GooglyBlockPiece::L, GooglyBlockPiece::I,
+    ];
+
+    fn sequence(cell: &mut NextBlockCell, length: usize) -> Vec<GooglyBlock> {
+        let mut blocks = vec![];
+        for _ in 0..length {
+            blocks.push(cell.current_block());
+            cell.update();
+        }
+
+        blocks
+    }
+
+
+    /// Two next block cells constructed from the same seed should generate the
+    /// same sequence of blocks.
+    #[test]
+    fn next_block_cells_with_the_same_seed_should_generate_the_same_blocks() {
+        let mut cell1 = NextBlockCell::from_seed(0xDEADBEEF, RandomizerKind::Classic, &PIECES, 1);
+        let mut cell2 = NextBlockCell::from_seed(0xDEADBEEF, RandomizerKind::Classic, &PIECES, 1);
+        let expected = sequence(&mut cell1, 1000);
+        let result = sequence(&mut cell2, 1000);
+
+        assert_eq!(result, expected);
+    }
+
+    /// Next block cells constructed from different seeds should generate different
+    /// sequences of blocks.
+    #[test]
+    fn next_block_cells_with_different_seeds_should_generate_different_blocks() {
+        let mut cell1 = NextBlockCell::from_seed(1, RandomizerKind::Classic, &PIECES, 1);
+        let mut cell2 = NextBlockCell::from_seed(2, RandomizerKind::Classic, &PIECES, 1);
+        let blocks1 = sequence(&mut cell1, 1000);
+        let blocks2 = sequence(&mut cell2, 1000);
+
+        assert_ne!(blocks1, blocks2);
+    }
+
+    /// A next block cell should report the seed it was constructed from.
+    #[test]
+    fn next_block_cell_should_remember_its_seed() {
+        let cell = NextBlockCell::from_seed(12345, RandomizerKind::SevenBag, &PIECES, 1);
+
+        assert_eq!(cell.seed(), Some(12345));
+        assert!(NextBlockCell::new(RandomizerKind::Classic, &PIECES, 1).seed().is_some());
+    }
+
+    /// A next block cell constructed from an arbitrary random number generator 
+    /// has no seed.
+    #[test]
+    fn next_block_cell_from_a_pluggable_rng_should_have_no_seed() {
+        let rng = Box::new(mock::StepRng::new(0, 1));
+        let mut cell = NextBlockCell::from_rng(rng, RandomizerKind::Nes.create(&PIECES), 1);
+        sequence(&mut cell, 100);
+
+        assert_eq!(cell.seed(), None);
+    }
+
+    /// Peeking into the queue should show the blocks in the order they will 
+    /// become the current block.
+    #[test]
+    fn peeking_should_show_the_upcoming_blocks_in_order() {
+        let mut cell = NextBlockCell::from_seed(0xC0FFEE, RandomizerKind::SevenBag, &PIECES, 5);
+        let expected: Vec<_> = (0..5).map(|n| cell.peek(n).unwrap()).collect();
+        let result = sequence(&mut cell, 5);
+
+        assert_eq!(result, expected);
+        assert_eq!(cell.peek(5), None);
+    }
+
+    /// The length of the preview queue should not change the sequence of blocks.
+    #[test]
+    fn the_preview_count_should_not_change_the_block_sequence() {
+        let mut cell1 = NextBlockCell::from_seed(0xDEADBEEF, RandomizerKind::Tgm, &PIECES, 1);
+        let mut cell2 = NextBlockCell::from_seed(0xDEADBEEF, RandomizerKind::Tgm, &PIECES, 6);
+        let expected = sequence(&mut cell1, 1000);
+        let result = sequence(&mut cell2, 1000);
+
+        assert_eq!(result, expected);
+    }
+
+    /// The number of previewed blocks should be clamped to the supported range.
+    #[test]
+    fn the_preview_count_should_be_clamped() {
+        let cell1 = NextBlockCell::from_seed(1, RandomizerKind::Classic, &PIECES, 0);
+        let cell2 = NextBlockCell::from_seed(1, RandomizerKind::Classic, &PIECES, 100);
+
+        assert_eq!(cell1.preview_count(), MIN_PREVIEW_COUNT);
+        assert_eq!(cell2.preview_count(), MAX_PREVIEW_COUNT);
     }
 }
@@ -0,0 +1,605 @@
+/*
+ *  Googly Blocks is a video game.
+ *  Copyright (C) 2018,2019,2020  Christopher Blanchard
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use crate::block::{
+    GooglyBlock,
+    GooglyBlockPiece,
+    GooglyBlockRotation,
+    GooglyBlockShape,
+};
+use crate::playing_field::{
+    BlockPosition,
+    HIDDEN_ROWS,
+};
+use toml::{
+    Value,
+};
+
+use std::fs;
+use std::path::Path;
+
+
+/// The definition file for the seven tetrominoes the game is played with by default.
+const STANDARD_PIECE_SET: &str = include_str!("../assets/pieces/tetrominoes.toml");
+
+/// The names of the seven standard pieces. A piece with one of these names plays
+/// as that piece, so it counts towards the piece statistics, and a T piece can spin.
+/// It must have the same cells as the standard piece in all four rotation states,
+/// because the SRS kicks and T-spins depend on them. A piece with any other name 
+/// is a custom piece.
+const STANDARD_PIECES: [(&str, GooglyBlockPiece); 7] = [
+    ("T", GooglyBlockPiece::T),
+    ("J", GooglyBlockPiece::J),
+    ("Z", GooglyBlockPiece::Z),
+    ("O", GooglyBlockPiece::O),
+    ("S", GooglyBlockPiece::S),
+    ("L", GooglyBlockPiece::L),
+    ("I", GooglyBlockPiece::I),
+];
+
+/// The row a piece spawns in when its definition does not give one. Negative
+/// rows are in the buffer zone above the visible playing field.
+pub const DEFAULT_SPAWN_ROW: isize = -3;
+
+/// The lowest row a piece can spawn in, which is the top row of the visible 
+/// playing field.
+pub const MAX_SPAWN_ROW: isize = 0;
+
+/// The description of a single piece in a piece set.
+pub struct PieceDefinition {
+    /// The piece this definition describes.
+    pub piece: GooglyBlockPiece,
+    /// The name of the piece in the definition file.
+    pub name: String,
+    /// The name of the block texture the piece is drawn with.
+    pub texture: String,
+    /// The row the piece spawns in.
+    pub spawn_row: isize,
+    /// The column the piece spawns in. When no column is given, the piece
+    /// spawns in the middle of the playing field.
+    pub spawn_column: Option<isize>,
+    /// The shape of each rotation state, in clockwise order.
+    rotations: Vec<GooglyBlockShape>,
+}
+
+impl PieceDefinition {
+    /// The shape of the piece in a given rotation state. A piece with fewer than
+    /// four rotation states cycles through the ones it has.
+    pub fn shape(&self, rotation: GooglyBlockRotation) -> &GooglyBlockShape {
+        let index = match rotation {
+            GooglyBlockRotation::R0 => 0,
+            GooglyBlockRotation::R1 => 1,
+            GooglyBlockRotation::R2 => 2,
+            GooglyBlockRotation::R3 => 3,
+        };
+
+        &self.rotations[index % self.rotations.len()]
+    }
+
+    /// The position the piece spawns at in a playing field with the given
+    /// number of columns. A spawn column that would put the piece through a
+    /// side wall is moved back inside the playing field.
+    pub fn starting_position(&self, columns: usize) -> BlockPosition {
+        let shape_columns = self.shape(GooglyBlockRotation::R0).columns() as isize;
+        let column = match self.spawn_column {
+            Some(column) => column,
+            None => (columns as isize - shape_columns + 1) / 2,
+        };
+        let column = isize::max(0, isize::min(column, columns as isize - shape_columns));
+
+        BlockPosition::new(self.spawn_row, column)
+    }
+}
+
+/// A piece set is the collection of pieces a game is played with. Piece sets
+/// are loaded from TOML definition files, so a game can be played with pieces
+/// of any size.
+pub struct PieceSet {
+    /// The name of the piece set.
+    pub name: String,
+    /// The pieces in the set, in the order they appear in the definition file.
+    definitions: Vec<PieceDefinition>,
+}
+
+impl PieceSet {
+    /// The seven tetrominoes the game is played with by default.
+    pub fn standard() -> PieceSet {
+        PieceSet::parse(STANDARD_PIECE_SET).expect("The standard piece set is invalid.")
+    }
+
+    /// Load a piece set from a definition file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<PieceSet, String> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|e| {
+            format!("Could not read piece set {}: {}", path.display(), e)
+        })?;
+
+        PieceSet::from_toml(&source)
+    }
+
+    /// Parse a piece set from the contents of a definition file.
+    pub fn from_toml(source: &str) -> Result<PieceSet, String> {
+        let piece_set = PieceSet::parse(source)?;
+        piece_set.check_standard_pieces()?;
+
+        Ok(piece_set)
+    }
+
+    /// Parse a piece set without comparing its standard pieces to the standard 
+    /// piece set, which is itself parsed this way.
+    fn parse(source: &str) -> Result<PieceSet, String> {
+        let value = source.parse::<Value>().map_err(|e| format!("Invalid piece set: {}", e))?;
+        let name = match value.get("name") {
+            Some(name) => name.as_str().ok_or("The name of a piece set must be a string")?,
+            None => "custom",
+        };
+        let pieces = match value.get("pieces").and_then(Value::as_array) {
+            Some(pieces) if !pieces.is_empty() => pieces,
+            _ => return Err(String::from("A piece set must have at least one piece")),
+        };
+        let mut definitions: Vec<PieceDefinition> = vec![];
+        for (index, piece) in pieces.iter().enumerate() {
+            let definition = parse_piece(index, piece)?;
+            if definitions.iter().any(|other| other.name == definition.name) {
+                return Err(format!("The piece set has more than one piece named {}", definition.name));
+            }
+            definitions.push(definition);
+        }
+
+        Ok(PieceSet {
+            name: String::from(name),
+            definitions: definitions,
+        })
+    }
+
+    /// Check that every piece named after a standard piece has that piece's cells
+    /// and box in all four rotation states.
+    fn check_standard_pieces(&self) -> Result<(), String> {
+        let standard_piece_set = PieceSet::standard();
+        for definition in self.definitions.iter() {
+            let standard = match standard_piece_set.definition(definition.piece) {
+                Some(standard) => standard,
+                None => continue,
+            };
+            let same_shapes = definition.rotations.len() == standard.rotations.len() &&
+                definition.rotations.iter().zip(standard.rotations.iter()).all(|(shape, standard_shape)| {
+                    shape.rows() == standard_shape.rows() && 
+                        shape.columns() == standard_shape.columns() && 
+                        sorted_cells(shape) == sorted_cells(standard_shape)
+                });
+            if !same_shapes {
+                return Err(format!(
+                    "Piece {} must have the cells of the standard {} piece in all four rotation states, \
+                     or a name other than T, J, Z, O, S, L, or I",
+                    definition.name, definition.name
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The pieces in the set.
+    pub fn pieces(&self) -> Vec<GooglyBlockPiece> {
+        self.definitions.iter().map(|definition| definition.piece).collect()
+    }
+
+    /// The definitions of the pieces in the set.
+    pub fn definitions(&self) -> &[PieceDefinition] {
+        &self.definitions
+    }
+
+    /// The definition of a piece, if the piece is in the set.
+    pub fn definition(&self, piece: GooglyBlockPiece) -> Option<&PieceDefinition> {
+        self.definitions.iter().find(|definition| definition.piece == piece)
+    }
+
+    /// The shape of a googly block. Every block in a game comes from the piece set
+    /// the game is played with, so asking for the shape of a piece from another
+    /// set is a bug.
+    pub fn shape(&self, block: GooglyBlock) -> &GooglyBlockShape {
+        match self.definition(block.piece) {
+            Some(definition) => definition.shape(block.rotation),
+            None => panic!("The piece {:?} is not in the piece set {}.", block.piece, self.name),
+        }
+    }
+
+    /// The width of the widest piece in the set. The playing field must be at
+    /// least this wide.
+    pub fn columns(&self) -> usize {
+        self.definitions.iter()
+            .map(|definition| definition.shape(GooglyBlockRotation::R0).columns())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The height of the tallest piece in the set. The playing field must be at
+    /// least this tall.
+    pub fn rows(&self) -> usize {
+        self.definitions.iter()
+            .map(|definition| definition.shape(GooglyBlockRotation::R0).rows())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Check that every piece in the set fits in a playing field of the given
+    /// size, both in its box and at its spawn position.
+    pub fn check_fits(&self, rows: usize, columns: usize) -> Result<(), String> {
+        for definition in self.definitions.iter() {
+            let shape = definition.shape(GooglyBlockRotation::R0);
+            if shape.columns() > columns || shape.rows() > rows {
+                return Err(format!(
+                    "Piece {} has a box of {} by {}, which does not fit in a playing field {} columns wide and {} rows tall",
+                    definition.name, shape.columns(), shape.rows(), columns, rows
+                ));
+            }
+            if definition.spawn_row + shape.rows() as isize > rows as isize {
+                return Err(format!(
+                    "Piece {} spawns in row {}, too low for its box to fit in a playing field {} rows tall",
+                    definition.name, definition.spawn_row, rows
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse the definition of the piece at the given index in a piece set.
+fn parse_piece(index: usize, value: &Value) -> Result<PieceDefinition, String> {
+    let name = value.get("name").and_then(Value::as_str)
+        .ok_or(format!("Piece {} in the piece set has no name", index + 1))?;
+    let texture = value.get("texture").and_then(Value::as_str)
+        .ok_or(format!("Piece {} has no texture", name))?;
+    let piece = STANDARD_PIECES.iter()
+        .find(|(standard_name, _)| *standard_name == name)
+        .map(|(_, piece)| *piece)
+        .unwrap_or(GooglyBlockPiece::Custom(index));
+    let spawn_row = match value.get("spawn_row") {
+        Some(row) => parse_integer(name, "spawn_row", row)? as isize,
+        None => DEFAULT_SPAWN_ROW,
+    };
+    if spawn_row < -(HIDDEN_ROWS as isize) {
+        return Err(format!(
+            "Piece {} spawns in row {}, above the top of the buffer zone in row {}", 
+            name, spawn_row, -(HIDDEN_ROWS as isize)
+        ));
+    }
+    if spawn_row > MAX_SPAWN_ROW {
+        return Err(format!(
+            "Piece {} spawns in row {}, below the top of the visible playing field in row {}", 
+            name, spawn_row, MAX_SPAWN_ROW
+        ));
+    }
+    let spawn_column = match value.get("spawn_column") {
+        Some(column) => Some(parse_integer(name, "spawn_column", column)? as isize),
+        None => None,
+    };
+
+    let rotations = value.get("rotations").and_then(Value::as_array)
+        .ok_or(format!("Piece {} has no rotations", name))?;
+    if !(rotations.len() == 1 || rotations.len() == 2 || rotations.len() == 4) {
+        return Err(format!(
+            "Piece {} must have 1, 2, or 4 rotation states, not {}", name, rotations.len()
+        ));
+    }
+    let cells = rotations.iter()
+        .map(|rotation| parse_cells(name, rotation))
+        .collect::<Result<Vec<Vec<(usize, usize)>>, String>>()?;
+    let wall_kicks = match value.get("wall_kicks").and_then(Value::as_array) {
+        Some(wall_kicks) if wall_kicks.len() == rotations.len() => wall_kicks.iter()
+            .map(|wall_kick| parse_integer(name, "wall_kicks", wall_kick).map(|wall_kick| wall_kick as isize))
+            .collect::<Result<Vec<isize>, String>>()?,
+        Some(_) => return Err(format!("Piece {} must have one wall kick for each rotation state", name)),
+        None => vec![0; rotations.len()],
+    };
+
+    // Every rotation state sits in the same square box, which is the smallest
+    // one that holds all of the cells unless the definition gives a bigger one.
+    let fitted_size = cells.iter()
+        .flat_map(|cells| cells.iter())
+        .map(|(row, column)| usize::max(*row, *column) + 1)
+        .max()
+        .unwrap_or(0);
+    let size = match value.get("size") {
+        Some(size) => match parse_integer(name, "size", size)? {
+            size if size >= 1 => size as usize,
+            _ => return Err(format!("The size of piece {} must be at least 1", name)),
+        },
+        None => fitted_size,
+    };
+    if size < fitted_size {
+        return Err(format!("The cells of piece {} do not fit in a box of size {}", name, size));
+    }
+
+    let element = piece.element();
+    let rotations = cells.into_iter().zip(wall_kicks)
+        .map(|(cells, wall_kick)| GooglyBlockShape::new(element, wall_kick, size, size, cells))
+        .collect();
+
+    Ok(PieceDefinition {
+        piece: piece,
+        name: String::from(name),
+        texture: String::from(texture),
+        spawn_row: spawn_row,
+        spawn_column: spawn_column,
+        rotations: rotations,
+    })
+}
+
+/// The cells of a shape in order, so shapes listing the same cells in a different
+/// order compare equal.
+fn sorted_cells(shape: &GooglyBlockShape) -> Vec<(usize, usize)> {
+    let mut cells: Vec<(usize, usize)> = shape.iter().collect();
+    cells.sort_unstable();
+
+    cells
+}
+
+/// Parse the cells of one rotation state of a piece from a list of `[row, column]` pairs.
+fn parse_cells(name: &str, value: &Value) -> Result<Vec<(usize, usize)>, String> {
+    let invalid = || format!("The rotations of piece {} must be lists of [row, column] pairs", name);
+    let cells = value.as_array().ok_or_else(invalid)?;
+    if cells.is_empty() {
+        return Err(format!("Every rotation state of piece {} must have at least one cell", name));
+    }
+    let mut parsed_cells = vec![];
+    for cell in cells.iter() {
+        let (row, column) = match cell.as_array().map(|cell| cell.as_slice()) {
+            Some([row, column]) => (row.as_integer(), column.as_integer()),
+            _ => return Err(invalid()),
+        };
+        let cell = match (row, column) {
+            (Some(row), Some(column)) if row >= 0 && column >= 0 => (row as usize, column as usize),
+            _ => return Err(invalid()),
+        };
+        if parsed_cells.contains(&cell) {
+            return Err(format!("Piece {} has the cell {:?} more than once", name, cell));
+        }
+        parsed_cells.push(cell);
+    }
+
+    Ok(parsed_cells)
+}
+
+/// Parse an integer valued field of a piece definition.
+fn parse_integer(name: &str, field: &str, value: &Value) -> Result<i64, String> {
+    value.as_integer().ok_or(format!("The {} of piece {} must be an integer", field, name))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        PieceSet,
+    };
+    use crate::block::{
+        GooglyBlock,
+        GooglyBlockElement,
+        GooglyBlockPiece,
+        GooglyBlockRotation,
+    };
+    use crate::playing_field::{
+        BlockPosition,
+    };
+
+
+    const ROTATIONS: [GooglyBlockRotation; 4] = [
+        GooglyBlockRotation::R0, GooglyBlockRotation::R1, GooglyBlockRotation::R2, GooglyBlockRotation::R3,
+    ];
+
+    const PENTOMINO_AND_TROMINO: &str = r#"
+        name = "test"
+
+        [[pieces]]
+        name = "P"
+        texture = "l_piece"
+        rotations = [
+            [[0, 0], [0, 1], [1, 0], [1, 1], [2, 0]],
+            [[0, 0], [0, 1], [0, 2], [1, 1], [1, 2]],
+            [[0, 2], [1, 1], [1, 2], [2, 1], [2, 2]],
+            [[1, 0], [1, 1], [2, 0], [2, 1], [2, 2]],
+        ]
+
+        [[pieces]]
+        name = "I3"
+        texture = "i_piece"
+        spawn_row = -2
+        spawn_column = 0
+        wall_kicks = [1, 0]
+        rotations = [
+            [[1, 0], [1, 1], [1, 2]],
+            [[0, 1], [1, 1], [2, 1]],
+        ]
+    "#;
+
+
+    /// The standard piece set should hold the seven tetrominoes, with four cells
+    /// in every rotation state.
+    #[test]
+    fn the_standard_piece_set_should_hold_the_seven_tetrominoes() {
+        let piece_set = PieceSet::standard();
+        let expected = vec![
+            GooglyBlockPiece::T, GooglyBlockPiece::J, GooglyBlockPiece::Z, GooglyBlockPiece::O,
+            GooglyBlockPiece::S, GooglyBlockPiece::L, GooglyBlockPiece::I,
+        ];
+
+        assert_eq!(piece_set.pieces(), expected);
+        for piece in expected.iter() {
+            for rotation in ROTATIONS.iter() {
+                let shape = piece_set.shape(GooglyBlock::new(*piece, *rotation));
+                assert_eq!(shape.cell_count(), 4);
+                assert_eq!(shape.element, piece.element());
+            }
+        }
+        assert_eq!(piece_set.columns(), 4);
+    }
+
+    /// The shapes in the standard piece set should be the original googly block shapes.
+    #[test]
+    fn the_standard_piece_set_should_have_the_original_shapes() {
+        let piece_set = PieceSet::standard();
+        let t_block = piece_set.shape(GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0));
+        let i_block = piece_set.shape(GooglyBlock::new(GooglyBlockPiece::I, GooglyBlockRotation::R1));
+
+        assert_eq!(t_block.iter().collect::<Vec<(usize, usize)>>(), vec![(0, 0), (0, 1), (0, 2), (1, 1)]);
+        assert_eq!((t_block.rows(), t_block.columns(), t_block.wall_kick_distance), (3, 3, 1));
+        assert_eq!(i_block.iter().collect::<Vec<(usize, usize)>>(), vec![(0, 2), (1, 2), (2, 2), (3, 2)]);
+        assert_eq!((i_block.rows(), i_block.columns(), i_block.wall_kick_distance), (4, 4, 0));
+    }
+
+    /// Pieces that are not one of the seven tetrominoes should be custom pieces,
+    /// and they can have any number of cells.
+    #[test]
+    fn piece_sets_should_support_pieces_of_any_size() {
+        let piece_set = PieceSet::from_toml(PENTOMINO_AND_TROMINO).unwrap();
+        let pentomino = piece_set.shape(GooglyBlock::new(GooglyBlockPiece::Custom(0), GooglyBlockRotation::R0));
+        let tromino = piece_set.shape(GooglyBlock::new(GooglyBlockPiece::Custom(1), GooglyBlockRotation::R0));
+
+        assert_eq!(piece_set.name, "test");
+        assert_eq!(piece_set.pieces(), vec![GooglyBlockPiece::Custom(0), GooglyBlockPiece::Custom(1)]);
+        assert_eq!(pentomino.cell_count(), 5);
+        assert_eq!(pentomino.element, GooglyBlockElement::Custom(0));
+        assert_eq!(tromino.cell_count(), 3);
+        assert_eq!(piece_set.definitions()[1].texture, "i_piece");
+    }
+
+    /// A piece with two rotation states should alternate between them.
+    #[test]
+    fn pieces_with_fewer_rotation_states_should_cycle_through_them() {
+        let piece_set = PieceSet::from_toml(PENTOMINO_AND_TROMINO).unwrap();
+        let tromino = GooglyBlock::new(GooglyBlockPiece::Custom(1), GooglyBlockRotation::R0);
+
+        assert_eq!(piece_set.shape(tromino), piece_set.shape(tromino.rotate().rotate()));
+        assert_ne!(piece_set.shape(tromino), piece_set.shape(tromino.rotate()));
+        assert_eq!(piece_set.shape(tromino.rotate()).wall_kick_distance, 0);
+    }
+
+    /// Pieces should spawn where their definitions say, and in the middle of the
+    /// playing field otherwise.
+    #[test]
+    fn pieces_should_spawn_where_their_definitions_say() {
+        let piece_set = PieceSet::from_toml(PENTOMINO_AND_TROMINO).unwrap();
+        let pentomino = piece_set.definition(GooglyBlockPiece::Custom(0)).unwrap();
+        let tromino = piece_set.definition(GooglyBlockPiece::Custom(1)).unwrap();
+
+        assert_eq!(pentomino.starting_position(10), BlockPosition::new(-3, 4));
+        assert_eq!(tromino.starting_position(10), BlockPosition::new(-2, 0));
+    }
+
+    /// Definition files that do not describe a playable piece set should be rejected.
+    #[test]
+    fn invalid_piece_sets_should_be_rejected() {
+        let invalid_piece_sets = [
+            "name = \"empty\"",
+            "[[pieces]]\ntexture = \"t_piece\"\nrotations = [[[0, 0]]]",
+            "[[pieces]]\nname = \"A\"\nrotations = [[[0, 0]]]",
+            "[[pieces]]\nname = \"A\"\ntexture = \"t_piece\"\nrotations = [[[0, 0]], [[0, 0]], [[0, 0]]]",
+            "[[pieces]]\nname = \"A\"\ntexture = \"t_piece\"\nrotations = [[]]",
+            "[[pieces]]\nname = \"A\"\ntexture = \"t_piece\"\nrotations = [[[0, -1]]]",
+            "[[pieces]]\nname = \"A\"\ntexture = \"t_piece\"\nrotations = [[[0, 0], [0, 0]]]",
+            "[[pieces]]\nname = \"A\"\ntexture = \"t_piece\"\nwall_kicks = [1, 0]\nrotations = [[[0, 0]]]",
+            "[[pieces]]\nname = \"A\"\ntexture = \"t_piece\"\nsize = 1\nrotations = [[[0, 0], [0, 1]]]",
+            "[[pieces]]\nname = \"A\"\ntexture = \"t_piece\"\nsize = 0\nrotations = [[[0, 0]]]",
+            "[[pieces]]\nname = \"A\"\ntexture = \"t_piece\"\nsize = -1\nrotations = [[[0, 0]]]",
+            "[[pieces]]\nname = \"A\"\ntexture = \"t_piece\"\nspawn_row = -21\nrotations = [[[0, 0]]]",
+            "[[pieces]]\nname = \"A\"\ntexture = \"t_piece\"\nspawn_row = 1\nrotations = [[[0, 0]]]",
+            "[[pieces]]\nname = \"A\"\ntexture = \"t_piece\"\nspawn_row = 1000\nrotations = [[[0, 0]]]",
+            "[[pieces]]\nname = \"A\"\ntexture = \"t_piece\"\nrotations = [[[0, 0]]]\n\
+             [[pieces]]\nname = \"A\"\ntexture = \"t_piece\"\nrotations = [[[0, 0]]]",
+            "[[pieces]\nname = \"A\"",
+        ];
+        for source in invalid_piece_sets.iter() {
+            assert!(PieceSet::from_toml(source).is_err(), "{}", source);
+        }
+    }
+
+    /// A piece may spawn anywhere from the top of the buffer zone down to the top
+    /// of the visible playing field, and its box may be bigger than its cells need.
+    #[test]
+    fn the_spawn_row_and_size_should_be_checked_at_load_time() {
+        let source = "[[pieces]]\nname = \"A\"\ntexture = \"t_piece\"\nspawn_row = -20\nsize = 3\nrotations = [[[0, 0]]]";
+        let piece_set = PieceSet::from_toml(source).unwrap();
+        let definition = piece_set.definition(GooglyBlockPiece::Custom(0)).unwrap();
+
+        assert_eq!(definition.starting_position(10).row, -20);
+        assert_eq!(definition.shape(GooglyBlockRotation::R0).columns(), 3);
+        assert!(PieceSet::from_toml(&source.replace("size = 3", "size = -3")).is_err());
+        assert!(PieceSet::from_toml(&source.replace("spawn_row = -20", "spawn_row = -21")).is_err());
+        assert!(PieceSet::from_toml(&source.replace("spawn_row = -20", "spawn_row = 0")).is_ok());
+        assert!(PieceSet::from_toml(&source.replace("spawn_row = -20", "spawn_row = 1")).is_err());
+    }
+
+    /// A piece whose box is wider or taller than the playing field, or that spawns
+    /// too low for its box to fit, should not fit the playing field.
+    #[test]
+    fn pieces_should_fit_the_playing_field() {
+        let source = "[[pieces]]\nname = \"A\"\ntexture = \"t_piece\"\nsize = 12\nrotations = [[[0, 0]]]";
+        let piece_set = PieceSet::from_toml(source).unwrap();
+
+        assert_eq!((piece_set.rows(), piece_set.columns()), (12, 12));
+        assert!(piece_set.check_fits(20, 12).is_ok());
+        assert!(piece_set.check_fits(20, 10).is_err());
+        assert!(piece_set.check_fits(10, 20).is_err());
+
+        let piece_set = PieceSet::from_toml(&source.replace("size = 12", "spawn_row = 0\nsize = 10")).unwrap();
+
+        assert!(piece_set.check_fits(10, 10).is_ok());
+        assert!(PieceSet::standard().check_fits(10, 4).is_ok());
+    }
+
+    /// A piece wider than the playing field should spawn against the left wall 
+    /// instead of past it.
+    #[test]
+    fn pieces_wider_than_the_playing_field_should_spawn_against_the_left_wall() {
+        let source = "[[pieces]]\nname = \"A\"\ntexture = \"t_piece\"\nsize = 12\nrotations = [[[0, 0]]]";
+        let piece_set = PieceSet::from_toml(source).unwrap();
+        let definition = piece_set.definition(GooglyBlockPiece::Custom(0)).unwrap();
+
+        assert_eq!(definition.starting_position(10).column, 0);
+    }
+
+    /// A piece named after a standard piece should be rejected unless it has the 
+    /// standard piece's cells in all four rotation states.
+    #[test]
+    fn pieces_with_standard_names_should_have_the_standard_shapes() {
+        let standard_t = "[[pieces]]\nname = \"T\"\ntexture = \"t_piece\"\nrotations = [\n\
+            [[0, 1], [0, 0], [0, 2], [1, 1]],\n\
+            [[1, 0], [0, 1], [1, 1], [2, 1]],\n\
+            [[0, 1], [1, 0], [1, 1], [1, 2]],\n\
+            [[0, 1], [1, 1], [1, 2], [2, 1]],\n]";
+        let piece_set = PieceSet::from_toml(standard_t).unwrap();
+
+        assert_eq!(piece_set.pieces(), vec![GooglyBlockPiece::T]);
+        assert!(PieceSet::from_toml(&standard_t.replace("[[0, 1], [0, 0], [0, 2], [1, 1]]", "[[0, 0], [0, 1], [0, 2]]")).is_err());
+        assert!(PieceSet::from_toml(&standard_t.replace("rotations = [", "size = 4\nrotations = [")).is_err());
+        assert!(PieceSet::from_toml("[[pieces]]\nname = \"I\"\ntexture = \"i_piece\"\nrotations = [[[1, 0], [1, 1], [1, 2], [1, 3]]]").is_err());
+
+        let custom_t = PieceSet::from_toml(&standard_t.replace("name = \"T\"", "name = \"T2\"")).unwrap();
+
+        assert_eq!(custom_t.pieces(), vec![GooglyBlockPiece::Custom(0)]);
+    }
+
+    /// The example tromino piece set should load.
+    #[test]
+    fn the_tromino_piece_set_should_load() {
+        let piece_set = PieceSet::from_toml(include_str!("../assets/pieces/trominoes.toml")).unwrap();
+
+        assert_eq!(piece_set.pieces().len(), 2);
+        assert_eq!(piece_set.columns(), 3);
+    }
+}
@@ -19,12 +19,41 @@ use crate::block::{
     GooglyBlock, 
     GooglyBlockPiece, 
     GooglyBlockElement,
+    GooglyBlockRotation,
+    GooglyBlockShape,
+};
+use crate::next_block::{
+    NextBlockCell,
+};
+use crate::piece_set::{
+    PieceSet,
+};
+use crate::rotation::{
+    RotationSystem,
+    RotationSystemKind,
 };
 use std::fmt;
 use std::iter::Iterator;
 use std::ops;
 use std::collections::hash_map::HashMap;
-
+use std::rc::Rc;
+
+
+/// The number of rows in a standard playing field.
+pub const DEFAULT_ROWS: usize = 20;
+/// The number of columns in a standard playing field.
+pub const DEFAULT_COLUMNS: usize = 10;
+/// The fewest rows a playing field can have.
+pub const MIN_ROWS: usize = 10;
+/// The most rows a playing field can have.
+pub const MAX_ROWS: usize = 40;
+/// The fewest columns a playing field can have. Every piece fits in four columns.
+pub const MIN_COLUMNS: usize = 4;
+/// The most columns a playing field can have.
+pub const MAX_COLUMNS: usize = 20;
+/// The number of hidden rows in the buffer zone above the visible playing field.
+/// Pieces spawn in the buffer zone, and blocks can lock there.
+pub const HIDDEN_ROWS: usize = 20;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum LandedBlocksQuery {
@@ -62,16 +91,16 @@ impl LandedBlocksQuery {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 struct LandedBlocksGridRow {
-    inner: [GooglyBlockElement; 10],
+    inner: Vec<GooglyBlockElement>,
     occupied: usize,
 }
 
 impl LandedBlocksGridRow {
-    fn new() -> LandedBlocksGridRow {
+    fn new(columns: usize) -> LandedBlocksGridRow {
         LandedBlocksGridRow {
-            inner: [GooglyBlockElement::EmptySpace; 10],
+            inner: vec![GooglyBlockElement::EmptySpace; columns],
             occupied: 0,
         }
     }
@@ -85,7 +114,9 @@ impl LandedBlocksGridRow {
     }
 
     #[inline]
-    fn len(&self) -> usize { 10 }
+    fn len(&self) -> usize { 
+        self.inner.len()
+    }
 
     #[inline]
     fn is_full(&self) -> bool {
@@ -114,9 +145,14 @@ impl ops::IndexMut<usize> for LandedBlocksGridRow {
     }
 }
 
+/// The grid of blocks that have landed in the playing field. Visible rows are 
+/// numbered from zero at the top of the visible playing field, and the rows of
+/// the hidden buffer zone above it have negative numbers.
 #[derive(Clone, Debug)]
 pub struct LandedBlocksGrid {
-    landed: [LandedBlocksGridRow; 20],
+    /// Every row of the grid from the top of the buffer zone down.
+    landed: Vec<LandedBlocksGridRow>,
+    columns: usize,
 }
 
 struct LandedBlocksIterator {
@@ -146,17 +182,18 @@ impl Iterator for LandedBlocksIterator {
 }
 
 struct LandedBlocksRowIterator<'a> {
-    row: usize,
+    index: usize,
     landed_blocks: &'a LandedBlocksGrid,
 }
 
 impl<'a> Iterator for LandedBlocksRowIterator<'a> {
-    type Item = (usize, &'a LandedBlocksGridRow);
+    type Item = (isize, &'a LandedBlocksGridRow);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.row < self.landed_blocks.rows() {
-            let item = (self.row, &self.landed_blocks.landed[self.row]);
-            self.row += 1;
+        if self.index < self.landed_blocks.landed.len() {
+            let row = self.index as isize - HIDDEN_ROWS as isize;
+            let item = (row, &self.landed_blocks.landed[self.index]);
+            self.index += 1;
 
             Some(item)
         } else {
@@ -166,27 +203,38 @@ impl<'a> Iterator for LandedBlocksRowIterator<'a> {
 }
 
 impl LandedBlocksGrid {
-    pub fn new() -> Self {
+    pub fn new(rows: usize, columns: usize) -> Self {
         LandedBlocksGrid {
-            landed: [LandedBlocksGridRow::new(); 20],
+            landed: vec![LandedBlocksGridRow::new(columns); HIDDEN_ROWS + rows],
+            columns: columns,
+        }
+    }
+
+    /// The index into the grid of a row, if the row is in the visible playing 
+    /// field or the buffer zone.
+    #[inline]
+    fn row_index(&self, row: isize) -> Option<usize> {
+        let index = row + HIDDEN_ROWS as isize;
+        if index >= 0 && index < self.landed.len() as isize {
+            Some(index as usize)
+        } else {
+            None
         }
     }
 
     pub fn get(&self, row: isize, column: isize) -> LandedBlocksQuery {
-        let rows = self.rows() as isize;
         let columns = self.columns() as isize;
-        if row < 0 || row >= rows || column < 0 || column >= columns {
-            LandedBlocksQuery::OutOfBounds(row, column)
-        } else {
-            LandedBlocksQuery::InOfBounds(self.landed[row as usize][column as usize])
+        match self.row_index(row) {
+            Some(row_idx) if column >= 0 && column < columns => {
+                LandedBlocksQuery::InOfBounds(self.landed[row_idx][column as usize])
+            }
+            _ => LandedBlocksQuery::OutOfBounds(row, column),
         }
     }
 
     pub fn insert(&mut self, row: isize, column: isize, new_element: GooglyBlockElement) {
-        let rows = self.rows() as isize;
         let columns = self.columns() as isize;
-        if (row >= 0) && (row < rows) && (column >= 0) && (column < columns) {
-            let row_idx = row as usize;
+        if let (Some(row_idx), true) = (self.row_index(row), column >= 0 && column < columns) {
             let column_idx = column as usize;
             let old_element = self.landed[row_idx][column_idx];
             if old_element.is_empty() && new_element.is_not_empty() {
@@ -202,8 +250,7 @@ impl LandedBlocksGrid {
         self.insert(row, column, GooglyBlockElement::EmptySpace);
     }
 
-    pub fn insert_block(&mut self, tl_row: isize, tl_column: isize, block: GooglyBlock) {
-        let shape = block.shape();
+    pub fn insert_block(&mut self, tl_row: isize, tl_column: isize, shape: &GooglyBlockShape) {
         for (row, column) in shape.iter().map(|(r, c)| (r as isize, c as isize)) {
             self.insert(tl_row + row, tl_column + column, shape.element);
         }
@@ -211,22 +258,63 @@ impl LandedBlocksGrid {
 
     #[inline]
     pub fn has_empty_row(&self, row: isize) -> bool {
-        if row >= 0 { 
-            let row_query = row as usize;
-            if row_query < self.rows() {
-                return self.landed[row_query].is_empty();
+        match self.row_index(row) {
+            Some(row_idx) => self.landed[row_idx].is_empty(),
+            None => false,
+        }
+    }
+
+    /// Determine whether every row of the grid, including the buffer zone, is empty.
+    pub fn is_empty(&self) -> bool {
+        self.row_iter().all(|(_, row)| row.is_empty())
+    }
+
+    /// Push garbage rows up from the bottom of the grid, one row for each hole 
+    /// column in order, so the last row pushed ends up at the bottom. Every cell of
+    /// a garbage row except its hole is filled with garbage. The rows at the top 
+    /// of the buffer zone fall off the grid to make room. A hole past the right 
+    /// wall goes in the rightmost column, so a garbage row always has a hole. 
+    /// Returns whether any of the rows that fell off had blocks in them.
+    pub fn push_garbage_rows(&mut self, holes: &[usize]) -> bool {
+        let mut overflowed = false;
+        for hole in holes.iter() {
+            let top_row = self.landed.remove(0);
+            overflowed = overflowed || !top_row.is_empty();
+
+            let hole = usize::min(*hole, self.columns - 1);
+            let mut garbage_row = LandedBlocksGridRow::new(self.columns);
+            for column in (0..self.columns).filter(|column| *column != hole) {
+                garbage_row[column] = GooglyBlockElement::Garbage;
+                garbage_row.occupied += 1;
             }
+            self.landed.push(garbage_row);
         }
 
-        false
+        overflowed
+    }
+
+    /// The number of rows that still have garbage in them.
+    pub fn garbage_rows(&self) -> usize {
+        self.landed.iter()
+            .filter(|row| row.inner.contains(&GooglyBlockElement::Garbage))
+            .count()
     }
 
+    /// The number of visible rows in the playing field.
     #[inline]
-    pub fn rows(&self) -> usize { 20 }
+    pub fn rows(&self) -> usize { 
+        self.landed.len() - HIDDEN_ROWS
+    }
+
+    /// The number of rows in the buffer zone above the visible playing field.
+    #[inline]
+    pub fn hidden_rows(&self) -> usize {
+        HIDDEN_ROWS
+    }
 
     #[inline]
     pub fn columns(&self) -> usize { 
-        self.landed[0].len()
+        self.columns
     }
 
     fn iter(&self) -> LandedBlocksIterator {
@@ -241,7 +329,7 @@ impl LandedBlocksGrid {
     fn row_iter(&self) -> LandedBlocksRowIterator {
         LandedBlocksRowIterator {
             landed_blocks: self,
-            row: 0,
+            index: 0,
         }
     }
 }
@@ -249,14 +337,14 @@ impl LandedBlocksGrid {
 impl fmt::Display for LandedBlocksGrid {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {    
         let mut disp = format!("{}", "");
-        for row in 0..self.rows() {
+        for (_, row) in self.row_iter().skip(HIDDEN_ROWS) {
             disp.push_str("| ");
             for column in 0..self.columns() {
-                disp.push_str(&format!("{} ", self.landed[row][column]));
+                disp.push_str(&format!("{} ", row[column]));
             }
             disp.push_str("|\n");
         }
-        disp.push_str("|=====================|");
+        disp.push_str(&format!("|{}|", "=".repeat(2 * self.columns() + 1)));
 
         write!(f, "{}", disp)
     }
@@ -280,8 +368,7 @@ impl BlockPosition {
 }
 
 
-fn collides_with_element(piece: GooglyBlock, top_left: BlockPosition, landed: &LandedBlocksGrid) -> bool {
-    let shape = piece.shape();
+pub(crate) fn collides_with_element(shape: &GooglyBlockShape, top_left: BlockPosition, landed: &LandedBlocksGrid) -> bool {
     for (row, column) in shape.iter() {
         let element_row = row as isize;
         let element_column = column as isize;
@@ -295,8 +382,7 @@ fn collides_with_element(piece: GooglyBlock, top_left: BlockPosition, landed: &L
     false
 }
 
-fn collides_with_left_wall(piece: GooglyBlock, top_left: BlockPosition, landed: &LandedBlocksGrid) -> bool {
-    let shape = piece.shape();
+pub(crate) fn collides_with_left_wall(shape: &GooglyBlockShape, top_left: BlockPosition) -> bool {
     for (_, column) in shape.iter() {
         let element_column = column as isize;
         if top_left.column + element_column < 0 {
@@ -307,8 +393,7 @@ fn collides_with_left_wall(piece: GooglyBlock, top_left: BlockPosition, landed:
     false
 }
 
-fn collides_with_right_wall(piece: GooglyBlock, top_left: BlockPosition, landed: &LandedBlocksGrid) -> bool {
-    let shape = piece.shape();
+pub(crate) fn collides_with_right_wall(shape: &GooglyBlockShape, top_left: BlockPosition, landed: &LandedBlocksGrid) -> bool {
     for (_, column) in shape.iter() {
         let element_column = column as isize;
         if top_left.column + element_column >= landed.columns() as isize {
@@ -319,8 +404,7 @@ fn collides_with_right_wall(piece: GooglyBlock, top_left: BlockPosition, landed:
     false
 }
 
-fn collides_with_floor(piece: GooglyBlock, top_left: BlockPosition, landed: &LandedBlocksGrid) -> bool {
-    let shape = piece.shape();
+pub(crate) fn collides_with_floor(shape: &GooglyBlockShape, top_left: BlockPosition, landed: &LandedBlocksGrid) -> bool {
     for (row, _) in shape.iter() {
         let part_row = row as isize;
         if top_left.row + part_row >= landed.rows() as isize {
@@ -331,25 +415,95 @@ fn collides_with_floor(piece: GooglyBlock, top_left: BlockPosition, landed: &Lan
     false
 }
 
+/// The kind of spin a T block locked with, under the 3-corner rule.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TSpin {
+    /// The block did not lock with a T-spin.
+    None,
+    /// A T-spin with only one of the corners in front of the point occupied.
+    Mini,
+    /// A T-spin with both of the corners in front of the point occupied.
+    Full,
+}
+
+/// The ways a game can end when the blocks reach the top of the playing field.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TopOut {
+    /// A new block spawned overlapping the landed blocks.
+    BlockOut,
+    /// A block locked entirely inside the hidden buffer zone.
+    LockOut,
+    /// Rising garbage pushed the stack past the top of the buffer zone, or left
+    /// no room for the current block.
+    GarbageOut,
+}
+
+impl fmt::Display for TopOut {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let disp = match *self {
+            TopOut::BlockOut => "BLOCK OUT",
+            TopOut::LockOut => "LOCK OUT",
+            TopOut::GarbageOut => "GARBAGE OUT",
+        };
+
+        write!(f, "{}", disp)
+    }
+}
+
+/// The index in the SRS kick tables of the kick that turns a T-spin mini
+/// into a full T-spin.
+const T_SPIN_TRIPLE_KICK: usize = 4;
+
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub enum GooglyBlockMove {
     Left,
     Right,
     Down,
     Fall,
-    Rotate,
+    RotateClockwise,
+    RotateCounterClockwise,
+    Hold,
+    HardDrop,
+    SonicDrop,
+}
+
+/// The position each piece in a piece set spawns at in a playing field with the
+/// given number of columns. Unless its definition says otherwise, a piece spawns
+/// centred in the playing field.
+pub fn starting_positions(piece_set: &PieceSet, columns: usize) -> HashMap<GooglyBlockPiece, BlockPosition> {
+    piece_set.definitions().iter()
+        .map(|definition| (definition.piece, definition.starting_position(columns)))
+        .collect()
 }
 
 pub struct PlayingFieldContextSpec {
     pub starting_block: GooglyBlock,
     pub starting_positions: HashMap<GooglyBlockPiece, BlockPosition>,
+    /// The pieces the game is played with.
+    pub piece_set: Rc<PieceSet>,
+    pub rotation_system: RotationSystemKind,
+    /// The number of rows in the playing field.
+    pub rows: usize,
+    /// The number of columns in the playing field.
+    pub columns: usize,
 }
 
 pub struct PlayingFieldContext {
     pub current_block: GooglyBlock,
     pub current_position: BlockPosition,
     pub landed_blocks: LandedBlocksGrid,
+    /// The block the player has put aside, if any.
+    pub held_block: Option<GooglyBlock>,
+    /// Whether the player may still hold the current block. The player may
+    /// hold at most once per block.
+    can_hold: bool,
+    /// Whether the last move that changed the current block was a rotation.
+    last_move_was_rotation: bool,
+    /// The index of the kick the last rotation used.
+    last_kick: usize,
     starting_positions: HashMap<GooglyBlockPiece, BlockPosition>,
+    piece_set: Rc<PieceSet>,
+    rotation_system: Box<dyn RotationSystem>,
 }
 
 impl PlayingFieldContext {
@@ -357,8 +511,98 @@ impl PlayingFieldContext {
         PlayingFieldContext {
             current_block: spec.starting_block,
             current_position: spec.starting_positions[&spec.starting_block.piece],
-            landed_blocks: LandedBlocksGrid::new(),
+            landed_blocks: LandedBlocksGrid::new(spec.rows, spec.columns),
+            held_block: None,
+            can_hold: true,
+            last_move_was_rotation: false,
+            last_kick: 0,
             starting_positions: spec.starting_positions,
+            piece_set: spec.piece_set,
+            rotation_system: spec.rotation_system.create(),
+        }
+    }
+
+    /// The pieces the game is played with.
+    #[inline]
+    pub fn piece_set(&self) -> &PieceSet {
+        &self.piece_set
+    }
+
+    /// The shape of the current block.
+    #[inline]
+    pub fn current_shape(&self) -> &GooglyBlockShape {
+        self.piece_set.shape(self.current_block)
+    }
+
+    /// Determine whether the player may still hold the current block.
+    #[inline]
+    pub fn can_hold(&self) -> bool {
+        self.can_hold
+    }
+
+    /// Hold the current block, swapping it with the held block. The first time the
+    /// player holds, the held slot is empty, so the next block takes the current
+    /// block's place. Returns whether the current block was held.
+    pub fn hold(&mut self, next_block: &mut NextBlockCell) -> bool {
+        if !self.can_hold {
+            return false;
+        }
+        if self.held_block.is_none() {
+            self.held_block = Some(next_block.current_block());
+            next_block.update();
+        }
+        self.update_block_position(GooglyBlockMove::Hold);
+
+        true
+    }
+
+    /// Determine whether the last move that changed the current block was a rotation.
+    #[inline]
+    pub fn last_move_was_rotation(&self) -> bool {
+        self.last_move_was_rotation
+    }
+
+    /// The index of the kick the last rotation of the current block used.
+    #[inline]
+    pub fn last_kick(&self) -> usize {
+        self.last_kick
+    }
+
+    /// Classify the spin the current block would lock with in its current position.
+    /// A T block spins when its last move was a rotation and at least three of the 
+    /// four corners around its centre are occupied. Walls and the floor count as
+    /// occupied. It is a full T-spin when both corners in front of its point are 
+    /// occupied, or when it got there with the last kick in the SRS table, and a 
+    /// mini T-spin otherwise.
+    pub fn t_spin(&self) -> TSpin {
+        if self.current_block.piece != GooglyBlockPiece::T || !self.last_move_was_rotation {
+            return TSpin::None;
+        }
+
+        // The centre of the block, and the corners in front of and behind its point.
+        let (center, front, back) = match self.current_block.rotation {
+            GooglyBlockRotation::R0 => ((0, 1), [(1, -1), (1, 1)], [(-1, -1), (-1, 1)]),
+            GooglyBlockRotation::R1 => ((1, 1), [(-1, -1), (1, -1)], [(-1, 1), (1, 1)]),
+            GooglyBlockRotation::R2 => ((1, 1), [(-1, -1), (-1, 1)], [(1, -1), (1, 1)]),
+            GooglyBlockRotation::R3 => ((1, 1), [(-1, 1), (1, 1)], [(-1, -1), (1, -1)]),
+        };
+        let occupied = |(row, column): (isize, isize)| {
+            let corner_row = self.current_position.row + center.0 + row;
+            let corner_column = self.current_position.column + center.1 + column;
+            match self.landed_blocks.get(corner_row, corner_column) {
+                LandedBlocksQuery::InOfBounds(GooglyBlockElement::EmptySpace) => false,
+                LandedBlocksQuery::InOfBounds(_) => true,
+                LandedBlocksQuery::OutOfBounds(_, _) => corner_row >= -(HIDDEN_ROWS as isize),
+            }
+        };
+        let front_corners = front.iter().filter(|corner| occupied(**corner)).count();
+        let back_corners = back.iter().filter(|corner| occupied(**corner)).count();
+        if front_corners + back_corners < 3 {
+            TSpin::None
+        } else if front_corners == 2 || self.last_kick == T_SPIN_TRIPLE_KICK {
+            TSpin::Full
+        } else {
+            TSpin::Mini
         }
     }
 
@@ -366,7 +610,7 @@ impl PlayingFieldContext {
         let mut full_row_count = 0;
         for (i, row_i) in self.landed_blocks.row_iter() {
             if row_i.is_full() {
-                out[full_row_count] = i as isize;
+                out[full_row_count] = i;
                 full_row_count += 1;
             }
         }
@@ -377,84 +621,159 @@ impl PlayingFieldContext {
     pub fn has_empty_row(&self, row: isize) -> bool {
         self.landed_blocks.has_empty_row(row)
     }
+
+    /// Determine whether the current block overlaps the landed blocks, which 
+    /// happens when a new block spawns on top of the stack.
+    pub fn is_blocked_out(&self) -> bool {
+        collides_with_element(self.current_shape(), self.current_position, &self.landed_blocks)
+    }
+
+    /// Push garbage rows up from the bottom of the playing field, one for each hole
+    /// column. When the garbage would overlap the current block, the current block
+    /// rises with the stack. Returns whether the garbage topped out the player.
+    pub fn push_garbage(&mut self, holes: &[usize]) -> bool {
+        let overflowed = self.landed_blocks.push_garbage_rows(holes);
+        let mut rows_lifted = 0;
+        let top_row = -(HIDDEN_ROWS as isize);
+        while self.is_blocked_out() && rows_lifted < holes.len() && self.current_position.row > top_row {
+            self.current_position.row -= 1;
+            rows_lifted += 1;
+        }
+
+        overflowed || self.is_blocked_out()
+    }
+
+    /// Determine whether the current block lies entirely inside the hidden buffer 
+    /// zone above the visible playing field.
+    pub fn is_locked_out(&self) -> bool {
+        let top_left = self.current_position;
+        self.current_shape().iter().all(|(row, _)| top_left.row + (row as isize) < 0)
+    }
     
     pub fn update_block_position(&mut self, block_move: GooglyBlockMove) {
+        let old_position = self.current_position;
         match block_move {
             GooglyBlockMove::Fall => {
                 let potential_top_left = BlockPosition::new(self.current_position.row + 1, self.current_position.column);
-                let collides_with_element = collides_with_element(self.current_block, potential_top_left, &self.landed_blocks);
-                let collides_with_floor = collides_with_floor(self.current_block, potential_top_left, &self.landed_blocks);
+                let collides_with_element = collides_with_element(self.current_shape(), potential_top_left, &self.landed_blocks);
+                let collides_with_floor = collides_with_floor(self.current_shape(), potential_top_left, &self.landed_blocks);
                 if !(collides_with_element || collides_with_floor) {
                     self.current_position = potential_top_left;
                 } 
             }
             GooglyBlockMove::Right => {
                 let potential_top_left = BlockPosition::new(self.current_position.row, self.current_position.column + 1);
-                let collides_with_element = collides_with_element(self.current_block, potential_top_left, &self.landed_blocks);
-                let collides_with_right_wall = collides_with_right_wall(self.current_block, potential_top_left, &self.landed_blocks);
+                let collides_with_element = collides_with_element(self.current_shape(), potential_top_left, &self.landed_blocks);
+                let collides_with_right_wall = collides_with_right_wall(self.current_shape(), potential_top_left, &self.landed_blocks);
                 if !(collides_with_element || collides_with_right_wall) {
                     self.current_position = potential_top_left;
                 } 
             }
             GooglyBlockMove::Left => {
                 let potential_top_left = BlockPosition::new(self.current_position.row, self.current_position.column - 1);
-                let collides_with_element = collides_with_element(self.current_block, potential_top_left, &self.landed_blocks);
-                let collides_with_right_wall = collides_with_left_wall(self.current_block, potential_top_left, &self.landed_blocks);
+                let collides_with_element = collides_with_element(self.current_shape(), potential_top_left, &self.landed_blocks);
+                let collides_with_right_wall = collides_with_left_wall(self.current_shape(), potential_top_left);
                 if !(collides_with_element || collides_with_right_wall) {
                     self.current_position = potential_top_left;
                 }             
             }
             GooglyBlockMove::Down => {
                 let potential_top_left = BlockPosition::new(self.current_position.row + 1, self.current_position.column);
-                let collides_with_element = collides_with_element(self.current_block, potential_top_left, &self.landed_blocks);
-                let collides_with_floor = collides_with_floor(self.current_block, potential_top_left, &self.landed_blocks);
+                let collides_with_element = collides_with_element(self.current_shape(), potential_top_left, &self.landed_blocks);
+                let collides_with_floor = collides_with_floor(self.current_shape(), potential_top_left, &self.landed_blocks);
                 if !(collides_with_element || collides_with_floor) {
                     self.current_position = potential_top_left;
                 } 
             }
-            GooglyBlockMove::Rotate => {
-                let potential_top_left = self.current_position;
-                let potential_block = self.current_block.rotate();
-                let potential_block_shape = potential_block.shape();
-
-                let collides_with_left_wall = collides_with_left_wall(potential_block, potential_top_left, &self.landed_blocks);
-                let collides_with_right_wall = collides_with_right_wall(potential_block, potential_top_left, &self.landed_blocks);
-                if collides_with_left_wall {
-                    let potential_top_left = BlockPosition::new(
-                        potential_top_left.row, potential_top_left.column + potential_block_shape.wall_kick_distance
-                    );
-                    let collides_with_element = collides_with_element(potential_block, potential_top_left, &self.landed_blocks);
-                    let collides_with_floor = collides_with_floor(potential_block, potential_top_left, &self.landed_blocks);
-                    if !(collides_with_element || collides_with_floor) {
-                        self.current_position = potential_top_left;
-                        self.current_block = potential_block;
-                    } 
-                } else if collides_with_right_wall {
-                    let potential_top_left = BlockPosition::new(
-                        potential_top_left.row, potential_top_left.column - potential_block_shape.wall_kick_distance
-                    );
-                    let collides_with_element = collides_with_element(potential_block, potential_top_left, &self.landed_blocks);
-                    let collides_with_floor = collides_with_floor(potential_block, potential_top_left, &self.landed_blocks);
-                    if !(collides_with_element || collides_with_floor) {
-                        self.current_position = potential_top_left;
-                        self.current_block = potential_block;
-                    } 
-                } else {
-                    let collides_with_element = collides_with_element(potential_block, potential_top_left, &self.landed_blocks);
-                    let collides_with_floor = collides_with_floor(potential_block, potential_top_left, &self.landed_blocks);
-                    if !(collides_with_element || collides_with_floor) {
-                        self.current_block = potential_block;
-                    } 
+            GooglyBlockMove::RotateClockwise => {
+                let potential_block = self.current_block.rotate_cw();
+                self.rotate_block(potential_block);
+            }
+            GooglyBlockMove::RotateCounterClockwise => {
+                let potential_block = self.current_block.rotate_ccw();
+                self.rotate_block(potential_block);
+            }
+            GooglyBlockMove::HardDrop | GooglyBlockMove::SonicDrop => {
+                self.current_position = self.landing_position();
+            }
+            GooglyBlockMove::Hold => {
+                // Swap the current block with the held block. Both blocks go back
+                // to their spawn state.
+                if let Some(held_block) = self.held_block {
+                    if self.can_hold {
+                        let current_piece = self.current_block.piece;
+                        self.held_block = Some(GooglyBlock::new(current_piece, GooglyBlockRotation::R0));
+                        self.current_block = GooglyBlock::new(held_block.piece, GooglyBlockRotation::R0);
+                        self.current_position = self.starting_positions[&held_block.piece];
+                        self.can_hold = false;
+                        // The block swapped in has not rotated yet, even if it spawns
+                        // where the old block sat.
+                        self.last_move_was_rotation = false;
+                        self.last_kick = 0;
+                    }
                 }
             }
         }
+
+        let is_rotation = block_move == GooglyBlockMove::RotateClockwise || 
+            block_move == GooglyBlockMove::RotateCounterClockwise;
+        if !is_rotation && self.current_position != old_position {
+            self.last_move_was_rotation = false;
+        }
+    }
+
+    /// Replace the current block with its rotated version, trying each kick from
+    /// the rotation system in turn. The rotation fails if the rotated block does
+    /// not fit at any of them.
+    fn rotate_block(&mut self, potential_block: GooglyBlock) {
+        let potential_shape = self.piece_set.shape(potential_block);
+        let kicks = self.rotation_system.kicks(
+            self.current_block, potential_block, potential_shape, self.current_position, &self.landed_blocks
+        );
+        for (kick, (row, column)) in kicks.iter().enumerate() {
+            let potential_top_left = BlockPosition::new(
+                self.current_position.row + row, self.current_position.column + column
+            );
+            let collides_with_element = collides_with_element(potential_shape, potential_top_left, &self.landed_blocks);
+            let collides_with_floor = collides_with_floor(potential_shape, potential_top_left, &self.landed_blocks);
+            let collides_with_left_wall = collides_with_left_wall(potential_shape, potential_top_left);
+            let collides_with_right_wall = collides_with_right_wall(potential_shape, potential_top_left, &self.landed_blocks);
+            if !(collides_with_element || collides_with_floor || collides_with_left_wall || collides_with_right_wall) {
+                self.current_position = potential_top_left;
+                self.current_block = potential_block;
+                self.last_move_was_rotation = true;
+                self.last_kick = kick;
+                return;
+            }
+        }
+    }
+
+    /// The number of rows the current block can fall before it lands.
+    pub fn drop_distance(&self) -> isize {
+        let mut distance = 0;
+        loop {
+            let potential_top_left = BlockPosition::new(
+                self.current_position.row + distance + 1, self.current_position.column
+            );
+            let collides_with_element = collides_with_element(self.current_shape(), potential_top_left, &self.landed_blocks);
+            let collides_with_floor = collides_with_floor(self.current_shape(), potential_top_left, &self.landed_blocks);
+            if collides_with_element || collides_with_floor {
+                return distance;
+            }
+            distance += 1;
+        }
+    }
+
+    /// The position where the current block would land if it dropped straight down.
+    pub fn landing_position(&self) -> BlockPosition {
+        BlockPosition::new(self.current_position.row + self.drop_distance(), self.current_position.column)
     }
 
     pub fn collapse_empty_rows(&mut self) {
-        for row in 0..self.landed_blocks.rows() {
+        for row in 0..self.landed_blocks.landed.len() {
             if self.landed_blocks.landed[row].is_empty() {
                 for above_row in 0..row {
-                    self.landed_blocks.landed[row - above_row] = self.landed_blocks.landed[row - above_row - 1];
+                    self.landed_blocks.landed[row - above_row] = self.landed_blocks.landed[row - above_row - 1].clone();
                 }
                 self.landed_blocks.landed[0].clear();
             }
@@ -462,18 +781,21 @@ impl PlayingFieldContext {
     }
     
     pub fn update_landed(&mut self) {
-        let block = self.current_block;
+        let shape = self.piece_set.shape(self.current_block);
         let position = self.current_position;
-        self.landed_blocks.insert_block(position.row, position.column, block);
+        self.landed_blocks.insert_block(position.row, position.column, shape);
     }
 
     pub fn update_new_block(&mut self, block: GooglyBlock) {
         self.current_block = block;
         self.current_position = self.starting_positions[&block.piece];
+        self.can_hold = true;
+        self.last_move_was_rotation = false;
+        self.last_kick = 0;
     }
 
     pub fn collides_with_element_below(&self) -> bool {
-        let shape = self.current_block.shape();
+        let shape = self.current_shape();
         let top_left = self.current_position;
         let landed = &self.landed_blocks;
         for (row, column) in shape.iter() {
@@ -490,7 +812,7 @@ impl PlayingFieldContext {
     }
     
     pub fn collides_with_floor_below(&self) -> bool {
-        let shape = self.current_block.shape();
+        let shape = self.current_shape();
         let top_left = self.current_position;
         for (row, _) in shape.iter() {
             let part_row = row as isize;
@@ -507,7 +829,7 @@ impl PlayingFieldContext {
     }
     
     pub fn collides_with_left_wall(&self) -> bool {
-        let shape = self.current_block.shape();
+        let shape = self.current_shape();
         let top_left = self.current_position;
         for (_, column) in shape.iter() {
             let element_column = column as isize;
@@ -524,7 +846,7 @@ impl PlayingFieldContext {
     }
     
     pub fn collides_with_right_wall(&self) -> bool {
-        let shape = self.current_block.shape();
+        let shape = self.current_shape();
         let top_left = self.current_position;
         let landed = &self.landed_blocks;
         for (_, column) in shape.iter() {
@@ -547,19 +869,23 @@ mod landed_blocks_tests {
         GooglyBlockElement, 
         GooglyBlockRotation, 
     };
+    use crate::piece_set::{
+        PieceSet,
+    };
     use super::{
         LandedBlocksGrid, 
-        LandedBlocksQuery
+        LandedBlocksQuery,
+        HIDDEN_ROWS,
     };
 
-    fn elements() -> [GooglyBlockElement; 8] { 
+    fn elements() -> [GooglyBlockElement; 10] { 
         use self::GooglyBlockElement::*;
-        [T, J, Z, O, S, L, I, EmptySpace]
+        [T, J, Z, O, S, L, I, Garbage, Custom(0), EmptySpace]
     }
     
     #[test]
     fn inserting_an_element_and_getting_it_back_yields_the_same_element() {
-        let mut landed = LandedBlocksGrid::new();
+        let mut landed = LandedBlocksGrid::new(20, 10);
         for element in elements().iter() {
             for (row, column) in landed.iter() {
                 landed.insert(row, column, *element);
@@ -573,7 +899,7 @@ mod landed_blocks_tests {
 
     #[test]
     fn inserting_the_same_element_to_the_same_position_twice_is_the_same_as_inserting_it_once() {
-        let mut landed = LandedBlocksGrid::new();
+        let mut landed = LandedBlocksGrid::new(20, 10);
         for element in elements().iter() {
             for (row, column) in landed.iter() {
                 landed.insert(row, column, *element);
@@ -588,7 +914,7 @@ mod landed_blocks_tests {
 
     #[test]
     fn all_cells_in_a_new_landed_blocks_matrix_should_be_empty_spaces() {
-        let landed = LandedBlocksGrid::new();
+        let landed = LandedBlocksGrid::new(20, 10);
         let expected = LandedBlocksQuery::InOfBounds(GooglyBlockElement::EmptySpace);
         for (row, column) in landed.iter() {
             let result = landed.get(row, column);
@@ -598,12 +924,12 @@ mod landed_blocks_tests {
 
     #[test]
     fn inserting_a_block_into_landed_blocks_and_getting_it_back_yields_the_same_elements() {
-        let block = GooglyBlock::new(GooglyBlockPiece::J, GooglyBlockRotation::R0);
-        let shape = block.shape();
-        let mut landed = LandedBlocksGrid::new();
+        let piece_set = PieceSet::standard();
+        let shape = piece_set.shape(GooglyBlock::new(GooglyBlockPiece::J, GooglyBlockRotation::R0));
+        let mut landed = LandedBlocksGrid::new(20, 10);
         let top_left_row = 5;
         let top_left_column = 6;
-        landed.insert_block(top_left_row, top_left_column, block);
+        landed.insert_block(top_left_row, top_left_column, shape);
         
         let expected = LandedBlocksQuery::InOfBounds(GooglyBlockElement::J);
         for (row, column) in shape.iter().map(|(r, c)| (r as isize, c as isize)) {
@@ -612,29 +938,83 @@ mod landed_blocks_tests {
         }
     }
 
+    /// A row above the buffer zone should be out of bounds.
+    #[test]
+    fn getting_an_element_from_a_row_above_the_buffer_zone_should_be_out_of_bounds() {
+        let landed = LandedBlocksGrid::new(20, 10);
+        assert!(landed.get(-(HIDDEN_ROWS as isize) - 1, 1).is_out_of_bounds());
+    }
+
+    /// Blocks inserted into the buffer zone should be kept like any other landed block.
     #[test]
-    fn getting_an_element_from_a_negative_valued_row_should_be_out_of_bounds() {
-        let landed = LandedBlocksGrid::new();
-        assert!(landed.get(-1, 1).is_out_of_bounds());
+    fn elements_inserted_into_the_buffer_zone_should_be_kept() {
+        let mut landed = LandedBlocksGrid::new(20, 10);
+        landed.insert(-1, 1, GooglyBlockElement::T);
+        landed.insert(-(HIDDEN_ROWS as isize), 1, GooglyBlockElement::T);
+
+        assert_eq!(landed.get(-1, 1), LandedBlocksQuery::InOfBounds(GooglyBlockElement::T));
+        assert_eq!(landed.get(-(HIDDEN_ROWS as isize), 1), LandedBlocksQuery::InOfBounds(GooglyBlockElement::T));
+        assert!(!landed.is_empty());
     }
 
     #[test]
     fn getting_an_element_from_a_negative_valued_column_should_be_out_of_bounds() {
-        let landed = LandedBlocksGrid::new();
+        let landed = LandedBlocksGrid::new(20, 10);
         assert!(landed.get(1, -1).is_out_of_bounds());
     }
 
     #[test]
     fn getting_an_element_from_a_row_larger_than_the_number_of_rows_should_be_out_of_bounds() {
-        let landed = LandedBlocksGrid::new();
+        let landed = LandedBlocksGrid::new(20, 10);
         assert!(landed.get(20, 1).is_out_of_bounds());
     }
 
     #[test]
     fn getting_an_element_from_a_column_larger_than_the_number_of_columns_should_be_out_of_bounds() {
-        let landed = LandedBlocksGrid::new();
+        let landed = LandedBlocksGrid::new(20, 10);
         assert!(landed.get(1, 10).is_out_of_bounds());
     }
+
+    /// Pushing garbage rows should raise the stack and leave a hole in each garbage row.
+    #[test]
+    fn pushing_garbage_rows_should_raise_the_stack_and_leave_a_hole_in_each_row() {
+        let mut landed = LandedBlocksGrid::new(20, 10);
+        landed.insert(19, 0, GooglyBlockElement::T);
+        let overflowed = landed.push_garbage_rows(&[3, 7]);
+
+        assert!(!overflowed);
+        assert_eq!(landed.get(17, 0), LandedBlocksQuery::InOfBounds(GooglyBlockElement::T));
+        assert_eq!(landed.get(18, 3), LandedBlocksQuery::InOfBounds(GooglyBlockElement::EmptySpace));
+        assert_eq!(landed.get(19, 7), LandedBlocksQuery::InOfBounds(GooglyBlockElement::EmptySpace));
+        for column in (0..10).filter(|column| *column != 7) {
+            assert_eq!(landed.get(19, column), LandedBlocksQuery::InOfBounds(GooglyBlockElement::Garbage));
+        }
+        assert_eq!(landed.garbage_rows(), 2);
+        assert!(!landed.has_empty_row(19));
+    }
+
+    /// A hole past the right wall should leave the hole in the rightmost column 
+    /// instead of filling the whole garbage row.
+    #[test]
+    fn a_garbage_hole_past_the_right_wall_should_stay_in_the_playing_field() {
+        let mut landed = LandedBlocksGrid::new(20, 10);
+        landed.push_garbage_rows(&[10]);
+
+        assert_eq!(landed.get(19, 9), LandedBlocksQuery::InOfBounds(GooglyBlockElement::EmptySpace));
+        for column in 0..9 {
+            assert_eq!(landed.get(19, column), LandedBlocksQuery::InOfBounds(GooglyBlockElement::Garbage));
+        }
+    }
+
+    /// Pushing blocks past the top of the buffer zone should overflow the stack.
+    #[test]
+    fn pushing_blocks_past_the_top_of_the_buffer_zone_should_overflow() {
+        let mut landed = LandedBlocksGrid::new(20, 10);
+        landed.insert(-(HIDDEN_ROWS as isize) + 1, 0, GooglyBlockElement::T);
+
+        assert!(!landed.push_garbage_rows(&[0]));
+        assert!(landed.push_garbage_rows(&[0]));
+    }
 }
 
 #[cfg(test)]
@@ -644,6 +1024,10 @@ mod collision_tests {
         GooglyBlockPiece, 
         GooglyBlockElement,
         GooglyBlockRotation, 
+        GooglyBlockShape,
+    };
+    use crate::piece_set::{
+        PieceSet,
     };
     use super::{
         LandedBlocksGrid,
@@ -656,7 +1040,7 @@ mod collision_tests {
     }
 
     fn test_case() -> CollisionDetectionTestCase {
-        let mut landed = LandedBlocksGrid::new();
+        let mut landed = LandedBlocksGrid::new(20, 10);
         landed.insert(19, 8, GooglyBlockElement::J);
         landed.insert(19, 9, GooglyBlockElement::J);
         landed.insert(18, 9, GooglyBlockElement::J);
@@ -705,7 +1089,7 @@ mod collision_tests {
         }
     }
 
-    fn failed(piece: GooglyBlock, top_left: BlockPosition, landed: &LandedBlocksGrid) -> String {
+    fn failed(piece: &GooglyBlockShape, top_left: BlockPosition, landed: &LandedBlocksGrid) -> String {
         let mut new_landed = (*landed).clone();
         new_landed.insert_block(top_left.row, top_left.column, piece);
         format!("{}", new_landed)
@@ -713,8 +1097,9 @@ mod collision_tests {
 
     #[test]
     fn block_elements_should_not_collide_with_unoccupied_cells() {
-        let empty_landed = LandedBlocksGrid::new();
-        let piece = GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0);
+        let empty_landed = LandedBlocksGrid::new(20, 10);
+        let piece_set = PieceSet::standard();
+        let piece = piece_set.shape(GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0));
         for (row, column) in empty_landed.iter() {
             assert!(!super::collides_with_element(
                 piece, BlockPosition::new(row, column), &empty_landed)
@@ -725,7 +1110,8 @@ mod collision_tests {
     #[test]
     fn blocks_should_collide_on_occupied_cells() {
         let test = test_case();
-        let piece = GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0);
+        let piece_set = PieceSet::standard();
+        let piece = piece_set.shape(GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0));
         for (oc_row, oc_column) in test.occupied_cells.iter() {
             let top_left = BlockPosition::new(*oc_row, *oc_column);
             assert!(super::collides_with_element(piece, top_left, &test.landed));
@@ -734,21 +1120,23 @@ mod collision_tests {
 
     #[test]
     fn blocks_crossing_leftmost_column_should_collide_with_left_wall() {
-        let landed = LandedBlocksGrid::new();
-        let piece = GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0);
+        let landed = LandedBlocksGrid::new(20, 10);
+        let piece_set = PieceSet::standard();
+        let piece = piece_set.shape(GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0));
         for row in (0..landed.rows()).map(|r| r as isize) {
             let top_left = BlockPosition::new(row, -1);
-            assert!(super::collides_with_left_wall(piece, top_left, &landed));
+            assert!(super::collides_with_left_wall(piece, top_left));
         }
     }
     
     #[test]
     fn blocks_with_elements_in_leftmost_column_should_not_collide_with_left_wall() {
-        let landed = LandedBlocksGrid::new();
-        let piece = GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0);
+        let landed = LandedBlocksGrid::new(20, 10);
+        let piece_set = PieceSet::standard();
+        let piece = piece_set.shape(GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0));
         for row in (0..landed.rows()).map(|r| r as isize) {
             let top_left = BlockPosition::new(row, 0);
-            assert!(!super::collides_with_left_wall(piece, top_left, &landed), 
+            assert!(!super::collides_with_left_wall(piece, top_left), 
                 "row: {}; column: {}", top_left.row, top_left.column
             );
         }        
@@ -756,8 +1144,9 @@ mod collision_tests {
 
     #[test]
     fn blocks_crossing_rightmost_column_should_collide_with_right_wall() {
-        let landed = LandedBlocksGrid::new();
-        let piece = GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0);
+        let landed = LandedBlocksGrid::new(20, 10);
+        let piece_set = PieceSet::standard();
+        let piece = piece_set.shape(GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0));
         for row in (0..landed.rows()).map(|r| r as isize) {
             let last_column = landed.columns() as isize - 1;
             let top_left = BlockPosition::new(row, last_column);
@@ -769,8 +1158,9 @@ mod collision_tests {
 
     #[test]
     fn blocks_with_elements_in_rightmost_column_should_not_collide_with_right_wall() {
-        let landed = LandedBlocksGrid::new();
-        let piece = GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0);
+        let landed = LandedBlocksGrid::new(20, 10);
+        let piece_set = PieceSet::standard();
+        let piece = piece_set.shape(GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0));
         for row in (0..landed.rows()).map(|r| r as isize) {
             let top_left = BlockPosition::new(row, 7);
             assert!(!super::collides_with_right_wall(piece, top_left, &landed), 
@@ -781,8 +1171,9 @@ mod collision_tests {
 
     #[test]
     fn blocks_crossing_floor_should_collide_with_floor() {
-        let landed = LandedBlocksGrid::new();
-        let piece = GooglyBlock::new(GooglyBlockPiece::I, GooglyBlockRotation::R0);
+        let landed = LandedBlocksGrid::new(20, 10);
+        let piece_set = PieceSet::standard();
+        let piece = piece_set.shape(GooglyBlock::new(GooglyBlockPiece::I, GooglyBlockRotation::R0));
         for column in (0..landed.columns()).map(|c| c as isize) {
             let last_row = (landed.rows() - 1) as isize;
             let top_left = BlockPosition::new(last_row, column);
@@ -792,8 +1183,9 @@ mod collision_tests {
 
     #[test]
     fn blocks_whose_bottom_elements_occupy_bottommost_row_should_not_collide_with_floor() {
-        let landed = LandedBlocksGrid::new();
-        let piece = GooglyBlock::new(GooglyBlockPiece::I, GooglyBlockRotation::R0);
+        let landed = LandedBlocksGrid::new(20, 10);
+        let piece_set = PieceSet::standard();
+        let piece = piece_set.shape(GooglyBlock::new(GooglyBlockPiece::I, GooglyBlockRotation::R0));
         for column in (0..landed.columns()).map(|c| c as isize) {
             let row = (landed.rows() - 3) as isize;
             let top_left = BlockPosition::new(row, column);
@@ -818,15 +1210,24 @@ mod playing_field_tests {
         PlayingFieldContext, 
         PlayingFieldContextSpec, 
         GooglyBlockMove,
+        TSpin,
+        HIDDEN_ROWS,
+    };
+    use crate::piece_set::{
+        PieceSet,
+    };
+    use crate::rotation::{
+        RotationSystemKind,
     };
     use std::collections::hash_map::HashMap;
+    use std::rc::Rc;
 
     struct PlayingFieldTestCase {
         playing_field: PlayingFieldContext,
     }
 
     fn test_case() -> PlayingFieldTestCase {
-        let mut landed_blocks = LandedBlocksGrid::new();
+        let mut landed_blocks = LandedBlocksGrid::new(20, 10);
         landed_blocks.insert(19, 8, GooglyBlockElement::J);
         landed_blocks.insert(19, 9, GooglyBlockElement::J);
         landed_blocks.insert(18, 9, GooglyBlockElement::J);
@@ -877,6 +1278,10 @@ mod playing_field_tests {
         let spec = PlayingFieldContextSpec {
             starting_block: starting_block,
             starting_positions: starting_positions,
+            piece_set: Rc::new(PieceSet::standard()),
+            rotation_system: RotationSystemKind::Classic,
+            rows: 20,
+            columns: 10,
         };
         let mut playing_field = PlayingFieldContext::new(spec);
         playing_field.landed_blocks = landed_blocks;
@@ -901,6 +1306,10 @@ mod playing_field_tests {
         let spec = PlayingFieldContextSpec {
             starting_block: starting_block,
             starting_positions: starting_positions,
+            piece_set: Rc::new(PieceSet::standard()),
+            rotation_system: RotationSystemKind::Classic,
+            rows: 20,
+            columns: 10,
         };
         let playing_field = PlayingFieldContext::new(spec);
 
@@ -925,7 +1334,7 @@ mod playing_field_tests {
         for mv in moves.iter() {
             playing_field.update_block_position(*mv);
             let top_left = playing_field.current_position;
-            let shape = playing_field.current_block.shape();
+            let shape = playing_field.current_shape();
             let last_row = playing_field.landed_blocks.rows() as isize - 1;
             for element in shape.iter() {
                 if top_left.row + element.0 as isize == last_row {
@@ -941,7 +1350,7 @@ mod playing_field_tests {
         for mv in moves.iter() {
             playing_field.update_block_position(*mv);
             let top_left = playing_field.current_position;
-            let shape = playing_field.current_block.shape();
+            let shape = playing_field.current_shape();
             let last_column = playing_field.landed_blocks.columns() as isize - 1;
             for element in shape.iter() {
                 if top_left.column + element.1 as isize == last_column {
@@ -957,7 +1366,7 @@ mod playing_field_tests {
         for mv in moves.iter() {
             playing_field.update_block_position(*mv);
             let top_left = playing_field.current_position;
-            let shape = playing_field.current_block.shape();
+            let shape = playing_field.current_shape();
             let first_column = 0;
             for element in shape.iter() {
                 if top_left.column + element.1 as isize == first_column {
@@ -996,4 +1405,293 @@ mod playing_field_tests {
         let moves = vec![GooglyBlockMove::Left; 20];
         assert!(moves_collide_with_left_wall(&mut test.playing_field, &moves));
     }
+
+    /// Holding should swap the current block with the held block, and put the 
+    /// held block back at its spawn position in its spawn orientation.
+    #[test]
+    fn holding_should_swap_the_current_block_with_the_held_block() {
+        let mut test = empty_playing_field_test_case();
+        test.playing_field.held_block = Some(GooglyBlock::new(GooglyBlockPiece::I, GooglyBlockRotation::R0));
+        test.playing_field.update_block_position(GooglyBlockMove::RotateClockwise);
+        test.playing_field.update_block_position(GooglyBlockMove::Fall);
+        test.playing_field.update_block_position(GooglyBlockMove::Hold);
+
+        assert_eq!(
+            test.playing_field.current_block, 
+            GooglyBlock::new(GooglyBlockPiece::I, GooglyBlockRotation::R0)
+        );
+        assert_eq!(test.playing_field.current_position, BlockPosition::new(-3, 3));
+        assert_eq!(
+            test.playing_field.held_block, 
+            Some(GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0))
+        );
+    }
+
+    /// The player should only be able to hold once until the next block spawns.
+    #[test]
+    fn holding_should_only_be_allowed_once_per_block() {
+        let mut test = empty_playing_field_test_case();
+        test.playing_field.held_block = Some(GooglyBlock::new(GooglyBlockPiece::I, GooglyBlockRotation::R0));
+        test.playing_field.update_block_position(GooglyBlockMove::Hold);
+        test.playing_field.update_block_position(GooglyBlockMove::Hold);
+
+        assert!(!test.playing_field.can_hold());
+        assert_eq!(test.playing_field.current_block.piece, GooglyBlockPiece::I);
+
+        test.playing_field.update_new_block(GooglyBlock::new(GooglyBlockPiece::O, GooglyBlockRotation::R0));
+
+        assert!(test.playing_field.can_hold());
+    }
+
+    /// A block swapped in from the held slot should not inherit the rotation state
+    /// of the block it replaced.
+    #[test]
+    fn holding_should_clear_the_last_rotation() {
+        let mut test = empty_playing_field_test_case();
+        test.playing_field.held_block = Some(GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0));
+        test.playing_field.current_block = GooglyBlock::new(GooglyBlockPiece::J, GooglyBlockRotation::R0);
+        test.playing_field.last_move_was_rotation = true;
+        test.playing_field.last_kick = 4;
+        test.playing_field.update_block_position(GooglyBlockMove::Hold);
+
+        assert_eq!(test.playing_field.current_block.piece, GooglyBlockPiece::T);
+        assert!(!test.playing_field.last_move_was_rotation());
+        assert_eq!(test.playing_field.last_kick(), 0);
+    }
+
+    /// A hard drop should move the block down until it rests on the landed blocks.
+    #[test]
+    fn dropping_should_move_the_block_onto_the_landed_blocks() {
+        let mut test = test_case();
+        let moves = vec![GooglyBlockMove::HardDrop, GooglyBlockMove::Fall];
+        assert!(moves_collide_with_elements(&mut test.playing_field, &moves));
+        assert_eq!(test.playing_field.drop_distance(), 0);
+    }
+
+    /// A sonic drop in an empty playing field should move the block down to the floor.
+    #[test]
+    fn dropping_in_an_empty_playing_field_should_stop_on_floor() {
+        let mut test = empty_playing_field_test_case();
+        let moves = vec![GooglyBlockMove::SonicDrop];
+        assert!(moves_collide_with_floor(&mut test.playing_field, &moves));
+        assert_eq!(test.playing_field.drop_distance(), 0);
+    }
+
+    /// The landing position should be where the block comes to rest when it falls.
+    #[test]
+    fn the_landing_position_should_be_where_falling_stops() {
+        let mut test = test_case();
+        let landing_position = test.playing_field.landing_position();
+        let moves = vec![GooglyBlockMove::Fall; 30];
+        moves_collide_with_elements(&mut test.playing_field, &moves);
+
+        assert_eq!(test.playing_field.current_position, landing_position);
+    }
+
+    /// Rotating a block counter-clockwise and then clockwise should return it to its
+    /// starting rotation.
+    #[test]
+    fn rotating_both_ways_in_an_empty_playing_field_should_return_to_the_start() {
+        let mut test = empty_playing_field_test_case();
+        let expected = test.playing_field.current_block;
+        test.playing_field.update_block_position(GooglyBlockMove::RotateCounterClockwise);
+
+        assert_eq!(
+            test.playing_field.current_block, 
+            GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R3)
+        );
+
+        test.playing_field.update_block_position(GooglyBlockMove::RotateClockwise);
+
+        assert_eq!(test.playing_field.current_block, expected);
+    }
+
+    fn t_block_test_case(rotation: GooglyBlockRotation, corners: &[(isize, isize)]) -> PlayingFieldTestCase {
+        let mut test = empty_playing_field_test_case();
+        test.playing_field.current_block = GooglyBlock::new(GooglyBlockPiece::T, rotation);
+        test.playing_field.current_position = BlockPosition::new(10, 3);
+        for (row, column) in corners.iter() {
+            test.playing_field.landed_blocks.insert(*row, *column, GooglyBlockElement::J);
+        }
+
+        test
+    }
+
+    /// A T block rotated into place with three corners occupied, including both in
+    /// front of its point, should be a full T-spin.
+    #[test]
+    fn a_t_block_rotated_into_three_corners_with_its_point_covered_should_be_a_t_spin() {
+        let mut test = t_block_test_case(GooglyBlockRotation::R1, &[(10, 3), (10, 5), (12, 3)]);
+        test.playing_field.update_block_position(GooglyBlockMove::RotateClockwise);
+
+        assert_eq!(test.playing_field.current_block.rotation, GooglyBlockRotation::R2);
+        assert!(test.playing_field.last_move_was_rotation());
+        assert_eq!(test.playing_field.t_spin(), TSpin::Full);
+    }
+
+    /// A T block rotated into place with a corner in front of its point open should be
+    /// a T-spin mini, unless it got there with the last SRS kick.
+    #[test]
+    fn a_t_block_rotated_into_three_corners_with_its_point_open_should_be_a_t_spin_mini() {
+        let mut test = t_block_test_case(GooglyBlockRotation::R3, &[(9, 3), (9, 5), (11, 3)]);
+        test.playing_field.update_block_position(GooglyBlockMove::RotateClockwise);
+
+        assert_eq!(test.playing_field.current_block.rotation, GooglyBlockRotation::R0);
+        assert_eq!(test.playing_field.t_spin(), TSpin::Mini);
+
+        test.playing_field.last_kick = 4;
+
+        assert_eq!(test.playing_field.t_spin(), TSpin::Full);
+    }
+
+    /// A T block that moved after its last rotation should not be a T-spin.
+    #[test]
+    fn a_t_block_that_moved_after_rotating_should_not_be_a_t_spin() {
+        let mut test = t_block_test_case(GooglyBlockRotation::R1, &[]);
+        test.playing_field.update_block_position(GooglyBlockMove::RotateClockwise);
+
+        assert!(test.playing_field.last_move_was_rotation());
+
+        test.playing_field.update_block_position(GooglyBlockMove::Left);
+
+        assert!(!test.playing_field.last_move_was_rotation());
+        assert_eq!(test.playing_field.t_spin(), TSpin::None);
+    }
+
+    /// A T block with fewer than three occupied corners should not be a T-spin.
+    #[test]
+    fn a_t_block_in_an_open_area_should_not_be_a_t_spin() {
+        let mut test = t_block_test_case(GooglyBlockRotation::R1, &[(10, 3)]);
+        test.playing_field.update_block_position(GooglyBlockMove::RotateClockwise);
+
+        assert!(test.playing_field.last_move_was_rotation());
+        assert_eq!(test.playing_field.t_spin(), TSpin::None);
+    }
+
+    /// The walls should count as occupied corners around a T block.
+    #[test]
+    fn the_walls_should_count_as_occupied_corners() {
+        let mut test = t_block_test_case(GooglyBlockRotation::R3, &[(10, 1)]);
+        test.playing_field.current_position = BlockPosition::new(10, -1);
+        test.playing_field.last_move_was_rotation = true;
+
+        assert_eq!(test.playing_field.t_spin(), TSpin::Mini);
+
+        test.playing_field.landed_blocks.insert(12, 1, GooglyBlockElement::J);
+
+        assert_eq!(test.playing_field.t_spin(), TSpin::Full);
+    }
+
+    /// Every piece should spawn centred in the playing field, rounding to the left.
+    #[test]
+    fn starting_positions_should_centre_every_piece_in_the_playing_field() {
+        let pieces = [
+            GooglyBlockPiece::T, GooglyBlockPiece::J, GooglyBlockPiece::Z, GooglyBlockPiece::O,
+            GooglyBlockPiece::S, GooglyBlockPiece::L, GooglyBlockPiece::I,
+        ];
+        let piece_set = PieceSet::standard();
+        for columns in [4, 10, 20].iter() {
+            let starting_positions = super::starting_positions(&piece_set, *columns);
+            for piece in pieces.iter() {
+                let shape = piece_set.shape(GooglyBlock::new(*piece, GooglyBlockRotation::R0));
+                let left = starting_positions[piece].column;
+                let right = *columns as isize - (left + shape.columns() as isize);
+
+                assert!(left >= 0 && right >= 0, "{:?} in {} columns", piece, columns);
+                assert!(left - right == 0 || left - right == 1, "{:?} in {} columns", piece, columns);
+            }
+        }
+        assert_eq!(super::starting_positions(&piece_set, 10)[&GooglyBlockPiece::T], BlockPosition::new(-3, 4));
+        assert_eq!(super::starting_positions(&piece_set, 10)[&GooglyBlockPiece::I], BlockPosition::new(-3, 3));
+    }
+
+    /// A horizontal I block should fill a whole row of a four column playing field.
+    #[test]
+    fn a_horizontal_i_block_should_fill_a_row_of_a_narrow_playing_field() {
+        let spec = PlayingFieldContextSpec {
+            starting_block: GooglyBlock::new(GooglyBlockPiece::I, GooglyBlockRotation::R0),
+            starting_positions: super::starting_positions(&PieceSet::standard(), 4),
+            piece_set: Rc::new(PieceSet::standard()),
+            rotation_system: RotationSystemKind::Classic,
+            rows: 20,
+            columns: 4,
+        };
+        let mut playing_field = PlayingFieldContext::new(spec);
+        playing_field.update_block_position(GooglyBlockMove::HardDrop);
+        playing_field.update_landed();
+        playing_field.landed_blocks.insert(18, 0, GooglyBlockElement::J);
+        let mut full_rows = [-1; 20];
+
+        assert_eq!(playing_field.get_full_rows(&mut full_rows), 1);
+        assert_eq!(full_rows[0], 19);
+
+        for column in 0..4 {
+            playing_field.landed_blocks.clear(19, column);
+        }
+        playing_field.collapse_empty_rows();
+
+        assert_eq!(playing_field.landed_blocks.get(19, 0), super::LandedBlocksQuery::InOfBounds(GooglyBlockElement::J));
+        assert!(playing_field.has_empty_row(18));
+    }
+
+    /// Clearing rows should pull blocks down out of the buffer zone.
+    #[test]
+    fn clearing_a_row_should_pull_blocks_down_out_of_the_buffer_zone() {
+        let mut test = empty_playing_field_test_case();
+        let playing_field = &mut test.playing_field;
+        playing_field.landed_blocks.insert(-1, 0, GooglyBlockElement::J);
+        playing_field.collapse_empty_rows();
+
+        assert_eq!(playing_field.landed_blocks.get(19, 0), super::LandedBlocksQuery::InOfBounds(GooglyBlockElement::J));
+        assert!(playing_field.has_empty_row(-1));
+    }
+
+    /// A block should be locked out only when it lies entirely inside the buffer zone.
+    #[test]
+    fn a_block_entirely_in_the_buffer_zone_should_be_locked_out() {
+        let mut test = empty_playing_field_test_case();
+        let playing_field = &mut test.playing_field;
+        playing_field.current_position = BlockPosition::new(-2, 4);
+        assert!(playing_field.is_locked_out());
+
+        playing_field.current_position = BlockPosition::new(-1, 4);
+        assert!(!playing_field.is_locked_out());
+    }
+
+    /// Rising garbage should lift the current block with the stack instead of
+    /// overlapping it.
+    #[test]
+    fn rising_garbage_should_lift_the_current_block_with_the_stack() {
+        let mut test = empty_playing_field_test_case();
+        let playing_field = &mut test.playing_field;
+        playing_field.update_block_position(GooglyBlockMove::HardDrop);
+        let landing_position = playing_field.current_position;
+        let garbage_out = playing_field.push_garbage(&[0, 0]);
+
+        assert!(!garbage_out);
+        assert_eq!(playing_field.current_position.row, landing_position.row - 2);
+        assert!(!playing_field.is_blocked_out());
+    }
+
+    /// Garbage that pushes the stack past the buffer zone should top out the player.
+    #[test]
+    fn garbage_pushing_the_stack_past_the_buffer_zone_should_top_out() {
+        let mut test = empty_playing_field_test_case();
+        let playing_field = &mut test.playing_field;
+        playing_field.landed_blocks.insert(-(HIDDEN_ROWS as isize), 9, GooglyBlockElement::J);
+
+        assert!(playing_field.push_garbage(&[0]));
+    }
+
+    /// A block that overlaps the landed blocks should be blocked out.
+    #[test]
+    fn a_block_overlapping_the_landed_blocks_should_be_blocked_out() {
+        let mut test = empty_playing_field_test_case();
+        let playing_field = &mut test.playing_field;
+        assert!(!playing_field.is_blocked_out());
+
+        let position = playing_field.current_position;
+        playing_field.landed_blocks.insert(position.row, position.column, GooglyBlockElement::J);
+        assert!(playing_field.is_blocked_out());
+    }
 }
@@ -15,22 +15,38 @@
  *  You should have received a copy of the GNU General Public License
  *  along with this program.  If not, see <http://www.gnu.org/licenses/>.
  */
+use crate::auto_shift::{
+    AutoShift,
+    AutoShiftSpec,
+    Shift,
+    ShiftDirection,
+};
 use crate::input::{
     InputKind,
     InputAction,
     Input,
 };
+use crate::gravity::{
+    GravityCurve,
+};
 use crate::flashing_state_machine::{
     FlashAnimationStateMachine,
 };
+use crate::lock_delay::{
+    LockDelay,
+    LockDelayKind,
+};
 use crate::next_block::{
     NextBlockCell
 };
 use crate::playing_field::{
     GooglyBlockMove,
     PlayingFieldContext,
+    TopOut,
+    TSpin,
 };
 use crate::score::{
+    LineClear,
     ScoreBoard,
     Statistics,
 };
@@ -45,8 +61,13 @@ use std::time::Duration;
 
 
 pub fn create(spec: PlayingFieldStateMachineSpec) -> PlayingFieldStateMachine {
-    let timers = Rc::new(RefCell::new(PlayingFieldTimers::new(spec.timers)));
-    let full_rows = Rc::new(RefCell::new(FullRows::new()));
+    let level = spec.score_board.borrow().level;
+    let timers = Rc::new(RefCell::new(PlayingFieldTimers::new(spec.timers, level)));
+    let rows = {
+        let playing_field_context = spec.playing_field_context.borrow();
+        playing_field_context.landed_blocks.rows() + playing_field_context.landed_blocks.hidden_rows()
+    };
+    let full_rows = Rc::new(RefCell::new(FullRows::new(rows)));
     let context = Rc::new(RefCell::new(PlayingFieldStateMachineContext {
         timers: timers,
         playing_field_state: spec.playing_field_context,
@@ -55,19 +76,28 @@ pub fn create(spec: PlayingFieldStateMachineSpec) -> PlayingFieldStateMachine {
         score_board: spec.score_board,
         full_rows: full_rows,
         flashing_state_machine: spec.flashing_state_machine,
+        perfect_clear_flashing_state_machine: spec.perfect_clear_flashing_state_machine,
         columns_cleared: 0,
+        hard_dropped: false,
+        blocked_out: false,
+        t_spin: TSpin::None,
+        pending_garbage: vec![],
     }));
 
     PlayingFieldStateMachine::new(context)
 }
 
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub struct PlayingFieldTimerSpec {
-    pub fall_interval: Interval,
-    pub collision_interval: Interval,
-    pub left_hold_interval: Interval,
-    pub right_hold_interval: Interval,
+    /// The fall interval of the current block at each level.
+    pub gravity: GravityCurve,
+    /// How long a block can rest on the stack before it locks.
+    pub lock_delay_interval: Interval,
+    /// When the lock delay of a block starts over.
+    pub lock_delay: LockDelayKind,
+    /// How the current block shifts while the player holds left or right.
+    pub auto_shift: AutoShiftSpec,
     pub down_hold_interval: Interval,
     pub rotate_interval: Interval,
     pub clearing_interval: Interval,
@@ -77,6 +107,8 @@ pub struct PlayingFieldTimerSpec {
 pub struct PlayingFieldStateMachineSpec {
     pub timers: PlayingFieldTimerSpec,
     pub flashing_state_machine: Rc<RefCell<FlashAnimationStateMachine>>,
+    /// The animation that plays when the player clears every block from the playing field.
+    pub perfect_clear_flashing_state_machine: Rc<RefCell<FlashAnimationStateMachine>>,
     pub playing_field_context: Rc<RefCell<PlayingFieldContext>>,
     pub next_block: Rc<RefCell<NextBlockCell>>,
     pub statistics: Rc<RefCell<Statistics>>,
@@ -85,37 +117,65 @@ pub struct PlayingFieldStateMachineSpec {
 
 struct PlayingFieldTimers {
     fall_timer: Timer,
-    collision_timer: Timer,
-    left_hold_timer: Timer,
-    right_hold_timer: Timer,
+    lock_delay: LockDelay,
+    auto_shift: AutoShift,
     down_hold_timer: Timer,
     rotate_timer: Timer,
     clearing_timer: Timer,
+    gravity: GravityCurve,
+    /// The level the fall timer is currently set for.
+    level: usize,
 }
 
 impl PlayingFieldTimers {
-    fn new(spec: PlayingFieldTimerSpec) -> PlayingFieldTimers {
+    fn new(spec: PlayingFieldTimerSpec, level: usize) -> PlayingFieldTimers {
         PlayingFieldTimers {
-            fall_timer: Timer::new(spec.fall_interval),
-            collision_timer: Timer::new(spec.collision_interval),
-            left_hold_timer: Timer::new(spec.left_hold_interval),
-            right_hold_timer: Timer::new(spec.right_hold_interval),
+            fall_timer: Timer::new(spec.gravity.fall_interval(level)),
+            lock_delay: LockDelay::new(spec.lock_delay, spec.lock_delay_interval),
+            auto_shift: AutoShift::new(spec.auto_shift),
             down_hold_timer: Timer::new(spec.down_hold_interval),
             rotate_timer: Timer::new(spec.rotate_interval),
             clearing_timer: Timer::new(spec.clearing_interval),
+            gravity: spec.gravity,
+            level: level,
+        }
+    }
+
+    /// Speed up the fall timer when the player reaches a new level.
+    fn update_level(&mut self, level: usize) {
+        if level != self.level {
+            self.fall_timer.set_interval(self.gravity.fall_interval(level));
+            self.level = level;
+        }
+    }
+}
+
+/// Shift the current block sideways, restarting the lock delay for each column
+/// the block moves.
+fn shift_block(playing_field_state: &mut PlayingFieldContext, lock_delay: &mut LockDelay, block_move: GooglyBlockMove, shift: Shift) {
+    let columns = match shift {
+        Shift::Columns(columns) => columns,
+        Shift::ToWall => playing_field_state.landed_blocks.columns(),
+    };
+    for _ in 0..columns {
+        let old_position = playing_field_state.current_position;
+        playing_field_state.update_block_position(block_move);
+        if playing_field_state.current_position == old_position {
+            break;
         }
+        lock_delay.moved();
     }
 }
 
 struct FullRows {
-    rows: [isize; 20],
+    rows: Vec<isize>,
     count: usize,
 }
 
 impl FullRows {
-    fn new() -> FullRows {
+    fn new(rows: usize) -> FullRows {
         FullRows {
-            rows: [-1; 20],
+            rows: vec![-1; rows],
             count: 0,
         }
     }
@@ -136,7 +196,18 @@ struct PlayingFieldStateMachineContext {
     score_board: Rc<RefCell<ScoreBoard>>,
     full_rows: Rc<RefCell<FullRows>>,
     flashing_state_machine: Rc<RefCell<FlashAnimationStateMachine>>,
+    perfect_clear_flashing_state_machine: Rc<RefCell<FlashAnimationStateMachine>>,
     columns_cleared: usize,
+    /// Whether the current block was hard dropped, in which case it locks on the 
+    /// next update instead of waiting for the lock delay.
+    hard_dropped: bool,
+    /// Whether the block swapped in by a hold overlaps the landed blocks, in which
+    /// case the game ends with a block out on the next update.
+    blocked_out: bool,
+    /// The kind of spin the last block locked with.
+    t_spin: TSpin,
+    /// The hole columns of the garbage rows waiting to rise into the playing field.
+    pending_garbage: Vec<usize>,
 }
 
 #[derive(Copy, Clone)]
@@ -152,46 +223,12 @@ impl PlayingFieldFallingState {
         let mut playing_field_state = context.playing_field_state.borrow_mut();
         match input.kind {
             InputKind::Left => {
-                match input.action {
-                    InputAction::Press | InputAction::Repeat => {
-                        timers.left_hold_timer.update(elapsed_milliseconds);
-                        if timers.left_hold_timer.event_triggered() {
-                            let collides_with_floor = playing_field_state.collides_with_floor_below();
-                            let collides_with_element = playing_field_state.collides_with_element_below();
-                            let collides_with_left_element = playing_field_state.collides_with_element_to_the_left();
-                            let collides_with_left_wall = playing_field_state.collides_with_left_wall();
-                            if !collides_with_left_element || !collides_with_left_wall {
-                                if collides_with_floor || collides_with_element {
-                                    timers.fall_timer.reset();
-                                }
-                                playing_field_state.update_block_position(GooglyBlockMove::Left);
-                            }
-                            timers.left_hold_timer.reset();
-                        }
-                    }
-                    _ => {}
-                }
+                let shift = timers.auto_shift.handle_input(ShiftDirection::Left, input.action, elapsed_milliseconds);
+                shift_block(&mut playing_field_state, &mut timers.lock_delay, GooglyBlockMove::Left, shift);
             }
             InputKind::Right => {
-                match input.action {
-                    InputAction::Press | InputAction::Repeat => {
-                        timers.right_hold_timer.update(elapsed_milliseconds);
-                        if timers.right_hold_timer.event_triggered() {
-                            let collides_with_floor = playing_field_state.collides_with_floor_below();
-                            let collides_with_element = playing_field_state.collides_with_element_below();
-                            let collides_with_right_element = playing_field_state.collides_with_element_to_the_right();
-                            let collides_with_right_wall = playing_field_state.collides_with_right_wall();
-                            if !collides_with_right_element || !collides_with_right_wall {
-                                if collides_with_floor || collides_with_element {
-                                    timers.fall_timer.reset();
-                                }
-                                playing_field_state.update_block_position(GooglyBlockMove::Right);
-                            }
-                            timers.right_hold_timer.reset();
-                        }
-                    }
-                    _ => {}
-                }
+                let shift = timers.auto_shift.handle_input(ShiftDirection::Right, input.action, elapsed_milliseconds);
+                shift_block(&mut playing_field_state, &mut timers.lock_delay, GooglyBlockMove::Right, shift);
             }
             InputKind::Down => {
                 match input.action {
@@ -203,25 +240,74 @@ impl PlayingFieldFallingState {
                             if collides_with_floor || collides_with_element {
                                 timers.fall_timer.reset();
                             }
+                            let old_position = playing_field_state.current_position;
                             playing_field_state.update_block_position(GooglyBlockMove::Down);
+                            let rows = playing_field_state.current_position.row - old_position.row;
+                            context.score_board.borrow_mut().soft_drop(rows as usize);
                             timers.down_hold_timer.reset();
                         }                        
                     }
                     _ => {}
                 }
             }
-            InputKind::Rotate => {
+            InputKind::RotateClockwise => {
                 match input.action {
                     InputAction::Press | InputAction::Repeat => {
                         timers.rotate_timer.update(elapsed_milliseconds);
                         if timers.rotate_timer.event_triggered() {
-                            playing_field_state.update_block_position(GooglyBlockMove::Rotate);
+                            let old_block = playing_field_state.current_block;
+                            let old_position = playing_field_state.current_position;
+                            playing_field_state.update_block_position(GooglyBlockMove::RotateClockwise);
+                            if playing_field_state.current_block != old_block 
+                                || playing_field_state.current_position != old_position {
+                                timers.lock_delay.moved();
+                            }
+                            timers.auto_shift.cut();
                             timers.rotate_timer.reset();
                         }
                     }
                     _ => {}
                 }
             }
+            InputKind::RotateCounterClockwise => {
+                match input.action {
+                    InputAction::Press | InputAction::Repeat => {
+                        timers.rotate_timer.update(elapsed_milliseconds);
+                        if timers.rotate_timer.event_triggered() {
+                            let old_block = playing_field_state.current_block;
+                            let old_position = playing_field_state.current_position;
+                            playing_field_state.update_block_position(GooglyBlockMove::RotateCounterClockwise);
+                            if playing_field_state.current_block != old_block 
+                                || playing_field_state.current_position != old_position {
+                                timers.lock_delay.moved();
+                            }
+                            timers.auto_shift.cut();
+                            timers.rotate_timer.reset();
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            InputKind::HardDrop if input.action == InputAction::Press => {
+                let rows = playing_field_state.drop_distance() as usize;
+                playing_field_state.update_block_position(GooglyBlockMove::HardDrop);
+                context.score_board.borrow_mut().hard_drop(rows);
+                context.hard_dropped = true;
+            }
+            InputKind::SonicDrop if input.action == InputAction::Press => {
+                playing_field_state.update_block_position(GooglyBlockMove::SonicDrop);
+                timers.fall_timer.reset();
+            }
+            InputKind::Hold if input.action == InputAction::Press => {
+                let mut next_block = context.next_block.borrow_mut();
+                if playing_field_state.hold(&mut next_block) {
+                    timers.fall_timer.reset();
+                    timers.lock_delay.reset();
+                    // The block swapped in spawns at its starting position,
+                    // which can overlap a high stack.
+                    context.blocked_out = playing_field_state.is_blocked_out();
+                }
+            }
             _ => {}
         } 
     }
@@ -233,38 +319,65 @@ impl PlayingFieldFallingState {
         let mut next_block = context.next_block.borrow_mut();
         let mut full_rows = context.full_rows.borrow_mut();
         let mut flashing_state_machine = context.flashing_state_machine.borrow_mut();
+        let mut perfect_clear_flashing_state_machine = context.perfect_clear_flashing_state_machine.borrow_mut();
+
+        if context.blocked_out {
+            return PlayingFieldState::GameOver(PlayingFieldGameOverState::new(TopOut::BlockOut));
+        }
+
+        if !context.pending_garbage.is_empty() {
+            let garbage_out = playing_field_state.push_garbage(&context.pending_garbage);
+            context.pending_garbage.clear();
+            if garbage_out {
+                return PlayingFieldState::GameOver(PlayingFieldGameOverState::new(TopOut::GarbageOut));
+            }
+        }
 
         let collides_with_floor = playing_field_state.collides_with_floor_below();
         let collides_with_element = playing_field_state.collides_with_element_below();
 
         timers.fall_timer.update(elapsed_milliseconds);
-        if collides_with_floor || collides_with_element {
-            timers.collision_timer.update(elapsed_milliseconds);
-        } else {
-            timers.collision_timer.reset();
-        }
+        let row = playing_field_state.current_position.row;
+        timers.lock_delay.update(elapsed_milliseconds, row, collides_with_floor || collides_with_element);
 
         if timers.fall_timer.event_triggered() {
-            playing_field_state.update_block_position(GooglyBlockMove::Fall);
+            // At high levels the fall interval can be shorter than a frame, so the
+            // block falls a row for each interval that elapsed until it lands.
+            for _ in 0..timers.fall_timer.event_count() {
+                let old_position = playing_field_state.current_position;
+                playing_field_state.update_block_position(GooglyBlockMove::Fall);
+                if playing_field_state.current_position == old_position {
+                    break;
+                }
+            }
             timers.fall_timer.reset();
         }
 
-        if timers.collision_timer.event_triggered() {
+        let mut locked = false;
+        if timers.lock_delay.is_expired() || context.hard_dropped {
+            context.hard_dropped = false;
+            locked = true;
+            context.t_spin = playing_field_state.t_spin();
             let current_block = playing_field_state.current_block;
+            let locked_out = playing_field_state.is_locked_out();
             playing_field_state.update_landed();
-            if !playing_field_state.has_empty_row(0) {
-                return PlayingFieldState::GameOver(PlayingFieldGameOverState::new());
+            statistics.update(current_block);
+            if locked_out {
+                return PlayingFieldState::GameOver(PlayingFieldGameOverState::new(TopOut::LockOut));
             }
             
-            statistics.update(current_block);
             let old_next_block = next_block.current_block();
             next_block.update();
             let new_next_block = old_next_block;
             playing_field_state.update_new_block(new_next_block);
-            timers.collision_timer.reset();
+            timers.lock_delay.reset();
+            if playing_field_state.is_blocked_out() {
+                return PlayingFieldState::GameOver(PlayingFieldGameOverState::new(TopOut::BlockOut));
+            }
         }
         
         flashing_state_machine.update(elapsed_milliseconds);
+        perfect_clear_flashing_state_machine.update(elapsed_milliseconds);
 
         let full_row_count = playing_field_state.get_full_rows(&mut full_rows.rows);
         full_rows.count = full_row_count;
@@ -274,6 +387,9 @@ impl PlayingFieldFallingState {
             }
             return PlayingFieldState::Clearing(PlayingFieldClearingState::new());
         } else {
+            if locked {
+                context.score_board.borrow_mut().update(LineClear::new(0, context.t_spin));
+            }
             return PlayingFieldState::Falling(PlayingFieldFallingState::new());
         }
     }
@@ -288,7 +404,16 @@ impl PlayingFieldClearingState {
     }
 
     fn handle_input(&self, context: &mut PlayingFieldStateMachineContext, input: Input, elapsed_milliseconds: Duration) {
+        // The block cannot move while the rows clear, but the player can still
+        // charge the auto shift for the next block.
+        let mut timers = context.timers.borrow_mut();
         match input.kind {
+            InputKind::Left => {
+                timers.auto_shift.handle_input(ShiftDirection::Left, input.action, elapsed_milliseconds);
+            }
+            InputKind::Right => {
+                timers.auto_shift.handle_input(ShiftDirection::Right, input.action, elapsed_milliseconds);
+            }
             _ => {}
         }
     }
@@ -299,26 +424,38 @@ impl PlayingFieldClearingState {
         let mut full_rows = context.full_rows.borrow_mut();
         let mut score_board = context.score_board.borrow_mut();
         let mut flashing_state_machine = context.flashing_state_machine.borrow_mut();
+        let mut perfect_clear_flashing_state_machine = context.perfect_clear_flashing_state_machine.borrow_mut();
         
         timers.clearing_timer.update(elapsed_milliseconds);
         if timers.clearing_timer.event_triggered() {
             timers.clearing_timer.reset();
-            let center_left = (4 - context.columns_cleared / 2) as isize;
-            let center_right = (5 + context.columns_cleared / 2) as isize;
-            for row in full_rows.rows.iter() {
-                if *row >= 0 {
-                    playing_field_state.landed_blocks.clear(*row, center_left);
-                    playing_field_state.landed_blocks.clear(*row, center_right);
-                }
+            // Clear the rows from the centre of the playing field outwards.
+            let columns = playing_field_state.landed_blocks.columns() as isize;
+            let step = (context.columns_cleared / 2) as isize;
+            let center_left = (columns - 1) / 2 - step;
+            let center_right = columns / 2 + step;
+            for row in full_rows.rows[0..full_rows.count].iter() {
+                playing_field_state.landed_blocks.clear(*row, center_left);
+                playing_field_state.landed_blocks.clear(*row, center_right);
             }
             context.columns_cleared += 2;
         }
 
         flashing_state_machine.update(elapsed_milliseconds);
+        perfect_clear_flashing_state_machine.update(elapsed_milliseconds);
 
-        if context.columns_cleared >= 10 {
+        if context.columns_cleared >= playing_field_state.landed_blocks.columns() {
             playing_field_state.collapse_empty_rows();
-            score_board.update(full_rows.count);
+            let mut clear = LineClear::new(full_rows.count, context.t_spin);
+            clear.perfect_clear = playing_field_state.landed_blocks.is_empty();
+            score_board.update(clear);
+            let mut statistics = context.statistics.borrow_mut();
+            statistics.update_combo(score_board.combo);
+            if clear.perfect_clear {
+                statistics.update_perfect_clear();
+                perfect_clear_flashing_state_machine.enable();
+            }
+            timers.update_level(score_board.level);
             full_rows.clear();
             context.columns_cleared = 0;
 
@@ -330,11 +467,16 @@ impl PlayingFieldClearingState {
 }
 
 #[derive(Copy, Clone)]
-struct PlayingFieldGameOverState {}
+struct PlayingFieldGameOverState {
+    /// How the blocks reached the top of the playing field.
+    top_out: TopOut,
+}
 
 impl PlayingFieldGameOverState {
-    fn new() -> PlayingFieldGameOverState {
-        PlayingFieldGameOverState {}
+    fn new(top_out: TopOut) -> PlayingFieldGameOverState {
+        PlayingFieldGameOverState {
+            top_out: top_out,
+        }
     }
 
     fn handle_input(&self, context: &mut PlayingFieldStateMachineContext, input: Input, elapsed_milliseconds: Duration) {
@@ -346,6 +488,7 @@ impl PlayingFieldGameOverState {
     fn update(&self, context: &mut PlayingFieldStateMachineContext, elapsed_milliseconds: Duration) -> PlayingFieldState {
         let mut flashing_state_machine = context.flashing_state_machine.borrow_mut();
         flashing_state_machine.disable();
+        context.perfect_clear_flashing_state_machine.borrow_mut().disable();
 
         PlayingFieldState::GameOver(*self)
     }
@@ -377,6 +520,21 @@ impl PlayingFieldStateMachine {
         }
     }
 
+    /// How the game ended, if it is over.
+    pub fn top_out(&self) -> Option<TopOut> {
+        match self.state {
+            PlayingFieldState::GameOver(s) => Some(s.top_out),
+            _ => None,
+        }
+    }
+
+    /// Queue garbage rows with the given hole columns to rise into the playing
+    /// field. The garbage rises on the next update while a block is falling, so
+    /// it never lands in the middle of a line clear.
+    pub fn add_garbage(&self, holes: &[usize]) {
+        self.context.borrow_mut().pending_garbage.extend_from_slice(holes);
+    }
+
     pub fn handle_input(&self, input: Input, elapsed_milliseconds: Duration) {
         let mut context = self.context.borrow_mut();
         match self.state {
@@ -396,3 +554,500 @@ impl PlayingFieldStateMachine {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use crate::auto_shift::{
+        AutoShiftSpec,
+    };
+    use crate::block::{
+        GooglyBlock,
+        GooglyBlockElement,
+        GooglyBlockPiece,
+        GooglyBlockRotation,
+    };
+    use crate::flashing_state_machine;
+    use crate::flashing_state_machine::{
+        FlashAnimationStateMachine,
+        FlashAnimationStateMachineSpec,
+    };
+    use crate::gravity::{
+        GravityCurve,
+    };
+    use crate::lock_delay::{
+        LockDelayKind,
+    };
+    use crate::input::{
+        Input,
+        InputAction,
+        InputKind,
+    };
+    use crate::next_block::{
+        NextBlockCell,
+    };
+    use crate::playing_field::{
+        BlockPosition,
+        PlayingFieldContext,
+        PlayingFieldContextSpec,
+        TopOut,
+    };
+    use crate::piece_set::{
+        PieceSet,
+    };
+    use crate::randomizer::{
+        RandomizerKind,
+    };
+    use crate::rotation::{
+        RotationSystemKind,
+    };
+    use crate::score::{
+        ScoreBoard,
+        ScoringSystemKind,
+        Statistics,
+    };
+    use crate::timer::{
+        Interval,
+    };
+    use super::{
+        PlayingFieldStateMachine,
+        PlayingFieldStateMachineSpec,
+        PlayingFieldTimerSpec,
+    };
+
+    use std::cell::RefCell;
+    use std::collections::hash_map::HashMap;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+
+    const FRAME: Duration = Duration::from_millis(16);
+
+    struct StateMachineTestCase {
+        state_machine: PlayingFieldStateMachine,
+        playing_field: Rc<RefCell<PlayingFieldContext>>,
+        statistics: Rc<RefCell<Statistics>>,
+        score_board: Rc<RefCell<ScoreBoard>>,
+        perfect_clear_flashing_state_machine: Rc<RefCell<FlashAnimationStateMachine>>,
+    }
+
+    impl StateMachineTestCase {
+        fn step(&mut self, inputs: &[Input]) {
+            for input in inputs.iter() {
+                self.state_machine.handle_input(*input, FRAME);
+            }
+            self.state_machine.update(FRAME);
+        }
+
+        fn step_frames(&mut self, frames: usize) {
+            for _ in 0..frames {
+                self.step(&[]);
+            }
+        }
+    }
+
+    fn test_case(
+        block: GooglyBlock, 
+        position: BlockPosition, 
+        landed: &[(isize, isize)], lock_delay: LockDelayKind) -> StateMachineTestCase {
+        test_case_with_auto_shift(block, position, landed, lock_delay, auto_shift())
+    }
+
+    fn test_case_with_auto_shift(
+        block: GooglyBlock, 
+        position: BlockPosition, 
+        landed: &[(isize, isize)], lock_delay: LockDelayKind, auto_shift: AutoShiftSpec) -> StateMachineTestCase {
+        let gravity = GravityCurve::constant(Interval::Milliseconds(1000)).unwrap();
+
+        test_case_with_timers(block, position, landed, lock_delay, auto_shift, gravity)
+    }
+
+    fn test_case_with_gravity(
+        block: GooglyBlock, 
+        position: BlockPosition, 
+        landed: &[(isize, isize)], gravity: GravityCurve) -> StateMachineTestCase {
+        test_case_with_timers(block, position, landed, LockDelayKind::Classic, auto_shift(), gravity)
+    }
+
+    fn auto_shift() -> AutoShiftSpec {
+        AutoShiftSpec {
+            delayed_auto_shift: Interval::Milliseconds(100),
+            auto_repeat_rate: Interval::Milliseconds(20),
+            das_cut_delay: Interval::Milliseconds(50),
+        }
+    }
+
+    fn test_case_with_timers(
+        block: GooglyBlock, 
+        position: BlockPosition, 
+        landed: &[(isize, isize)], 
+        lock_delay: LockDelayKind, auto_shift: AutoShiftSpec, gravity: GravityCurve) -> StateMachineTestCase {
+        let starting_positions: HashMap<GooglyBlockPiece, BlockPosition> = [
+            (GooglyBlockPiece::T, BlockPosition::new(-3, 4)),
+            (GooglyBlockPiece::J, BlockPosition::new(-3, 4)),
+            (GooglyBlockPiece::Z, BlockPosition::new(-3, 4)),
+            (GooglyBlockPiece::O, BlockPosition::new(-3, 4)),
+            (GooglyBlockPiece::S, BlockPosition::new(-3, 4)),
+            (GooglyBlockPiece::L, BlockPosition::new(-3, 4)),
+            (GooglyBlockPiece::I, BlockPosition::new(-3, 3)),
+        ].iter().copied().collect();
+        let spec = PlayingFieldContextSpec {
+            starting_block: block,
+            starting_positions: starting_positions,
+            piece_set: Rc::new(PieceSet::standard()),
+            rotation_system: RotationSystemKind::Srs,
+            rows: 20,
+            columns: 10,
+        };
+        let mut playing_field = PlayingFieldContext::new(spec);
+        playing_field.current_position = position;
+        for (row, column) in landed.iter() {
+            playing_field.landed_blocks.insert(*row, *column, GooglyBlockElement::J);
+        }
+        let playing_field = Rc::new(RefCell::new(playing_field));
+        let timers = PlayingFieldTimerSpec {
+            gravity: gravity,
+            lock_delay_interval: Interval::Milliseconds(500),
+            lock_delay: lock_delay,
+            auto_shift: auto_shift,
+            down_hold_interval: Interval::Milliseconds(35),
+            rotate_interval: Interval::Milliseconds(100),
+            clearing_interval: Interval::Milliseconds(60),
+        };
+        let flash_timers = FlashAnimationStateMachineSpec {
+            flash_switch_interval: Interval::Milliseconds(50),
+            flash_stop_interval: Interval::Milliseconds(500),
+        };
+        let perfect_clear_flash_timers = FlashAnimationStateMachineSpec {
+            flash_switch_interval: Interval::Milliseconds(100),
+            flash_stop_interval: Interval::Milliseconds(1500),
+        };
+        let flashing_state_machine = Rc::new(RefCell::new(flashing_state_machine::create(flash_timers)));
+        let perfect_clear_flashing_state_machine = Rc::new(RefCell::new(
+            flashing_state_machine::create(perfect_clear_flash_timers)
+        ));
+        let next_block = Rc::new(RefCell::new(NextBlockCell::from_seed(1, RandomizerKind::SevenBag, &PieceSet::standard().pieces(), 1)));
+        let statistics = Rc::new(RefCell::new(Statistics::new()));
+        let score_board = Rc::new(RefCell::new(ScoreBoard::new(20, 0, ScoringSystemKind::Guideline.create())));
+        let spec = PlayingFieldStateMachineSpec {
+            timers: timers,
+            flashing_state_machine: flashing_state_machine,
+            perfect_clear_flashing_state_machine: perfect_clear_flashing_state_machine.clone(),
+            playing_field_context: playing_field.clone(),
+            next_block: next_block,
+            statistics: statistics.clone(),
+            score_board: score_board.clone(),
+        };
+
+        StateMachineTestCase {
+            state_machine: super::create(spec),
+            playing_field: playing_field,
+            statistics: statistics,
+            score_board: score_board,
+            perfect_clear_flashing_state_machine: perfect_clear_flashing_state_machine,
+        }
+    }
+
+    fn press(kind: InputKind) -> Input {
+        Input::new(kind, InputAction::Press)
+    }
+
+    fn hold(kind: InputKind) -> Input {
+        Input::new(kind, InputAction::Repeat)
+    }
+
+    fn release(kind: InputKind) -> Input {
+        Input::new(kind, InputAction::Release)
+    }
+
+    /// Determine whether the O block the test started with has locked.
+    fn is_locked(test: &StateMachineTestCase) -> bool {
+        test.statistics.borrow().o_pieces > 0
+    }
+
+    /// Shuffle the current block back and forth along the floor, tapping right
+    /// and left in turn once every five frames, for the given number of frames.
+    fn shuffle_frames(test: &mut StateMachineTestCase, frames: usize) {
+        for frame in 0..frames {
+            let kind = if (frame / 5) % 2 == 0 { InputKind::Right } else { InputKind::Left };
+            if frame % 5 == 0 {
+                test.step(&[press(kind)]);
+            } else {
+                test.step(&[hold(kind)]);
+            }
+        }
+    }
+
+    /// Rest an O block on a two column ledge, tap it off the ledge, and soft
+    /// drop it to the floor. The block stays grounded on the ledge for 448 
+    /// milliseconds before it slides off.
+    fn slide_off_ledge(lock_delay: LockDelayKind) -> StateMachineTestCase {
+        let block = GooglyBlock::new(GooglyBlockPiece::O, GooglyBlockRotation::R0);
+        let mut test = test_case(block, BlockPosition::new(17, 0), &[(19, 0), (19, 1)], lock_delay);
+        test.step_frames(26);
+        test.step(&[press(InputKind::Right)]);
+        test.step(&[release(InputKind::Right)]);
+        test.step(&[press(InputKind::Right)]);
+        for _ in 0..3 {
+            test.step(&[press(InputKind::Down)]);
+        }
+        assert_eq!(test.playing_field.borrow().current_position, BlockPosition::new(18, 2));
+
+        test
+    }
+
+
+    /// Clearing the last blocks in the playing field should count as a perfect clear, 
+    /// earn the perfect clear bonus, and start the perfect clear animation.
+    #[test]
+    fn clearing_every_block_should_be_a_perfect_clear() {
+        let landed: Vec<(isize, isize)> = (0..6).map(|column| (19, column)).collect();
+        let block = GooglyBlock::new(GooglyBlockPiece::I, GooglyBlockRotation::R0);
+        let mut test = test_case(block, BlockPosition::new(0, 6), &landed, LockDelayKind::Classic);
+        test.step(&[press(InputKind::HardDrop)]);
+        test.step_frames(20);
+
+        assert!(test.playing_field.borrow().landed_blocks.is_empty());
+        assert_eq!(test.statistics.borrow().perfect_clears, 1);
+        assert!(test.score_board.borrow().last_clear.unwrap().perfect_clear);
+        assert_eq!(test.score_board.borrow().score, 2 * 17 + 100 + 800);
+        assert!(!test.perfect_clear_flashing_state_machine.borrow().is_disabled());
+    }
+
+    /// A line clear that leaves blocks behind should not be a perfect clear.
+    #[test]
+    fn clearing_with_blocks_left_over_should_not_be_a_perfect_clear() {
+        let mut landed: Vec<(isize, isize)> = (0..6).map(|column| (19, column)).collect();
+        landed.push((18, 0));
+        let block = GooglyBlock::new(GooglyBlockPiece::I, GooglyBlockRotation::R0);
+        let mut test = test_case(block, BlockPosition::new(0, 6), &landed, LockDelayKind::Classic);
+        test.step(&[press(InputKind::HardDrop)]);
+        test.step_frames(20);
+
+        assert_eq!(test.score_board.borrow().lines, 1);
+        assert_eq!(test.statistics.borrow().perfect_clears, 0);
+        assert!(test.perfect_clear_flashing_state_machine.borrow().is_disabled());
+    }
+
+    /// A fall interval shorter than a frame should drop the block a row for every 
+    /// interval that elapsed during the frame, and stop it on the floor.
+    #[test]
+    fn gravity_faster_than_a_frame_should_drop_several_rows_per_frame() {
+        let block = GooglyBlock::new(GooglyBlockPiece::O, GooglyBlockRotation::R0);
+        let gravity = GravityCurve::constant(Interval::Milliseconds(5)).unwrap();
+        let mut test = test_case_with_gravity(block, BlockPosition::new(0, 4), &[], gravity);
+        test.step_frames(1);
+        assert_eq!(test.playing_field.borrow().current_position, BlockPosition::new(3, 4));
+
+        test.step_frames(6);
+        assert_eq!(test.playing_field.borrow().current_position, BlockPosition::new(18, 4));
+        assert!(!is_locked(&test));
+    }
+
+    /// Under the classic lock delay, moving a block along the floor should not 
+    /// keep it from locking.
+    #[test]
+    fn the_classic_lock_delay_should_lock_a_moving_block() {
+        let block = GooglyBlock::new(GooglyBlockPiece::O, GooglyBlockRotation::R0);
+        let mut test = test_case(block, BlockPosition::new(18, 4), &[], LockDelayKind::Classic);
+        shuffle_frames(&mut test, 31);
+        assert!(!is_locked(&test));
+
+        shuffle_frames(&mut test, 1);
+        assert!(is_locked(&test));
+    }
+
+    /// Under the step reset lock delay, moving a block along the floor should not 
+    /// keep it from locking.
+    #[test]
+    fn the_step_reset_lock_delay_should_lock_a_moving_block() {
+        let block = GooglyBlock::new(GooglyBlockPiece::O, GooglyBlockRotation::R0);
+        let mut test = test_case(block, BlockPosition::new(18, 4), &[], LockDelayKind::StepReset);
+        shuffle_frames(&mut test, 32);
+
+        assert!(is_locked(&test));
+    }
+
+    /// Under the step reset lock delay, a block that falls to a lower row should 
+    /// get a fresh lock delay.
+    #[test]
+    fn the_step_reset_lock_delay_should_reset_when_the_block_falls() {
+        let mut test = slide_off_ledge(LockDelayKind::StepReset);
+        test.step_frames(25);
+        assert!(!is_locked(&test));
+
+        test.step_frames(10);
+        assert!(is_locked(&test));
+    }
+
+    /// Under the classic lock delay, a block that falls to a lower row should 
+    /// keep the lock delay it already used up.
+    #[test]
+    fn the_classic_lock_delay_should_not_reset_when_the_block_falls() {
+        let mut test = slide_off_ledge(LockDelayKind::Classic);
+        test.step_frames(5);
+
+        assert!(is_locked(&test));
+    }
+
+    /// Under the move reset lock delay, moving a block should keep it from locking
+    /// until it runs out of resets.
+    #[test]
+    fn the_move_reset_lock_delay_should_lock_after_fifteen_resets() {
+        let block = GooglyBlock::new(GooglyBlockPiece::O, GooglyBlockRotation::R0);
+        let mut test = test_case(block, BlockPosition::new(18, 4), &[], LockDelayKind::MoveReset);
+        // The first move comes before the lock delay sees the block on the floor,
+        // so the fifteenth reset happens on the seventy-sixth frame.
+        shuffle_frames(&mut test, 75 + 20);
+        assert!(!is_locked(&test));
+
+        shuffle_frames(&mut test, 20);
+        assert!(is_locked(&test));
+    }
+
+    /// Pressing a direction should shift the block right away, and holding it
+    /// should shift the block again once the delayed auto shift runs out.
+    #[test]
+    fn a_block_should_shift_when_pressed_and_again_after_the_delay() {
+        let block = GooglyBlock::new(GooglyBlockPiece::O, GooglyBlockRotation::R0);
+        let mut test = test_case(block, BlockPosition::new(0, 4), &[], LockDelayKind::Classic);
+        test.step(&[press(InputKind::Left)]);
+        assert_eq!(test.playing_field.borrow().current_position, BlockPosition::new(0, 3));
+
+        for _ in 0..6 {
+            test.step(&[hold(InputKind::Left)]);
+        }
+        assert_eq!(test.playing_field.borrow().current_position, BlockPosition::new(0, 3));
+
+        test.step(&[hold(InputKind::Left)]);
+        assert_eq!(test.playing_field.borrow().current_position, BlockPosition::new(0, 2));
+        test.step(&[hold(InputKind::Left)]);
+        assert_eq!(test.playing_field.borrow().current_position, BlockPosition::new(0, 1));
+    }
+
+    /// With an auto repeat rate of zero, a block should shift straight to the
+    /// wall once the delayed auto shift runs out.
+    #[test]
+    fn an_auto_repeat_rate_of_zero_should_shift_a_block_to_the_wall() {
+        let auto_shift = AutoShiftSpec {
+            delayed_auto_shift: Interval::Milliseconds(100),
+            auto_repeat_rate: Interval::Milliseconds(0),
+            das_cut_delay: Interval::Milliseconds(0),
+        };
+        let block = GooglyBlock::new(GooglyBlockPiece::O, GooglyBlockRotation::R0);
+        let mut test = test_case_with_auto_shift(block, BlockPosition::new(0, 4), &[], LockDelayKind::Classic, auto_shift);
+        test.step(&[press(InputKind::Right)]);
+        for _ in 0..7 {
+            test.step(&[hold(InputKind::Right)]);
+        }
+
+        assert_eq!(test.playing_field.borrow().current_position, BlockPosition::new(0, 8));
+    }
+
+    /// Rotating a block should pause its auto shift for the DAS cut delay.
+    #[test]
+    fn rotating_a_block_should_pause_its_auto_shift() {
+        let block = GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0);
+        let mut test = test_case(block, BlockPosition::new(0, 2), &[], LockDelayKind::Classic);
+        test.step(&[press(InputKind::Right)]);
+        // The block shifts and rotates on the seventh frame the keys are held.
+        for _ in 0..7 {
+            test.step(&[hold(InputKind::Right), press(InputKind::RotateClockwise)]);
+        }
+        assert_eq!(test.playing_field.borrow().current_block.rotation, GooglyBlockRotation::R1);
+        assert_eq!(test.playing_field.borrow().current_position.column, 4);
+
+        for _ in 0..3 {
+            test.step(&[hold(InputKind::Right)]);
+        }
+        assert_eq!(test.playing_field.borrow().current_position.column, 4);
+
+        test.step(&[hold(InputKind::Right)]);
+        assert_eq!(test.playing_field.borrow().current_position.column, 5);
+    }
+
+    /// A block that locks entirely inside the hidden buffer zone should end the 
+    /// game with a lock out.
+    #[test]
+    fn locking_a_block_above_the_visible_field_should_be_a_lock_out() {
+        let landed: Vec<(isize, isize)> = (0..20).flat_map(|row| vec![(row, 4), (row, 5)]).collect();
+        let block = GooglyBlock::new(GooglyBlockPiece::O, GooglyBlockRotation::R0);
+        let mut test = test_case(block, BlockPosition::new(-4, 4), &landed, LockDelayKind::Classic);
+        test.step(&[press(InputKind::HardDrop)]);
+
+        assert!(test.state_machine.is_game_over());
+        assert_eq!(test.state_machine.top_out(), Some(TopOut::LockOut));
+        assert_eq!(test.playing_field.borrow().landed_blocks.get(-1, 4).unwrap(), GooglyBlockElement::O);
+    }
+
+    /// A block that locks partly inside the visible field should not end the game.
+    #[test]
+    fn locking_a_block_partly_above_the_visible_field_should_not_end_the_game() {
+        let landed: Vec<(isize, isize)> = (1..20).flat_map(|row| vec![(row, 0), (row, 1)]).collect();
+        let block = GooglyBlock::new(GooglyBlockPiece::O, GooglyBlockRotation::R0);
+        let mut test = test_case(block, BlockPosition::new(-4, 0), &landed, LockDelayKind::Classic);
+        test.step(&[press(InputKind::HardDrop)]);
+
+        assert!(!test.state_machine.is_game_over());
+        assert_eq!(test.playing_field.borrow().landed_blocks.get(-1, 0).unwrap(), GooglyBlockElement::O);
+    }
+
+    /// A new block that spawns overlapping the landed blocks should end the game
+    /// with a block out.
+    #[test]
+    fn spawning_a_block_on_top_of_the_stack_should_be_a_block_out() {
+        let landed: Vec<(isize, isize)> = (-3..0).flat_map(|row| (3..7).map(move |column| (row, column))).collect();
+        let block = GooglyBlock::new(GooglyBlockPiece::O, GooglyBlockRotation::R0);
+        let mut test = test_case(block, BlockPosition::new(0, 0), &landed, LockDelayKind::Classic);
+        test.step(&[press(InputKind::HardDrop)]);
+
+        assert!(test.state_machine.is_game_over());
+        assert_eq!(test.state_machine.top_out(), Some(TopOut::BlockOut));
+    }
+
+    /// Holding a block on a stack that reaches the spawn position should end the 
+    /// game with a block out instead of letting the swapped in block overlap the stack.
+    #[test]
+    fn holding_a_block_on_top_of_the_stack_should_be_a_block_out() {
+        let landed: Vec<(isize, isize)> = (-3..0).flat_map(|row| (3..7).map(move |column| (row, column))).collect();
+        let block = GooglyBlock::new(GooglyBlockPiece::O, GooglyBlockRotation::R0);
+        let mut test = test_case(block, BlockPosition::new(0, 0), &landed, LockDelayKind::Classic);
+        test.step_frames(5);
+        assert!(!test.state_machine.is_game_over());
+
+        test.step(&[press(InputKind::Hold)]);
+
+        assert!(test.state_machine.is_game_over());
+        assert_eq!(test.state_machine.top_out(), Some(TopOut::BlockOut));
+        assert_eq!(test.playing_field.borrow().held_block.unwrap().piece, GooglyBlockPiece::O);
+        assert!(test.playing_field.borrow().is_blocked_out());
+        assert!(!is_locked(&test));
+    }
+
+    /// Queued garbage should rise into the playing field on the next update.
+    #[test]
+    fn queued_garbage_should_rise_on_the_next_update() {
+        let block = GooglyBlock::new(GooglyBlockPiece::O, GooglyBlockRotation::R0);
+        let mut test = test_case(block, BlockPosition::new(0, 4), &[(19, 0)], LockDelayKind::Classic);
+        test.state_machine.add_garbage(&[5, 6]);
+        test.step(&[]);
+
+        let playing_field = test.playing_field.borrow();
+        assert_eq!(playing_field.landed_blocks.get(17, 0).unwrap(), GooglyBlockElement::J);
+        assert_eq!(playing_field.landed_blocks.get(18, 5).unwrap(), GooglyBlockElement::EmptySpace);
+        assert_eq!(playing_field.landed_blocks.get(19, 5).unwrap(), GooglyBlockElement::Garbage);
+        assert_eq!(playing_field.landed_blocks.garbage_rows(), 2);
+    }
+
+    /// Garbage that pushes the stack past the top of the buffer zone should end
+    /// the game with a garbage out.
+    #[test]
+    fn garbage_pushing_the_stack_out_should_be_a_garbage_out() {
+        let block = GooglyBlock::new(GooglyBlockPiece::O, GooglyBlockRotation::R0);
+        let mut test = test_case(block, BlockPosition::new(0, 4), &[(-20, 0)], LockDelayKind::Classic);
+        test.state_machine.add_garbage(&[5]);
+        test.step(&[]);
+
+        assert!(test.state_machine.is_game_over());
+        assert_eq!(test.state_machine.top_out(), Some(TopOut::GarbageOut));
+    }
+}
@@ -0,0 +1,452 @@
+/*
+ *  Googly Blocks is a video game.
+ *  Copyright (C) 2018,2019,2020  Christopher Blanchard
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use rand::{
+    Rng,
+    RngCore,
+};
+use rand::seq::{
+    SliceRandom,
+};
+use crate::block::{
+    GooglyBlockPiece,
+};
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::str;
+
+
+/// A randomizer decides which piece the player gets next. The randomizer does not
+/// own its source of randomness so that every randomizer can be driven from the
+/// same seeded generator.
+pub trait Randomizer {
+    /// Choose the next piece.
+    fn next(&mut self, rng: &mut dyn RngCore) -> GooglyBlockPiece;
+}
+
+/// The randomizers that can be selected in the game settings.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RandomizerKind {
+    /// Uniform sampling with a bounded reroll of repeated pieces.
+    Classic,
+    /// The NES randomizer that rerolls a repeated piece at most once.
+    Nes,
+    /// The TGM randomizer that rerolls against a history of the last four pieces.
+    Tgm,
+    /// Deal the seven pieces from a shuffled bag.
+    SevenBag,
+}
+
+impl RandomizerKind {
+    /// Construct a randomizer of this kind that deals the given pieces. The
+    /// randomizer indexes the pieces in the order they are given.
+    pub fn create(self, pieces: &[GooglyBlockPiece]) -> Box<dyn Randomizer> {
+        match self {
+            RandomizerKind::Classic => Box::new(ClassicRandomizer::new(pieces)),
+            RandomizerKind::Nes => Box::new(NesRandomizer::new(pieces)),
+            RandomizerKind::Tgm => Box::new(TgmRandomizer::new(pieces, 6)),
+            RandomizerKind::SevenBag => Box::new(SevenBagRandomizer::new(pieces)),
+        }
+    }
+}
+
+impl fmt::Display for RandomizerKind {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let disp = match *self {
+            RandomizerKind::Classic => "classic",
+            RandomizerKind::Nes => "nes",
+            RandomizerKind::Tgm => "tgm",
+            RandomizerKind::SevenBag => "bag",
+        };
+        write!(formatter, "{}", disp)
+    }
+}
+
+impl str::FromStr for RandomizerKind {
+    type Err = String;
+
+    fn from_str(st: &str) -> Result<RandomizerKind, String> {
+        match st {
+            "classic" => Ok(RandomizerKind::Classic),
+            "nes" => Ok(RandomizerKind::Nes),
+            "tgm" => Ok(RandomizerKind::Tgm),
+            "bag" | "7bag" | "7-bag" => Ok(RandomizerKind::SevenBag),
+            _ => Err(format!("Unknown randomizer: {}", st)),
+        }
+    }
+}
+
+/// The original Googly Blocks randomizer. It samples pieces uniformly, and
+/// rerolls a piece that repeats the last one up to eight times.
+pub struct ClassicRandomizer {
+    /// The pieces to choose from.
+    pieces: Vec<GooglyBlockPiece>,
+    /// The last piece generated.
+    last_piece: Option<GooglyBlockPiece>,
+}
+
+impl ClassicRandomizer {
+    pub fn new(pieces: &[GooglyBlockPiece]) -> ClassicRandomizer {
+        ClassicRandomizer {
+            pieces: pieces.to_vec(),
+            last_piece: None,
+        }
+    }
+}
+
+impl Randomizer for ClassicRandomizer {
+    fn next(&mut self, rng: &mut dyn RngCore) -> GooglyBlockPiece {
+        let count = self.pieces.len();
+        let last_piece = match self.last_piece {
+            Some(piece) => piece,
+            None => self.pieces[rng.gen_range(0..count)],
+        };
+        let mut piece = self.pieces[rng.gen_range(0..count)];
+        let mut gas = 0;
+        // We perform a bounded iteration over the random number generator
+        // to reduce the probability of generating long runs of the same pieces.
+        // The gas parameter exists to guarantee that the loop terminates.
+        while (gas < 8) && (piece == last_piece) {
+            piece = self.pieces[rng.gen_range(0..count)];
+            gas += 1;
+        }
+        self.last_piece = Some(piece);
+
+        piece
+    }
+}
+
+/// The NES randomizer. It rolls a die with one more face than there are pieces,
+/// where the extra face means roll again. If the roll says to roll again or it 
+/// repeats the last piece, it rolls a die with one face per piece once and takes 
+/// whatever comes up.
+pub struct NesRandomizer {
+    /// The pieces to choose from.
+    pieces: Vec<GooglyBlockPiece>,
+    /// The last piece generated.
+    last_piece: Option<GooglyBlockPiece>,
+}
+
+impl NesRandomizer {
+    pub fn new(pieces: &[GooglyBlockPiece]) -> NesRandomizer {
+        NesRandomizer {
+            pieces: pieces.to_vec(),
+            last_piece: None,
+        }
+    }
+}
+
+impl Randomizer for NesRandomizer {
+    fn next(&mut self, rng: &mut dyn RngCore) -> GooglyBlockPiece {
+        let count = self.pieces.len();
+        let roll = rng.gen_range(0..(count + 1));
+        let piece = if roll == count || Some(self.pieces[roll]) == self.last_piece {
+            self.pieces[rng.gen_range(0..count)]
+        } else {
+            self.pieces[roll]
+        };
+        self.last_piece = Some(piece);
+
+        piece
+    }
+}
+
+/// The pieces the TGM randomizer never starts with, since they cannot be placed
+/// on an empty playing field without leaving a hole.
+const TGM_UNFAIR_FIRST_PIECES: [GooglyBlockPiece; 3] = [
+    GooglyBlockPiece::S, GooglyBlockPiece::Z, GooglyBlockPiece::O,
+];
+
+/// The TGM randomizer. It keeps a history of the last four pieces and rerolls
+/// a piece found in the history up to a fixed number of times. The history starts
+/// full of Z pieces, and the first piece is never an S, Z, or O piece unless the
+/// randomizer has nothing else to deal.
+pub struct TgmRandomizer {
+    /// The pieces to choose from.
+    pieces: Vec<GooglyBlockPiece>,
+    /// The last four pieces generated.
+    history: VecDeque<GooglyBlockPiece>,
+    /// The number of rolls made before giving up on avoiding the history.
+    tries: usize,
+    /// Whether the first piece has been generated yet.
+    started: bool,
+}
+
+impl TgmRandomizer {
+    pub fn new(pieces: &[GooglyBlockPiece], tries: usize) -> TgmRandomizer {
+        let history = [GooglyBlockPiece::Z; 4].iter().copied().collect();
+
+        TgmRandomizer {
+            pieces: pieces.to_vec(),
+            history: history,
+            tries: tries,
+            started: false,
+        }
+    }
+}
+
+impl Randomizer for TgmRandomizer {
+    fn next(&mut self, rng: &mut dyn RngCore) -> GooglyBlockPiece {
+        let piece = if !self.started {
+            self.started = true;
+            let mut first_pieces: Vec<GooglyBlockPiece> = self.pieces.iter()
+                .filter(|piece| !TGM_UNFAIR_FIRST_PIECES.contains(piece))
+                .copied()
+                .collect();
+            if first_pieces.is_empty() {
+                first_pieces = self.pieces.clone();
+            }
+            first_pieces[rng.gen_range(0..first_pieces.len())]
+        } else {
+            let count = self.pieces.len();
+            let mut piece = self.pieces[rng.gen_range(0..count)];
+            for _ in 1..self.tries {
+                if !self.history.contains(&piece) {
+                    break;
+                }
+                piece = self.pieces[rng.gen_range(0..count)];
+            }
+            piece
+        };
+        self.history.pop_front();
+        self.history.push_back(piece);
+
+        piece
+    }
+}
+
+/// The seven bag randomizer. It deals all of its pieces in a random order before
+/// shuffling a fresh bag. With the standard pieces, a bag holds seven pieces.
+pub struct SevenBagRandomizer {
+    /// The pieces in a full bag.
+    pieces: Vec<GooglyBlockPiece>,
+    /// The pieces left in the current bag.
+    bag: Vec<GooglyBlockPiece>,
+}
+
+impl SevenBagRandomizer {
+    pub fn new(pieces: &[GooglyBlockPiece]) -> SevenBagRandomizer {
+        SevenBagRandomizer {
+            pieces: pieces.to_vec(),
+            bag: Vec::with_capacity(pieces.len()),
+        }
+    }
+}
+
+impl Randomizer for SevenBagRandomizer {
+    fn next(&mut self, rng: &mut dyn RngCore) -> GooglyBlockPiece {
+        if self.bag.is_empty() {
+            self.bag.extend_from_slice(&self.pieces);
+            self.bag.shuffle(rng);
+        }
+
+        self.bag.pop().unwrap()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        RandomizerKind,
+    };
+    use crate::block::{
+        GooglyBlockPiece,
+    };
+    use rand::{
+        SeedableRng,
+    };
+    use rand::rngs::{
+        StdRng,
+    };
+
+    use std::collections::HashMap;
+
+
+    const SEQUENCE_LENGTH: usize = 70_000;
+
+    const PIECES: [GooglyBlockPiece; 7] = [
+        GooglyBlockPiece::T, GooglyBlockPiece::J, GooglyBlockPiece::Z, GooglyBlockPiece::O,
+        GooglyBlockPiece::S, GooglyBlockPiece::L, GooglyBlockPiece::I,
+    ];
+
+    fn sequence(kind: RandomizerKind, seed: u64) -> Vec<GooglyBlockPiece> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut randomizer = kind.create(&PIECES);
+
+        (0..SEQUENCE_LENGTH).map(|_| randomizer.next(&mut rng)).collect()
+    }
+
+    fn frequencies(pieces: &[GooglyBlockPiece]) -> HashMap<GooglyBlockPiece, usize> {
+        let mut counts = HashMap::new();
+        for piece in pieces.iter() {
+            *counts.entry(*piece).or_insert(0) += 1;
+        }
+
+        counts
+    }
+
+    /// The longest run of pieces without seeing each piece.
+    fn longest_drought(pieces: &[GooglyBlockPiece]) -> usize {
+        PIECES.iter().map(|target| {
+            let mut longest = 0;
+            let mut current = 0;
+            for piece in pieces.iter() {
+                if piece == target {
+                    current = 0;
+                } else {
+                    current += 1;
+                    longest = usize::max(longest, current);
+                }
+            }
+            longest
+        }).max().unwrap()
+    }
+
+    /// The fraction of pieces that repeat the piece before them.
+    fn repeat_fraction(pieces: &[GooglyBlockPiece]) -> f64 {
+        let repeats = pieces.windows(2).filter(|pair| pair[0] == pair[1]).count();
+
+        (repeats as f64) / ((pieces.len() - 1) as f64)
+    }
+
+    fn assert_evenly_distributed(pieces: &[GooglyBlockPiece], tolerance: f64) {
+        let counts = frequencies(pieces);
+        let expected = 1.0 / 7.0;
+        for piece in PIECES.iter() {
+            let result = (counts[piece] as f64) / (pieces.len() as f64);
+            assert!(
+                (result - expected).abs() < tolerance,
+                "piece {:?} has frequency {} but expected about {}", piece, result, expected
+            );
+        }
+    }
+
+
+    /// Every randomizer should deal the same sequence for the same seed.
+    #[test]
+    fn randomizers_should_be_deterministic_for_a_given_seed() {
+        let kinds = [
+            RandomizerKind::Classic, RandomizerKind::Nes, RandomizerKind::Tgm, RandomizerKind::SevenBag
+        ];
+        for kind in kinds.iter() {
+            assert_eq!(sequence(*kind, 7), sequence(*kind, 7), "randomizer {}", kind);
+        }
+    }
+
+    /// The randomizer names in the settings should round trip.
+    #[test]
+    fn randomizer_kinds_should_parse_from_their_names() {
+        let kinds = [
+            RandomizerKind::Classic, RandomizerKind::Nes, RandomizerKind::Tgm, RandomizerKind::SevenBag
+        ];
+        for kind in kinds.iter() {
+            let result: RandomizerKind = format!("{}", kind).parse().unwrap();
+            assert_eq!(result, *kind);
+        }
+        assert!("uniform".parse::<RandomizerKind>().is_err());
+    }
+
+    /// The classic randomizer should deal every piece equally often, and it should
+    /// almost never repeat a piece.
+    #[test]
+    fn classic_randomizer_should_be_uniform_and_rarely_repeat() {
+        let pieces = sequence(RandomizerKind::Classic, 1);
+
+        assert_evenly_distributed(&pieces, 0.01);
+        assert!(repeat_fraction(&pieces) < 0.001);
+    }
+
+    /// The NES randomizer should deal every piece equally often, and it should
+    /// repeat a piece about two times in fifty six.
+    #[test]
+    fn nes_randomizer_should_be_uniform_and_sometimes_repeat() {
+        let pieces = sequence(RandomizerKind::Nes, 2);
+        let repeats = repeat_fraction(&pieces);
+
+        assert_evenly_distributed(&pieces, 0.01);
+        assert!(repeats > 0.025 && repeats < 0.047, "repeat fraction {}", repeats);
+    }
+
+    /// The TGM randomizer should deal every piece equally often, and its history
+    /// should make droughts far shorter than uniform sampling does.
+    #[test]
+    fn tgm_randomizer_should_be_uniform_with_short_droughts() {
+        let pieces = sequence(RandomizerKind::Tgm, 3);
+        let classic_pieces = sequence(RandomizerKind::Classic, 3);
+
+        assert_evenly_distributed(&pieces, 0.01);
+        assert!(repeat_fraction(&pieces) < 0.01);
+        assert!(longest_drought(&pieces) < longest_drought(&classic_pieces));
+    }
+
+    /// The TGM randomizer should never start with an S, Z, or O piece.
+    #[test]
+    fn tgm_randomizer_should_not_start_with_an_s_z_or_o_piece() {
+        for seed in 0..1000 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut randomizer = RandomizerKind::Tgm.create(&PIECES);
+            let first = randomizer.next(&mut rng);
+            assert_ne!(first, GooglyBlockPiece::S);
+            assert_ne!(first, GooglyBlockPiece::Z);
+            assert_ne!(first, GooglyBlockPiece::O);
+        }
+    }
+
+    /// Every group of seven pieces dealt by the seven bag randomizer should contain
+    /// each piece exactly once.
+    #[test]
+    fn seven_bag_randomizer_should_deal_every_piece_once_per_bag() {
+        let pieces = sequence(RandomizerKind::SevenBag, 4);
+        for bag in pieces.chunks(7) {
+            let counts = frequencies(bag);
+            for piece in PIECES.iter() {
+                assert_eq!(counts[piece], 1);
+            }
+        }
+    }
+
+    /// The seven bag randomizer can never go more than twelve pieces without dealing
+    /// a given piece, or deal the same piece more than twice in a row.
+    #[test]
+    fn seven_bag_randomizer_should_bound_droughts_and_runs() {
+        let pieces = sequence(RandomizerKind::SevenBag, 5);
+        let longest_run = pieces.windows(3).any(|run| run[0] == run[1] && run[1] == run[2]);
+
+        assert!(longest_drought(&pieces) <= 12);
+        assert!(!longest_run);
+    }
+
+    /// Every randomizer should only deal the pieces it was given, and it should
+    /// deal all of them.
+    #[test]
+    fn randomizers_should_deal_only_the_pieces_they_are_given() {
+        let pieces = [GooglyBlockPiece::Custom(0), GooglyBlockPiece::Custom(1), GooglyBlockPiece::Custom(2)];
+        let kinds = [
+            RandomizerKind::Classic, RandomizerKind::Nes, RandomizerKind::Tgm, RandomizerKind::SevenBag
+        ];
+        for kind in kinds.iter() {
+            let mut rng = StdRng::seed_from_u64(6);
+            let mut randomizer = kind.create(&pieces);
+            let counts = frequencies(&(0..300).map(|_| randomizer.next(&mut rng)).collect::<Vec<GooglyBlockPiece>>());
+
+            assert_eq!(counts.len(), pieces.len(), "randomizer {}", kind);
+            assert!(pieces.iter().all(|piece| counts[piece] > 0), "randomizer {}", kind);
+        }
+    }
+}
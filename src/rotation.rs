@@ -0,0 +1,564 @@
+/*
+ *  Googly Blocks is a video game.
+ *  Copyright (C) 2018,2019,2020  Christopher Blanchard
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use crate::block::{
+    GooglyBlock,
+    GooglyBlockPiece,
+    GooglyBlockRotation,
+    GooglyBlockShape,
+};
+use crate::playing_field::{
+    BlockPosition,
+    LandedBlocksGrid,
+};
+use crate::playing_field;
+
+use std::fmt;
+use std::str;
+
+
+/// A wall kick offset in playing field rows and columns. Rows grow down the
+/// playing field, so a negative row offset kicks a block upwards.
+pub type KickOffset = (isize, isize);
+
+/// A rotation system decides where a block goes when the player rotates it.
+pub trait RotationSystem {
+    /// The offsets from the block's current position to try, in order, when
+    /// rotating `block` into `rotated`. The playing field takes the first offset
+    /// where the rotated block fits, and the rotation fails if none of them do.
+    fn kicks(
+        &self,
+        block: GooglyBlock,
+        rotated: GooglyBlock,
+        rotated_shape: &GooglyBlockShape,
+        top_left: BlockPosition, landed: &LandedBlocksGrid) -> Vec<KickOffset>;
+}
+
+/// The rotation systems that can be selected in the game settings.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RotationSystemKind {
+    /// Kick a block away from the side walls only.
+    Classic,
+    /// The Super Rotation System.
+    Srs,
+}
+
+impl RotationSystemKind {
+    /// Construct a rotation system of this kind.
+    pub fn create(self) -> Box<dyn RotationSystem> {
+        match self {
+            RotationSystemKind::Classic => Box::new(ClassicRotationSystem::new()),
+            RotationSystemKind::Srs => Box::new(SuperRotationSystem::new()),
+        }
+    }
+}
+
+impl fmt::Display for RotationSystemKind {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let disp = match *self {
+            RotationSystemKind::Classic => "classic",
+            RotationSystemKind::Srs => "srs",
+        };
+        write!(formatter, "{}", disp)
+    }
+}
+
+impl str::FromStr for RotationSystemKind {
+    type Err = String;
+
+    fn from_str(st: &str) -> Result<RotationSystemKind, String> {
+        match st {
+            "classic" => Ok(RotationSystemKind::Classic),
+            "srs" => Ok(RotationSystemKind::Srs),
+            _ => Err(format!("Unknown rotation system: {}", st)),
+        }
+    }
+}
+
+/// The original Googly Blocks rotation system. A rotated block that pokes
+/// through a side wall is pushed back into the playing field by the wall
+/// kick distance of its shape. It never kicks off landed blocks or the floor.
+#[derive(Default)]
+pub struct ClassicRotationSystem {}
+
+impl ClassicRotationSystem {
+    pub fn new() -> ClassicRotationSystem {
+        ClassicRotationSystem {}
+    }
+}
+
+impl RotationSystem for ClassicRotationSystem {
+    fn kicks(
+        &self,
+        _block: GooglyBlock,
+        _rotated: GooglyBlock,
+        rotated_shape: &GooglyBlockShape,
+        top_left: BlockPosition, landed: &LandedBlocksGrid) -> Vec<KickOffset> {
+
+        let wall_kick_distance = rotated_shape.wall_kick_distance;
+        if playing_field::collides_with_left_wall(rotated_shape, top_left) {
+            vec![(0, wall_kick_distance)]
+        } else if playing_field::collides_with_right_wall(rotated_shape, top_left, landed) {
+            vec![(0, -wall_kick_distance)]
+        } else {
+            vec![(0, 0)]
+        }
+    }
+}
+
+/// The four orientations of a piece in the Super Rotation System, named
+/// relative to the orientation a piece spawns in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum SrsState {
+    Spawn,
+    Right,
+    Two,
+    Left,
+}
+
+impl SrsState {
+    /// The SRS orientation of a googly block rotation. Googly blocks spawn
+    /// upside down compared to SRS pieces, so R0 is the SRS state 2, and
+    /// each rotation turns the block clockwise.
+    fn from_rotation(rotation: GooglyBlockRotation) -> SrsState {
+        match rotation {
+            GooglyBlockRotation::R0 => SrsState::Two,
+            GooglyBlockRotation::R1 => SrsState::Left,
+            GooglyBlockRotation::R2 => SrsState::Spawn,
+            GooglyBlockRotation::R3 => SrsState::Right,
+        }
+    }
+}
+
+/// The SRS wall kicks for the J, L, S, T, and Z pieces, in rows and columns.
+fn srs_jlstz_kicks(from: SrsState, to: SrsState) -> [KickOffset; 5] {
+    use self::SrsState::*;
+    match (from, to) {
+        (Spawn, Right) | (Two, Right) => [(0, 0), (0, -1), (-1, -1), (2, 0), (2, -1)],
+        (Right, Spawn) | (Right, Two) => [(0, 0), (0, 1), (1, 1), (-2, 0), (-2, 1)],
+        (Two, Left) | (Spawn, Left) => [(0, 0), (0, 1), (-1, 1), (2, 0), (2, 1)],
+        (Left, Two) | (Left, Spawn) => [(0, 0), (0, -1), (1, -1), (-2, 0), (-2, -1)],
+        _ => [(0, 0); 5],
+    }
+}
+
+/// The SRS wall kicks for the I piece, in rows and columns.
+fn srs_i_kicks(from: SrsState, to: SrsState) -> [KickOffset; 5] {
+    use self::SrsState::*;
+    match (from, to) {
+        (Spawn, Right) | (Left, Two) => [(0, 0), (0, -2), (0, 1), (1, -2), (-2, 1)],
+        (Right, Spawn) | (Two, Left) => [(0, 0), (0, 2), (0, -1), (-1, 2), (2, -1)],
+        (Right, Two) | (Spawn, Left) => [(0, 0), (0, -1), (0, 2), (-2, -1), (1, 2)],
+        (Two, Right) | (Left, Spawn) => [(0, 0), (0, 1), (0, -2), (2, 1), (-1, -2)],
+        _ => [(0, 0); 5],
+    }
+}
+
+/// The offset from the cells of a googly block shape to the cells of the SRS
+/// shape for the same orientation. Some googly block shapes do not sit in the
+/// same place in their bounding box as SRS shapes do, so this offset makes the
+/// blocks turn about the same centre as SRS pieces.
+fn srs_shape_offset(block: GooglyBlock) -> KickOffset {
+    use crate::block::GooglyBlockPiece::*;
+    use crate::block::GooglyBlockRotation::*;
+    match (block.piece, block.rotation) {
+        (T, R0) => (1, 0),
+        (S, R1) | (Z, R1) | (I, R1) => (0, -1),
+        (S, R2) | (Z, R2) | (I, R2) => (-1, 0),
+        _ => (0, 0),
+    }
+}
+
+/// The Super Rotation System. Each rotation tries five kick offsets from a
+/// table chosen by the starting and ending orientation, with a separate table
+/// for the I piece. The kicks move blocks off of the walls, the floor, and
+/// landed blocks. The O piece never kicks, and the pieces of custom piece sets
+/// kick like the J, L, S, T, and Z pieces.
+#[derive(Default)]
+pub struct SuperRotationSystem {}
+
+impl SuperRotationSystem {
+    pub fn new() -> SuperRotationSystem {
+        SuperRotationSystem {}
+    }
+}
+
+impl RotationSystem for SuperRotationSystem {
+    fn kicks(
+        &self,
+        block: GooglyBlock,
+        rotated: GooglyBlock,
+        _rotated_shape: &GooglyBlockShape,
+        _top_left: BlockPosition, _landed: &LandedBlocksGrid) -> Vec<KickOffset> {
+
+        let from = SrsState::from_rotation(block.rotation);
+        let to = SrsState::from_rotation(rotated.rotation);
+        let kicks = match block.piece {
+            GooglyBlockPiece::O => return vec![(0, 0)],
+            GooglyBlockPiece::I => srs_i_kicks(from, to),
+            _ => srs_jlstz_kicks(from, to),
+        };
+        let (from_row, from_column) = srs_shape_offset(block);
+        let (to_row, to_column) = srs_shape_offset(rotated);
+
+        kicks.iter()
+            .map(|(row, column)| (row + to_row - from_row, column + to_column - from_column))
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        RotationSystemKind,
+    };
+    use crate::block::{
+        GooglyBlock,
+        GooglyBlockElement,
+        GooglyBlockPiece,
+        GooglyBlockRotation,
+    };
+    use crate::playing_field::{
+        BlockPosition,
+        GooglyBlockMove,
+        PlayingFieldContext,
+        PlayingFieldContextSpec,
+    };
+    use crate::piece_set::{
+        PieceSet,
+    };
+    use std::collections::hash_map::HashMap;
+    use std::rc::Rc;
+
+
+    type Cells = Vec<(isize, isize)>;
+
+    const PIECES: [GooglyBlockPiece; 7] = [
+        GooglyBlockPiece::T, GooglyBlockPiece::J, GooglyBlockPiece::Z, GooglyBlockPiece::O,
+        GooglyBlockPiece::S, GooglyBlockPiece::L, GooglyBlockPiece::I,
+    ];
+
+    const ROTATIONS: [GooglyBlockRotation; 4] = [
+        GooglyBlockRotation::R0, GooglyBlockRotation::R1, GooglyBlockRotation::R2, GooglyBlockRotation::R3,
+    ];
+
+    /// The SRS states in clockwise order starting from the spawn state.
+    const SPAWN: usize = 0;
+    const RIGHT: usize = 1;
+    const TWO: usize = 2;
+    const LEFT: usize = 3;
+
+    /// The SRS state of each googly block rotation.
+    fn srs_state(rotation: GooglyBlockRotation) -> usize {
+        match rotation {
+            GooglyBlockRotation::R0 => TWO,
+            GooglyBlockRotation::R1 => LEFT,
+            GooglyBlockRotation::R2 => SPAWN,
+            GooglyBlockRotation::R3 => RIGHT,
+        }
+    }
+
+    /// The standard SRS piece shapes in their bounding boxes, listed by row and
+    /// column with rows growing downwards.
+    fn srs_shape(piece: GooglyBlockPiece, state: usize) -> Cells {
+        let cells: [[(isize, isize); 4]; 4] = match piece {
+            GooglyBlockPiece::T => [
+                [(0, 1), (1, 0), (1, 1), (1, 2)],
+                [(0, 1), (1, 1), (1, 2), (2, 1)],
+                [(1, 0), (1, 1), (1, 2), (2, 1)],
+                [(0, 1), (1, 0), (1, 1), (2, 1)],
+            ],
+            GooglyBlockPiece::J => [
+                [(0, 0), (1, 0), (1, 1), (1, 2)],
+                [(0, 1), (0, 2), (1, 1), (2, 1)],
+                [(1, 0), (1, 1), (1, 2), (2, 2)],
+                [(0, 1), (1, 1), (2, 0), (2, 1)],
+            ],
+            GooglyBlockPiece::L => [
+                [(0, 2), (1, 0), (1, 1), (1, 2)],
+                [(0, 1), (1, 1), (2, 1), (2, 2)],
+                [(1, 0), (1, 1), (1, 2), (2, 0)],
+                [(0, 0), (0, 1), (1, 1), (2, 1)],
+            ],
+            GooglyBlockPiece::S => [
+                [(0, 1), (0, 2), (1, 0), (1, 1)],
+                [(0, 1), (1, 1), (1, 2), (2, 2)],
+                [(1, 1), (1, 2), (2, 0), (2, 1)],
+                [(0, 0), (1, 0), (1, 1), (2, 1)],
+            ],
+            GooglyBlockPiece::Z => [
+                [(0, 0), (0, 1), (1, 1), (1, 2)],
+                [(0, 2), (1, 1), (1, 2), (2, 1)],
+                [(1, 0), (1, 1), (2, 1), (2, 2)],
+                [(0, 1), (1, 0), (1, 1), (2, 0)],
+            ],
+            GooglyBlockPiece::I => [
+                [(1, 0), (1, 1), (1, 2), (1, 3)],
+                [(0, 2), (1, 2), (2, 2), (3, 2)],
+                [(2, 0), (2, 1), (2, 2), (2, 3)],
+                [(0, 1), (1, 1), (2, 1), (3, 1)],
+            ],
+            GooglyBlockPiece::O => [
+                [(0, 1), (0, 2), (1, 1), (1, 2)],
+                [(0, 1), (0, 2), (1, 1), (1, 2)],
+                [(0, 1), (0, 2), (1, 1), (1, 2)],
+                [(0, 1), (0, 2), (1, 1), (1, 2)],
+            ],
+            GooglyBlockPiece::Custom(_) => panic!("Custom pieces have no standard SRS shape."),
+        };
+
+        sorted(cells[state].to_vec())
+    }
+
+    /// The standard SRS wall kick tables, listed as (x, y) offsets with y growing
+    /// upwards, the way the guideline writes them.
+    fn srs_kicks(piece: GooglyBlockPiece, from: usize, to: usize) -> [(isize, isize); 5] {
+        match piece {
+            GooglyBlockPiece::O => [(0, 0); 5],
+            GooglyBlockPiece::I => match (from, to) {
+                (SPAWN, RIGHT) => [(0, 0), (-2, 0), ( 1, 0), (-2, -1), ( 1,  2)],
+                (RIGHT, SPAWN) => [(0, 0), ( 2, 0), (-1, 0), ( 2,  1), (-1, -2)],
+                (RIGHT, TWO)   => [(0, 0), (-1, 0), ( 2, 0), (-1,  2), ( 2, -1)],
+                (TWO, RIGHT)   => [(0, 0), ( 1, 0), (-2, 0), ( 1, -2), (-2,  1)],
+                (TWO, LEFT)    => [(0, 0), ( 2, 0), (-1, 0), ( 2,  1), (-1, -2)],
+                (LEFT, TWO)    => [(0, 0), (-2, 0), ( 1, 0), (-2, -1), ( 1,  2)],
+                (LEFT, SPAWN)  => [(0, 0), ( 1, 0), (-2, 0), ( 1, -2), (-2,  1)],
+                (SPAWN, LEFT)  => [(0, 0), (-1, 0), ( 2, 0), (-1,  2), ( 2, -1)],
+                _ => panic!("Not a rotation by a quarter turn."),
+            },
+            _ => match (from, to) {
+                (SPAWN, RIGHT) => [(0, 0), (-1, 0), (-1,  1), (0, -2), (-1, -2)],
+                (RIGHT, SPAWN) => [(0, 0), ( 1, 0), ( 1, -1), (0,  2), ( 1,  2)],
+                (RIGHT, TWO)   => [(0, 0), ( 1, 0), ( 1, -1), (0,  2), ( 1,  2)],
+                (TWO, RIGHT)   => [(0, 0), (-1, 0), (-1,  1), (0, -2), (-1, -2)],
+                (TWO, LEFT)    => [(0, 0), ( 1, 0), ( 1,  1), (0, -2), ( 1, -2)],
+                (LEFT, TWO)    => [(0, 0), (-1, 0), (-1, -1), (0,  2), (-1,  2)],
+                (LEFT, SPAWN)  => [(0, 0), (-1, 0), (-1, -1), (0,  2), (-1,  2)],
+                (SPAWN, LEFT)  => [(0, 0), ( 1, 0), ( 1,  1), (0, -2), ( 1, -2)],
+                _ => panic!("Not a rotation by a quarter turn."),
+            },
+        }
+    }
+
+    fn sorted(mut cells: Cells) -> Cells {
+        cells.sort();
+        cells
+    }
+
+    fn translate(cells: &Cells, row: isize, column: isize) -> Cells {
+        sorted(cells.iter().map(|(r, c)| (r + row, c + column)).collect())
+    }
+
+    fn block_cells(playing_field: &PlayingFieldContext) -> Cells {
+        let position = playing_field.current_position;
+        let cells = playing_field.current_shape().iter()
+            .map(|(row, column)| (position.row + row as isize, position.column + column as isize))
+            .collect();
+
+        sorted(cells)
+    }
+
+    /// Find the top left corner of the SRS bounding box holding the given cells.
+    fn srs_box(cells: &Cells, piece: GooglyBlockPiece, state: usize) -> (isize, isize) {
+        let shape = srs_shape(piece, state);
+        let row = cells[0].0 - shape[0].0;
+        let column = cells[0].1 - shape[0].1;
+        assert_eq!(translate(&shape, row, column), *cells, "{:?} in state {}", piece, state);
+
+        (row, column)
+    }
+
+    fn playing_field(block: GooglyBlock, kind: RotationSystemKind) -> PlayingFieldContext {
+        let starting_positions: HashMap<GooglyBlockPiece, BlockPosition> = PIECES.iter()
+            .map(|piece| (*piece, BlockPosition::new(8, 3)))
+            .collect();
+        let spec = PlayingFieldContextSpec {
+            starting_block: block,
+            starting_positions: starting_positions,
+            piece_set: Rc::new(PieceSet::standard()),
+            rotation_system: kind,
+            rows: 20,
+            columns: 10,
+        };
+
+        PlayingFieldContext::new(spec)
+    }
+
+    fn rotations() -> Vec<(GooglyBlockRotation, GooglyBlockMove, GooglyBlockRotation)> {
+        let mut rotations = vec![];
+        for rotation in ROTATIONS.iter() {
+            let block = GooglyBlock::new(GooglyBlockPiece::T, *rotation);
+            rotations.push((*rotation, GooglyBlockMove::RotateClockwise, block.rotate_cw().rotation));
+            rotations.push((*rotation, GooglyBlockMove::RotateCounterClockwise, block.rotate_ccw().rotation));
+        }
+
+        rotations
+    }
+
+
+    /// Every googly block shape should be an SRS shape in the matching orientation.
+    #[test]
+    fn googly_block_shapes_should_match_the_srs_shapes() {
+        for piece in PIECES.iter() {
+            for rotation in ROTATIONS.iter() {
+                let playing_field = playing_field(GooglyBlock::new(*piece, *rotation), RotationSystemKind::Srs);
+                srs_box(&block_cells(&playing_field), *piece, srs_state(*rotation));
+            }
+        }
+    }
+
+    /// In open space, every piece should turn about the centre of its SRS bounding
+    /// box for every rotation in both directions.
+    #[test]
+    fn srs_rotations_in_open_space_should_turn_about_the_srs_centre() {
+        for piece in PIECES.iter() {
+            for (from, block_move, to) in rotations() {
+                let mut playing_field = playing_field(GooglyBlock::new(*piece, from), RotationSystemKind::Srs);
+                let (box_row, box_column) = srs_box(&block_cells(&playing_field), *piece, srs_state(from));
+                playing_field.update_block_position(block_move);
+                let expected = translate(&srs_shape(*piece, srs_state(to)), box_row, box_column);
+                let result = block_cells(&playing_field);
+
+                assert_eq!(playing_field.current_block, GooglyBlock::new(*piece, to));
+                assert_eq!(result, expected, "{:?} from {:?} to {:?}", piece, from, to);
+            }
+        }
+    }
+
+    /// When the first kicks are blocked by landed blocks, an SRS rotation should
+    /// take the first kick in the table that fits. This is checked for every kick
+    /// of every piece and every rotation where the earlier kicks can be blocked
+    /// without blocking the later one.
+    #[test]
+    fn srs_rotations_should_take_the_first_kick_that_fits() {
+        let mut cases = 0;
+        for piece in PIECES.iter().filter(|piece| **piece != GooglyBlockPiece::O) {
+            for (from, block_move, to) in rotations() {
+                let from_state = srs_state(from);
+                let to_state = srs_state(to);
+                let kicks = srs_kicks(*piece, from_state, to_state);
+                let (box_row, box_column) = {
+                    let playing_field = playing_field(GooglyBlock::new(*piece, from), RotationSystemKind::Srs);
+                    srs_box(&block_cells(&playing_field), *piece, from_state)
+                };
+                let candidates: Vec<Cells> = kicks.iter()
+                    .map(|(x, y)| translate(&srs_shape(*piece, to_state), box_row - y, box_column + x))
+                    .collect();
+                for kick in 1..kicks.len() {
+                    let blockers: Option<Vec<(isize, isize)>> = (0..kick)
+                        .map(|earlier| {
+                            candidates[earlier].iter()
+                                .find(|cell| !candidates[kick].contains(cell))
+                                .copied()
+                        })
+                        .collect();
+                    let blockers = match blockers {
+                        Some(blockers) => blockers,
+                        None => continue,
+                    };
+                    let mut playing_field = playing_field(GooglyBlock::new(*piece, from), RotationSystemKind::Srs);
+                    for (row, column) in blockers.iter() {
+                        playing_field.landed_blocks.insert(*row, *column, GooglyBlockElement::T);
+                    }
+                    playing_field.update_block_position(block_move);
+                    let result = block_cells(&playing_field);
+
+                    assert_eq!(
+                        result, candidates[kick],
+                        "{:?} from {:?} to {:?} with kick {}", piece, from, to, kick
+                    );
+                    cases += 1;
+                }
+            }
+        }
+
+        // Six pieces with eight rotations and four kicks each.
+        assert!(cases >= 6 * 8 * 4 / 2, "Only {} kicks were tested.", cases);
+    }
+
+    /// A T block resting on the floor should kick upwards when it rotates into
+    /// an orientation that pokes through the floor.
+    #[test]
+    fn srs_rotations_should_kick_off_the_floor() {
+        let block = GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R2);
+        let mut playing_field = playing_field(block, RotationSystemKind::Srs);
+        playing_field.update_block_position(GooglyBlockMove::HardDrop);
+        let (box_row, box_column) = srs_box(&block_cells(&playing_field), GooglyBlockPiece::T, SPAWN);
+        playing_field.update_block_position(GooglyBlockMove::RotateClockwise);
+        // The first two kicks poke through the floor, and the third kick moves
+        // the block up one row and one column to the left.
+        let expected = translate(&srs_shape(GooglyBlockPiece::T, RIGHT), box_row - 1, box_column - 1);
+        let result = block_cells(&playing_field);
+
+        assert_eq!(result, expected);
+    }
+
+    /// An I block standing against the left wall should kick away from the wall
+    /// when it turns flat.
+    #[test]
+    fn srs_rotations_should_kick_the_i_block_off_the_wall() {
+        let block = GooglyBlock::new(GooglyBlockPiece::I, GooglyBlockRotation::R3);
+        let mut playing_field = playing_field(block, RotationSystemKind::Srs);
+        for _ in 0..10 {
+            playing_field.update_block_position(GooglyBlockMove::Left);
+        }
+        playing_field.update_block_position(GooglyBlockMove::RotateClockwise);
+        let result = block_cells(&playing_field);
+
+        assert!(result.iter().all(|(_, column)| *column >= 0));
+        assert_eq!(playing_field.current_block, GooglyBlock::new(GooglyBlockPiece::I, GooglyBlockRotation::R0));
+    }
+
+    /// The classic rotation system should push a block that pokes through a side
+    /// wall back into the playing field by its wall kick distance.
+    #[test]
+    fn classic_rotations_should_kick_off_the_side_walls() {
+        let block = GooglyBlock::new(GooglyBlockPiece::I, GooglyBlockRotation::R1);
+        let mut playing_field = playing_field(block, RotationSystemKind::Classic);
+        for _ in 0..10 {
+            playing_field.update_block_position(GooglyBlockMove::Left);
+        }
+        let column = playing_field.current_position.column;
+        playing_field.update_block_position(GooglyBlockMove::RotateClockwise);
+
+        assert_eq!(playing_field.current_block, GooglyBlock::new(GooglyBlockPiece::I, GooglyBlockRotation::R2));
+        assert_eq!(playing_field.current_position.column, column + 2);
+    }
+
+    /// The classic rotation system should not kick off of landed blocks.
+    #[test]
+    fn classic_rotations_should_not_kick_off_landed_blocks() {
+        let block = GooglyBlock::new(GooglyBlockPiece::T, GooglyBlockRotation::R0);
+        let mut playing_field = playing_field(block, RotationSystemKind::Classic);
+        let expected = playing_field.current_block;
+        let position = playing_field.current_position;
+        playing_field.landed_blocks.insert(position.row + 2, position.column + 1, GooglyBlockElement::J);
+        playing_field.update_block_position(GooglyBlockMove::RotateClockwise);
+
+        assert_eq!(playing_field.current_block, expected);
+        assert_eq!(playing_field.current_position, position);
+    }
+
+    /// Rotation systems should be selectable by name.
+    #[test]
+    fn rotation_systems_should_be_parsed_from_their_names() {
+        assert_eq!("srs".parse::<RotationSystemKind>(), Ok(RotationSystemKind::Srs));
+        assert_eq!("classic".parse::<RotationSystemKind>(), Ok(RotationSystemKind::Classic));
+        assert!("ars".parse::<RotationSystemKind>().is_err());
+    }
+}
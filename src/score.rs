@@ -19,6 +19,369 @@ use crate::block::{
     GooglyBlockPiece,
     GooglyBlock,
 };
+use crate::playing_field::{
+    TSpin,
+};
+use toml::{
+    Value,
+};
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str;
+
+
+/// A line clear made by a block locking into the playing field.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LineClear {
+    /// The number of lines cleared. This is zero when the block locks
+    /// without completing any lines.
+    pub lines: usize,
+    /// The kind of spin the block locked with.
+    pub t_spin: TSpin,
+    /// The number of line clearing blocks in a row, counting this one. This is 
+    /// filled in by the score board.
+    pub combo: usize,
+    /// Whether this is a difficult line clear following another difficult line 
+    /// clear. This is filled in by the score board.
+    pub back_to_back: bool,
+    /// Whether the line clear emptied the playing field.
+    pub perfect_clear: bool,
+}
+
+impl LineClear {
+    /// Construct a line clear of the given number of lines.
+    pub fn new(lines: usize, t_spin: TSpin) -> LineClear {
+        LineClear {
+            lines: lines,
+            t_spin: t_spin,
+            combo: 0,
+            back_to_back: false,
+            perfect_clear: false,
+        }
+    }
+
+    /// Determine whether this is a difficult line clear. Tetrises and T-spins
+    /// that clear lines are difficult.
+    pub fn is_difficult(&self) -> bool {
+        self.lines >= 4 || (self.lines > 0 && self.t_spin != TSpin::None)
+    }
+
+    /// Determine whether the block cleared any lines or locked with a T-spin.
+    pub fn is_empty(&self) -> bool {
+        self.lines == 0 && self.t_spin == TSpin::None
+    }
+}
+
+impl fmt::Display for LineClear {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let spin = match self.t_spin {
+            TSpin::None => "",
+            TSpin::Mini => "T-SPIN MINI",
+            TSpin::Full => "T-SPIN",
+        };
+        let lines = match self.lines {
+            0 => "",
+            1 => "SINGLE",
+            2 => "DOUBLE",
+            3 => "TRIPLE",
+            _ => "TETRIS",
+        };
+        let separator = if spin.is_empty() || lines.is_empty() { "" } else { " " };
+        write!(formatter, "{}{}{}", spin, separator, lines)
+    }
+}
+
+/// A scoring system decides how many points the player earns for each line
+/// clear and for dropping blocks.
+pub trait ScoringSystem {
+    /// The points for a block locking with the given line clear at the given level.
+    fn line_clear(&self, clear: LineClear, level: usize) -> usize;
+    /// The points for soft dropping a block the given number of rows.
+    fn soft_drop(&self, rows: usize, level: usize) -> usize;
+    /// The points for hard dropping a block the given number of rows.
+    fn hard_drop(&self, rows: usize, level: usize) -> usize;
+}
+
+/// The scoring systems that can be selected in the game settings.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScoringSystemKind {
+    /// The NES scoring system.
+    Nes,
+    /// The Tetris guideline scoring system.
+    Guideline,
+    /// A scoring system with custom point values.
+    Custom(CustomScoringSystem),
+}
+
+impl ScoringSystemKind {
+    /// Construct a scoring system of this kind.
+    pub fn create(self) -> Box<dyn ScoringSystem> {
+        match self {
+            ScoringSystemKind::Nes => Box::new(NesScoringSystem::new()),
+            ScoringSystemKind::Guideline => Box::new(GuidelineScoringSystem::new()),
+            ScoringSystemKind::Custom(scoring_system) => Box::new(scoring_system),
+        }
+    }
+}
+
+impl fmt::Display for ScoringSystemKind {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let disp = match *self {
+            ScoringSystemKind::Nes => "nes",
+            ScoringSystemKind::Guideline => "guideline",
+            ScoringSystemKind::Custom(_) => "custom",
+        };
+        write!(formatter, "{}", disp)
+    }
+}
+
+impl str::FromStr for ScoringSystemKind {
+    type Err = String;
+
+    fn from_str(st: &str) -> Result<ScoringSystemKind, String> {
+        match st {
+            "nes" => Ok(ScoringSystemKind::Nes),
+            "guideline" => Ok(ScoringSystemKind::Guideline),
+            _ => Err(format!("Unknown scoring system: {}", st)),
+        }
+    }
+}
+
+/// The NES scoring system. A line clear is worth 40, 100, 300, or 1200 points
+/// times one more than the level, and soft dropping is worth a point per row.
+/// The NES has no hard drop, T-spins, combos, or perfect clear bonus, so a hard 
+/// drop is worth two points per row, the same as in the guideline, and every 
+/// line clear scores by the number of lines alone.
+#[derive(Default)]
+pub struct NesScoringSystem {}
+
+impl NesScoringSystem {
+    pub fn new() -> NesScoringSystem {
+        NesScoringSystem {}
+    }
+}
+
+impl ScoringSystem for NesScoringSystem {
+    fn line_clear(&self, clear: LineClear, level: usize) -> usize {
+        let points = match clear.lines {
+            0 => 0,
+            1 => 40,
+            2 => 100,
+            3 => 300,
+            _ => 1200,
+        };
+
+        points * (level + 1)
+    }
+
+    fn soft_drop(&self, rows: usize, _level: usize) -> usize {
+        rows
+    }
+
+    fn hard_drop(&self, rows: usize, _level: usize) -> usize {
+        2 * rows
+    }
+}
+
+/// The Tetris guideline scoring system. A line clear is worth 100, 300, 500,
+/// or 800 points times the level, where guideline levels start from one. A
+/// T-spin is worth 400, 800, 1200, or 1600 points for clearing zero to three
+/// lines, and a T-spin mini 100, 200, or 400 points for clearing zero to two
+/// lines. A difficult line clear following another is worth half as much again,
+/// and each line clearing block after the first in a combo is worth 50 points
+/// more times the level. A perfect clear is worth a bonus of 800, 1200, 1800, or 
+/// 2000 points for clearing one to four lines. Soft dropping is worth a point per 
+/// row and hard dropping two points per row.
+#[derive(Default)]
+pub struct GuidelineScoringSystem {}
+
+impl GuidelineScoringSystem {
+    pub fn new() -> GuidelineScoringSystem {
+        GuidelineScoringSystem {}
+    }
+}
+
+impl ScoringSystem for GuidelineScoringSystem {
+    fn line_clear(&self, clear: LineClear, level: usize) -> usize {
+        let points = match (clear.t_spin, clear.lines) {
+            (TSpin::None, 0) => 0,
+            (TSpin::None, 1) => 100,
+            (TSpin::None, 2) => 300,
+            (TSpin::None, 3) => 500,
+            (TSpin::None, _) => 800,
+            (TSpin::Mini, 0) => 100,
+            (TSpin::Mini, 1) => 200,
+            (TSpin::Mini, _) => 400,
+            (TSpin::Full, 0) => 400,
+            (TSpin::Full, 1) => 800,
+            (TSpin::Full, 2) => 1200,
+            (TSpin::Full, _) => 1600,
+        };
+        let points = if clear.back_to_back { points * 3 / 2 } else { points };
+        let combo_points = 50 * clear.combo.saturating_sub(1);
+        let perfect_clear_points = if clear.perfect_clear {
+            match clear.lines {
+                0 => 0,
+                1 => 800,
+                2 => 1200,
+                3 => 1800,
+                _ => 2000,
+            }
+        } else {
+            0
+        };
+
+        (points + combo_points + perfect_clear_points) * (level + 1)
+    }
+
+    fn soft_drop(&self, rows: usize, _level: usize) -> usize {
+        rows
+    }
+
+    fn hard_drop(&self, rows: usize, _level: usize) -> usize {
+        2 * rows
+    }
+}
+
+/// A scoring system with custom point values.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CustomScoringSystem {
+    /// The points for clearing one, two, three, and four lines.
+    pub line_clear_points: [usize; 4],
+    /// The points for a T-spin clearing zero, one, two, and three lines.
+    pub t_spin_points: [usize; 4],
+    /// The points for a T-spin mini clearing zero, one, and two lines.
+    pub t_spin_mini_points: [usize; 3],
+    /// The extra points for each line clearing block after the first in a combo.
+    pub combo_points: usize,
+    /// The extra points for a back-to-back difficult line clear, as a percentage
+    /// of the points for the line clear.
+    pub back_to_back_percent: usize,
+    /// The extra points for a perfect clear of one, two, three, and four lines.
+    pub perfect_clear_points: [usize; 4],
+    /// Whether line clears are worth one more times their points than the level.
+    pub level_multiplier: bool,
+    /// The points for each row soft dropped.
+    pub soft_drop_points: usize,
+    /// The points for each row hard dropped.
+    pub hard_drop_points: usize,
+}
+
+impl Default for CustomScoringSystem {
+    /// The custom scoring system starts out with the guideline point values.
+    fn default() -> CustomScoringSystem {
+        CustomScoringSystem {
+            line_clear_points: [100, 300, 500, 800],
+            t_spin_points: [400, 800, 1200, 1600],
+            t_spin_mini_points: [100, 200, 400],
+            combo_points: 50,
+            back_to_back_percent: 50,
+            perfect_clear_points: [800, 1200, 1800, 2000],
+            level_multiplier: true,
+            soft_drop_points: 1,
+            hard_drop_points: 2,
+        }
+    }
+}
+
+impl CustomScoringSystem {
+    /// Load a custom scoring system from a definition file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<CustomScoringSystem, String> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|e| {
+            format!("Could not read scoring system {}: {}", path.display(), e)
+        })?;
+
+        CustomScoringSystem::from_toml(&source)
+    }
+
+    /// Parse a custom scoring system from the contents of a definition file. Each
+    /// field has the same name as in the scoring system, and a field the file 
+    /// leaves out keeps its guideline value.
+    pub fn from_toml(source: &str) -> Result<CustomScoringSystem, String> {
+        let value = source.parse::<Value>().map_err(|e| format!("Invalid scoring system: {}", e))?;
+        let table = value.as_table().ok_or("A scoring system must be a table")?;
+        let mut scoring_system = CustomScoringSystem::default();
+        for (field, value) in table.iter() {
+            match field.as_str() {
+                "line_clear_points" => parse_points(field, value, &mut scoring_system.line_clear_points)?,
+                "t_spin_points" => parse_points(field, value, &mut scoring_system.t_spin_points)?,
+                "t_spin_mini_points" => parse_points(field, value, &mut scoring_system.t_spin_mini_points)?,
+                "perfect_clear_points" => parse_points(field, value, &mut scoring_system.perfect_clear_points)?,
+                "combo_points" => scoring_system.combo_points = parse_point(field, value)?,
+                "back_to_back_percent" => scoring_system.back_to_back_percent = parse_point(field, value)?,
+                "soft_drop_points" => scoring_system.soft_drop_points = parse_point(field, value)?,
+                "hard_drop_points" => scoring_system.hard_drop_points = parse_point(field, value)?,
+                "level_multiplier" => {
+                    scoring_system.level_multiplier = value.as_bool()
+                        .ok_or(format!("The {} of a scoring system must be true or false", field))?;
+                }
+                _ => return Err(format!("Unknown scoring system field: {}", field)),
+            }
+        }
+
+        Ok(scoring_system)
+    }
+}
+
+/// Parse a non-negative point value of a scoring system definition.
+fn parse_point(field: &str, value: &Value) -> Result<usize, String> {
+    match value.as_integer() {
+        Some(points) if points >= 0 => Ok(points as usize),
+        _ => Err(format!("The {} of a scoring system must be a non-negative integer", field)),
+    }
+}
+
+/// Parse a list of point values of a scoring system definition, which must have
+/// exactly one value for each entry of the table it fills in.
+fn parse_points(field: &str, value: &Value, points: &mut [usize]) -> Result<(), String> {
+    let values = value.as_array()
+        .filter(|values| values.len() == points.len())
+        .ok_or(format!("The {} of a scoring system must be a list of {} integers", field, points.len()))?;
+    for (points, value) in points.iter_mut().zip(values.iter()) {
+        *points = parse_point(field, value)?;
+    }
+
+    Ok(())
+}
+
+impl ScoringSystem for CustomScoringSystem {
+    fn line_clear(&self, clear: LineClear, level: usize) -> usize {
+        let points = match (clear.t_spin, clear.lines) {
+            (TSpin::None, 0) => 0,
+            (TSpin::None, lines) => self.line_clear_points[usize::min(lines, 4) - 1],
+            (TSpin::Mini, lines) => self.t_spin_mini_points[usize::min(lines, 2)],
+            (TSpin::Full, lines) => self.t_spin_points[usize::min(lines, 3)],
+        };
+        let points = if clear.back_to_back {
+            points + points * self.back_to_back_percent / 100
+        } else {
+            points
+        };
+        let points = points + self.combo_points * clear.combo.saturating_sub(1);
+        let points = if clear.perfect_clear && clear.lines > 0 {
+            points + self.perfect_clear_points[usize::min(clear.lines, 4) - 1]
+        } else {
+            points
+        };
+
+        if self.level_multiplier {
+            points * (level + 1)
+        } else {
+            points
+        }
+    }
+
+    fn soft_drop(&self, rows: usize, _level: usize) -> usize {
+        self.soft_drop_points * rows
+    }
+
+    fn hard_drop(&self, rows: usize, _level: usize) -> usize {
+        self.hard_drop_points * rows
+    }
+}
 
 /// The score board type that tracks the player's progress during a 
 /// game of Googly Blocks.
@@ -33,27 +396,75 @@ pub struct ScoreBoard {
     /// was cleared with one piece. In particular, when four lines
     /// are cleared with an I piece.
     pub tetrises: usize,
+    /// The last block that cleared lines or locked with a T-spin.
+    pub last_clear: Option<LineClear>,
+    /// The number of line clearing blocks in a row. This goes back to zero when
+    /// a block locks without clearing any lines.
+    pub combo: usize,
+    /// Whether the last line clear was difficult, so that the next difficult 
+    /// line clear is back-to-back.
+    pub back_to_back: bool,
     /// The number of lines left before the next level.
     lines_before_next_level: usize,
     /// The number of lines per level.
     lines_per_level: usize,
+    /// The rules for awarding points.
+    scoring_system: Box<dyn ScoringSystem>,
+}
+
+/// The highest level a player can start a game at.
+pub const MAX_STARTING_LEVEL: usize = 19;
+
+/// The number of lines a player starting at the given level has to clear before
+/// the first level up, following the NES. Starting at level `n` takes as many lines
+/// as clearing levels zero through `n` would, but at most the larger of ten levels
+/// worth of lines and `n - 5` levels worth of lines. Every level up after the first 
+/// one takes one level worth of lines.
+fn lines_before_first_level(starting_level: usize, lines_per_level: usize) -> usize {
+    usize::min(
+        starting_level * lines_per_level + lines_per_level,
+        usize::max(10 * lines_per_level, (starting_level * lines_per_level).saturating_sub(5 * lines_per_level))
+    )
 }
 
 impl ScoreBoard {
-    /// Construct a new scoreboard.
-    pub fn new(lines_per_level: usize) -> ScoreBoard {
+    /// Construct a new scoreboard for a game starting at the given level.
+    pub fn new(lines_per_level: usize, starting_level: usize, scoring_system: Box<dyn ScoringSystem>) -> ScoreBoard {
         ScoreBoard {
             score: 0,
-            level: 0,
+            level: starting_level,
             lines: 0,
             tetrises: 0,
-            lines_before_next_level: lines_per_level,
+            last_clear: None,
+            combo: 0,
+            back_to_back: false,
+            lines_before_next_level: lines_before_first_level(starting_level, lines_per_level),
             lines_per_level: lines_per_level,
+            scoring_system: scoring_system,
         }
     }
 
-    /// Update the scoreboard.
-    pub fn update(&mut self, new_lines_cleared: usize) {
+    /// Update the scoreboard after a block locks into the playing field.
+    pub fn update(&mut self, clear: LineClear) {
+        let mut clear = clear;
+        if clear.lines > 0 {
+            self.combo += 1;
+            clear.back_to_back = self.back_to_back && clear.is_difficult();
+            self.back_to_back = clear.is_difficult();
+        } else {
+            self.combo = 0;
+        }
+        clear.combo = self.combo;
+
+        let new_lines_cleared = clear.lines;
+        self.score += self.scoring_system.line_clear(clear, self.level);
+        if new_lines_cleared >= 4 {
+            self.tetrises += 1;
+        }
+        if !clear.is_empty() {
+            self.last_clear = Some(clear);
+        }
+
         self.lines += new_lines_cleared;
         if new_lines_cleared >= self.lines_before_next_level {
             self.level += 1;
@@ -61,23 +472,16 @@ impl ScoreBoard {
         } else {
             self.lines_before_next_level -= new_lines_cleared;
         }
+    }
 
-        match new_lines_cleared {
-            0 => {}
-            1 => {
-                self.score += 40;
-            }
-            2 => {
-                self.score += 100;
-            }
-            3 => {
-                self.score += 300;
-            }
-            _ => {
-                self.score += 1200;
-                self.tetrises += 1;
-            }
-        }
+    /// Award points for soft dropping a block the given number of rows.
+    pub fn soft_drop(&mut self, rows: usize) {
+        self.score += self.scoring_system.soft_drop(rows, self.level);
+    }
+
+    /// Award points for hard dropping a block the given number of rows.
+    pub fn hard_drop(&mut self, rows: usize) {
+        self.score += self.scoring_system.hard_drop(rows, self.level);
     }
 }
 
@@ -98,6 +502,12 @@ pub struct Statistics {
     pub l_pieces: usize,
     /// The number of I pieces.
     pub i_pieces: usize, 
+    /// The number of pieces from a custom piece set.
+    pub custom_pieces: usize,
+    /// The longest combo of line clearing blocks in a row.
+    pub max_combo: usize,
+    /// The number of line clears that emptied the playing field.
+    pub perfect_clears: usize,
 }
 
 impl Statistics {
@@ -111,6 +521,9 @@ impl Statistics {
             s_pieces: 0,
             l_pieces: 0,
             i_pieces: 0,
+            custom_pieces: 0,
+            max_combo: 0,
+            perfect_clears: 0,
         }
     }
 
@@ -124,15 +537,36 @@ impl Statistics {
             GooglyBlockPiece::S => self.s_pieces += 1,
             GooglyBlockPiece::L => self.l_pieces += 1,
             GooglyBlockPiece::I => self.i_pieces += 1,
+            GooglyBlockPiece::Custom(_) => self.custom_pieces += 1,
         }
     }
+
+    /// Record the current combo, keeping the longest one seen so far.
+    pub fn update_combo(&mut self, combo: usize) {
+        self.max_combo = usize::max(self.max_combo, combo);
+    }
+
+    /// Count a line clear that emptied the playing field.
+    pub fn update_perfect_clear(&mut self) {
+        self.perfect_clears += 1;
+    }
 }
 
 
 #[cfg(test)]
 mod tests {
     use super::{
-        ScoreBoard
+        CustomScoringSystem,
+        GuidelineScoringSystem,
+        LineClear,
+        MAX_STARTING_LEVEL,
+        ScoreBoard,
+        ScoringSystem,
+        ScoringSystemKind,
+        Statistics,
+    };
+    use crate::playing_field::{
+        TSpin,
     };
 
 
@@ -140,8 +574,8 @@ mod tests {
     /// specified at construction time have been cleared.
     #[test]
     fn score_board_should_transition_to_next_level_on_crossing_line_threshold() {
-        let mut score_board = ScoreBoard::new(20);
-        score_board.update(20);
+        let mut score_board = ScoreBoard::new(20, 0, ScoringSystemKind::Nes.create());
+        score_board.update(LineClear::new(20, TSpin::None));
         let expected = 1;
         let result = score_board.level;
 
@@ -152,8 +586,8 @@ mod tests {
     /// the number of lines per level.
     #[test]
     fn score_board_should_not_transition_to_next_level_if_lines_per_level_not_crossed() {
-        let mut score_board = ScoreBoard::new(20);
-        score_board.update(19);
+        let mut score_board = ScoreBoard::new(20, 0, ScoringSystemKind::Nes.create());
+        score_board.update(LineClear::new(19, TSpin::None));
         let expected = 0;
         let result = score_board.level;
 
@@ -164,11 +598,267 @@ mod tests {
     /// after a level transition.
     #[test]
     fn score_board_lines_before_next_level_should_not_exceed_lines_per_level() {
-        let mut score_board = ScoreBoard::new(20);
-        score_board.update(21);
+        let mut score_board = ScoreBoard::new(20, 0, ScoringSystemKind::Nes.create());
+        score_board.update(LineClear::new(21, TSpin::None));
         let expected = score_board.lines_per_level;
         let result = score_board.lines_before_next_level;
 
         assert!(result <= expected);
     }
-}
\ No newline at end of file
+
+    /// Starting at a higher level should follow the NES rules for the number of 
+    /// lines until the first level up.
+    #[test]
+    fn the_first_level_up_should_follow_the_nes_rules() {
+        let cases = [(0, 10), (5, 60), (9, 100), (12, 100), (15, 100), (18, 130), (MAX_STARTING_LEVEL, 140)];
+        for (starting_level, lines) in cases.iter() {
+            let score_board = ScoreBoard::new(10, *starting_level, ScoringSystemKind::Nes.create());
+
+            assert_eq!(score_board.level, *starting_level);
+            assert_eq!(score_board.lines_before_next_level, *lines, "starting level {}", starting_level);
+        }
+    }
+
+    /// After the first level up, every level should take one level worth of lines.
+    #[test]
+    fn levels_after_the_first_level_up_should_take_the_lines_per_level() {
+        let mut score_board = ScoreBoard::new(10, 18, ScoringSystemKind::Nes.create());
+        for _ in 0..32 {
+            score_board.update(LineClear::new(4, TSpin::None));
+        }
+        assert_eq!(score_board.level, 18);
+
+        score_board.update(LineClear::new(2, TSpin::None));
+        assert_eq!(score_board.level, 19);
+
+        for _ in 0..2 {
+            score_board.update(LineClear::new(4, TSpin::None));
+        }
+        score_board.update(LineClear::new(2, TSpin::None));
+        assert_eq!(score_board.level, 20);
+    }
+
+    /// A hard drop should award two points for every row dropped.
+    #[test]
+    fn score_board_should_award_two_points_per_row_hard_dropped() {
+        let mut score_board = ScoreBoard::new(20, 0, ScoringSystemKind::Nes.create());
+        score_board.hard_drop(17);
+        let expected = 34;
+        let result = score_board.score;
+
+        assert_eq!(result, expected);
+    }
+
+    /// The NES scoring system should multiply the points for a line clear by one
+    /// more than the level.
+    #[test]
+    fn nes_scoring_should_multiply_line_clears_by_the_level() {
+        let mut score_board = ScoreBoard::new(4, 0, ScoringSystemKind::Nes.create());
+        score_board.update(LineClear::new(4, TSpin::None));
+        score_board.update(LineClear::new(1, TSpin::None));
+        let expected = 1200 + 40 * 2;
+        let result = score_board.score;
+
+        assert_eq!(result, expected);
+        assert_eq!(score_board.tetrises, 1);
+    }
+
+    /// The guideline scoring system should award 100, 300, 500, and 800 points
+    /// times the guideline level.
+    #[test]
+    fn guideline_scoring_should_award_guideline_points() {
+        let scoring_system = ScoringSystemKind::Guideline.create();
+        let result: Vec<usize> = (0..5).map(|lines| scoring_system.line_clear(LineClear::new(lines, TSpin::None), 2)).collect();
+        let expected = vec![0, 300, 900, 1500, 2400];
+
+        assert_eq!(result, expected);
+    }
+
+    /// The score board should award points for soft drops with the configured
+    /// scoring system.
+    #[test]
+    fn score_board_should_award_points_for_soft_drops() {
+        let mut score_board = ScoreBoard::new(20, 0, ScoringSystemKind::Nes.create());
+        score_board.soft_drop(5);
+
+        assert_eq!(score_board.score, 5);
+    }
+
+    /// A custom scoring system should use its own point values.
+    #[test]
+    fn custom_scoring_should_use_its_own_point_values() {
+        let scoring_system = CustomScoringSystem {
+            line_clear_points: [10, 20, 30, 40],
+            t_spin_points: [0, 0, 0, 0],
+            t_spin_mini_points: [0, 0, 0],
+            combo_points: 0,
+            back_to_back_percent: 0,
+            perfect_clear_points: [0, 0, 0, 0],
+            level_multiplier: false,
+            soft_drop_points: 0,
+            hard_drop_points: 3,
+        };
+        let mut score_board = ScoreBoard::new(1, 0, ScoringSystemKind::Custom(scoring_system).create());
+        score_board.update(LineClear::new(2, TSpin::None));
+        score_board.update(LineClear::new(3, TSpin::None));
+        score_board.soft_drop(10);
+        score_board.hard_drop(10);
+        let expected = 20 + 30 + 30;
+        let result = score_board.score;
+
+        assert_eq!(result, expected);
+    }
+
+    /// The guideline scoring system should award T-spin points, and the NES
+    /// scoring system should score a T-spin as a plain line clear.
+    #[test]
+    fn t_spins_should_be_scored_by_the_scoring_system() {
+        let guideline = ScoringSystemKind::Guideline.create();
+        let nes = ScoringSystemKind::Nes.create();
+
+        assert_eq!(guideline.line_clear(LineClear::new(2, TSpin::Full), 0), 1200);
+        assert_eq!(guideline.line_clear(LineClear::new(0, TSpin::Full), 0), 400);
+        assert_eq!(guideline.line_clear(LineClear::new(1, TSpin::Mini), 1), 400);
+        assert_eq!(nes.line_clear(LineClear::new(2, TSpin::Full), 0), 100);
+    }
+
+    /// The score board should remember the last line clear or T-spin for the UI.
+    #[test]
+    fn score_board_should_remember_the_last_clear() {
+        let mut score_board = ScoreBoard::new(20, 0, ScoringSystemKind::Guideline.create());
+        score_board.update(LineClear::new(0, TSpin::Mini));
+        score_board.update(LineClear::new(0, TSpin::None));
+        let result = score_board.last_clear.unwrap();
+
+        assert_eq!(result.t_spin, TSpin::Mini);
+        assert_eq!(result.lines, 0);
+        assert_eq!(format!("{}", result), "T-SPIN MINI");
+        assert_eq!(format!("{}", LineClear::new(3, TSpin::Full)), "T-SPIN TRIPLE");
+    }
+
+    /// The combo should count line clearing blocks in a row, and go back to zero
+    /// when a block locks without clearing any lines.
+    #[test]
+    fn score_board_should_count_combos() {
+        let mut score_board = ScoreBoard::new(20, 0, ScoringSystemKind::Guideline.create());
+        score_board.update(LineClear::new(1, TSpin::None));
+        score_board.update(LineClear::new(2, TSpin::None));
+        score_board.update(LineClear::new(1, TSpin::None));
+
+        assert_eq!(score_board.combo, 3);
+        // 100 + (300 + 50) + (100 + 100) points at level zero.
+        assert_eq!(score_board.score, 650);
+
+        score_board.update(LineClear::new(0, TSpin::None));
+
+        assert_eq!(score_board.combo, 0);
+    }
+
+    /// Difficult line clears in a row should be back-to-back, even with blocks 
+    /// that clear no lines in between. An easy line clear breaks the chain.
+    #[test]
+    fn score_board_should_track_back_to_back_clears() {
+        let mut score_board = ScoreBoard::new(100, 0, ScoringSystemKind::Guideline.create());
+        score_board.update(LineClear::new(4, TSpin::None));
+        score_board.update(LineClear::new(0, TSpin::None));
+        score_board.update(LineClear::new(2, TSpin::Full));
+
+        assert!(score_board.back_to_back);
+        assert!(score_board.last_clear.unwrap().back_to_back);
+        assert_eq!(score_board.score, 800 + 1800);
+
+        score_board.update(LineClear::new(1, TSpin::None));
+        score_board.update(LineClear::new(4, TSpin::None));
+
+        assert!(!score_board.last_clear.unwrap().back_to_back);
+    }
+
+    /// A perfect clear should earn a bonus on top of the line clear in the 
+    /// guideline scoring system.
+    #[test]
+    fn guideline_scoring_should_award_a_perfect_clear_bonus() {
+        let scoring_system = ScoringSystemKind::Guideline.create();
+        let mut clear = LineClear::new(4, TSpin::None);
+        clear.perfect_clear = true;
+
+        assert_eq!(scoring_system.line_clear(clear, 0), 800 + 2000);
+        assert_eq!(scoring_system.line_clear(clear, 1), 2 * (800 + 2000));
+    }
+
+    /// The statistics should keep the longest combo.
+    #[test]
+    fn statistics_should_keep_the_longest_combo() {
+        let mut statistics = Statistics::new();
+        statistics.update_combo(3);
+        statistics.update_combo(0);
+        statistics.update_combo(2);
+
+        assert_eq!(statistics.max_combo, 3);
+    }
+
+    /// Scoring systems should be selectable by name.
+    #[test]
+    fn scoring_systems_should_be_parsed_from_their_names() {
+        assert_eq!("nes".parse::<ScoringSystemKind>(), Ok(ScoringSystemKind::Nes));
+        assert_eq!("guideline".parse::<ScoringSystemKind>(), Ok(ScoringSystemKind::Guideline));
+        assert!("custom".parse::<ScoringSystemKind>().is_err());
+        assert!("tgm".parse::<ScoringSystemKind>().is_err());
+    }
+
+    /// A custom scoring system should read its point values from a definition 
+    /// file, keeping the guideline values for the fields the file leaves out.
+    #[test]
+    fn custom_scoring_should_be_parsed_from_a_definition_file() {
+        let source = "line_clear_points = [10, 20, 30, 40]\ncombo_points = 0\nlevel_multiplier = false";
+        let scoring_system = CustomScoringSystem::from_toml(source).unwrap();
+        let expected = CustomScoringSystem {
+            line_clear_points: [10, 20, 30, 40],
+            combo_points: 0,
+            level_multiplier: false,
+            ..CustomScoringSystem::default()
+        };
+
+        assert_eq!(scoring_system, expected);
+        assert_eq!(CustomScoringSystem::from_toml(""), Ok(CustomScoringSystem::default()));
+    }
+
+    /// Definition files that do not describe a scoring system should be rejected.
+    #[test]
+    fn invalid_custom_scoring_systems_should_be_rejected() {
+        let invalid_scoring_systems = [
+            "line_clear_points = [10, 20, 30]",
+            "line_clear_points = [10, 20, 30, -40]",
+            "t_spin_mini_points = \"many\"",
+            "combo_points = -1",
+            "level_multiplier = 1",
+            "hold_points = 10",
+            "line_clear_points = [",
+        ];
+        for source in invalid_scoring_systems.iter() {
+            assert!(CustomScoringSystem::from_toml(source).is_err(), "{}", source);
+        }
+    }
+
+    /// The example scoring system should load.
+    #[test]
+    fn the_example_scoring_system_should_load() {
+        let scoring_system = CustomScoringSystem::from_toml(include_str!("../assets/scoring/classic.toml")).unwrap();
+
+        assert!(!scoring_system.level_multiplier);
+    }
+
+    /// The default custom scoring system should score the same as the guideline.
+    #[test]
+    fn the_default_custom_scoring_should_match_the_guideline() {
+        let custom = CustomScoringSystem::default();
+        let guideline = GuidelineScoringSystem::new();
+        let mut clear = LineClear::new(2, TSpin::Full);
+        clear.back_to_back = true;
+        clear.combo = 3;
+        clear.perfect_clear = true;
+
+        assert_eq!(custom.line_clear(clear, 4), guideline.line_clear(clear, 4));
+        assert_eq!(custom.soft_drop(7, 4), guideline.soft_drop(7, 4));
+        assert_eq!(custom.hard_drop(7, 4), guideline.hard_drop(7, 4));
+    }
+}
@@ -0,0 +1,467 @@
+/*
+ *  Googly Blocks is a video game.
+ *  Copyright (C) 2018,2019,2020  Christopher Blanchard
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use crate::auto_shift::{
+    AutoShiftDelay,
+    DEFAULT_AUTO_REPEAT_RATE,
+    DEFAULT_DAS_CUT_DELAY,
+    DEFAULT_DELAYED_AUTO_SHIFT,
+};
+use crate::game_mode::{
+    GameModeKind,
+};
+use crate::gravity::{
+    GravityCurveKind,
+};
+use crate::lock_delay::{
+    LockDelayKind,
+};
+use crate::next_block::{
+    MAX_PREVIEW_COUNT,
+    MIN_PREVIEW_COUNT,
+};
+use crate::playing_field::{
+    DEFAULT_COLUMNS,
+    DEFAULT_ROWS,
+    MAX_COLUMNS,
+    MAX_ROWS,
+    MIN_COLUMNS,
+    MIN_ROWS,
+};
+use crate::randomizer::{
+    RandomizerKind,
+};
+use crate::rotation::{
+    RotationSystemKind,
+};
+use crate::score::{
+    ScoringSystemKind,
+};
+
+
+/// The settings a game of Googly Blocks is played with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameSettings {
+    /// The goal of the game.
+    pub game_mode: GameModeKind,
+    /// The seed for the next block generator. When no seed is given, the game
+    /// chooses one at random.
+    pub seed: Option<u64>,
+    /// The strategy for choosing the next piece.
+    pub randomizer: RandomizerKind,
+    /// The number of upcoming pieces shown in the next piece panel.
+    pub preview_count: usize,
+    /// The rules for rotating and wall kicking pieces.
+    pub rotation_system: RotationSystemKind,
+    /// How fast pieces fall at each level.
+    pub gravity: GravityCurveKind,
+    /// The rules for awarding points.
+    pub scoring_system: ScoringSystemKind,
+    /// The path to the definition file of a custom scoring system. When one is
+    /// given, the game is scored with it instead of the chosen scoring system.
+    pub scoring_file: Option<String>,
+    /// When the lock delay of a resting piece starts over.
+    pub lock_delay: LockDelayKind,
+    /// The number of rows in the playing field.
+    pub rows: usize,
+    /// The number of columns in the playing field.
+    pub columns: usize,
+    /// The path to the definition file of the piece set to play with. When no
+    /// piece set is given, the game is played with the seven tetrominoes.
+    pub piece_set: Option<String>,
+    /// How long left or right is held before the piece starts shifting on its own.
+    pub delayed_auto_shift: AutoShiftDelay,
+    /// The time between shifts once the piece shifts on its own.
+    pub auto_repeat_rate: AutoShiftDelay,
+    /// How long auto shifting pauses after a rotation.
+    pub das_cut_delay: AutoShiftDelay,
+    /// Whether the sonic drop move is enabled.
+    pub sonic_drop: bool,
+    /// Whether to show where the current piece will land.
+    pub ghost_piece: bool,
+}
+
+impl Default for GameSettings {
+    /// The settings the game is played with when no arguments are given.
+    fn default() -> GameSettings {
+        GameSettings {
+            game_mode: GameModeKind::Endless,
+            seed: None,
+            randomizer: RandomizerKind::Classic,
+            preview_count: 3,
+            rotation_system: RotationSystemKind::Classic,
+            gravity: GravityCurveKind::Nes,
+            scoring_system: ScoringSystemKind::Nes,
+            scoring_file: None,
+            lock_delay: LockDelayKind::Classic,
+            rows: DEFAULT_ROWS,
+            columns: DEFAULT_COLUMNS,
+            piece_set: None,
+            delayed_auto_shift: DEFAULT_DELAYED_AUTO_SHIFT,
+            auto_repeat_rate: DEFAULT_AUTO_REPEAT_RATE,
+            das_cut_delay: DEFAULT_DAS_CUT_DELAY,
+            sonic_drop: false,
+            ghost_piece: true,
+        }
+    }
+}
+
+impl GameSettings {
+    /// Construct the default game settings.
+    pub fn new() -> GameSettings {
+        GameSettings::default()
+    }
+
+    /// Parse the game settings from the command line arguments. The arguments
+    /// should not include the program name.
+    pub fn from_args<I>(args: I) -> Result<GameSettings, String>
+        where I: IntoIterator<Item = String>
+    {
+        let mut settings = GameSettings::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, value) = match arg.find('=') {
+                Some(index) => (String::from(&arg[..index]), Some(String::from(&arg[(index + 1)..]))),
+                None => (arg, None),
+            };
+            match flag.as_str() {
+                "--mode" => {
+                    let value = value_of(&flag, value, &mut args)?;
+                    settings.game_mode = value.parse::<GameModeKind>()?;
+                }
+                "--seed" => {
+                    let value = value_of(&flag, value, &mut args)?;
+                    settings.seed = Some(parse_value(&flag, &value)?);
+                }
+                "--randomizer" => {
+                    let value = value_of(&flag, value, &mut args)?;
+                    settings.randomizer = value.parse::<RandomizerKind>()?;
+                }
+                "--preview" => {
+                    let value = value_of(&flag, value, &mut args)?;
+                    let preview_count = parse_value(&flag, &value)?;
+                    if !(MIN_PREVIEW_COUNT..=MAX_PREVIEW_COUNT).contains(&preview_count) {
+                        return Err(format!(
+                            "The preview must show between {} and {} pieces: {}", 
+                            MIN_PREVIEW_COUNT, MAX_PREVIEW_COUNT, preview_count
+                        ));
+                    }
+                    settings.preview_count = preview_count;
+                }
+                "--rotation" => {
+                    let value = value_of(&flag, value, &mut args)?;
+                    settings.rotation_system = value.parse::<RotationSystemKind>()?;
+                }
+                "--gravity" => {
+                    let value = value_of(&flag, value, &mut args)?;
+                    settings.gravity = value.parse::<GravityCurveKind>()?;
+                }
+                "--scoring" => {
+                    let value = value_of(&flag, value, &mut args)?;
+                    settings.scoring_system = value.parse::<ScoringSystemKind>()?;
+                }
+                "--scoring-file" => {
+                    settings.scoring_file = Some(value_of(&flag, value, &mut args)?);
+                }
+                "--lock-delay" => {
+                    let value = value_of(&flag, value, &mut args)?;
+                    settings.lock_delay = value.parse::<LockDelayKind>()?;
+                }
+                "--width" => {
+                    let value = value_of(&flag, value, &mut args)?;
+                    let columns = parse_value(&flag, &value)?;
+                    if !(MIN_COLUMNS..=MAX_COLUMNS).contains(&columns) {
+                        return Err(format!(
+                            "The playing field must be between {} and {} columns wide: {}", 
+                            MIN_COLUMNS, MAX_COLUMNS, columns
+                        ));
+                    }
+                    settings.columns = columns;
+                }
+                "--height" => {
+                    let value = value_of(&flag, value, &mut args)?;
+                    let rows = parse_value(&flag, &value)?;
+                    if !(MIN_ROWS..=MAX_ROWS).contains(&rows) {
+                        return Err(format!(
+                            "The playing field must be between {} and {} rows tall: {}", 
+                            MIN_ROWS, MAX_ROWS, rows
+                        ));
+                    }
+                    settings.rows = rows;
+                }
+                "--pieces" => {
+                    settings.piece_set = Some(value_of(&flag, value, &mut args)?);
+                }
+                "--das" => {
+                    let value = value_of(&flag, value, &mut args)?;
+                    settings.delayed_auto_shift = value.parse::<AutoShiftDelay>()?;
+                }
+                "--arr" => {
+                    let value = value_of(&flag, value, &mut args)?;
+                    settings.auto_repeat_rate = value.parse::<AutoShiftDelay>()?;
+                }
+                "--das-cut" => {
+                    let value = value_of(&flag, value, &mut args)?;
+                    settings.das_cut_delay = value.parse::<AutoShiftDelay>()?;
+                }
+                "--no-ghost" => {
+                    settings.ghost_piece = false;
+                }
+                "--sonic-drop" => {
+                    settings.sonic_drop = match value {
+                        Some(value) => parse_value(&flag, &value)?,
+                        None => true,
+                    };
+                }
+                _ => {
+                    return Err(format!("Unknown argument: {}", flag));
+                }
+            }
+        }
+
+        Ok(settings)
+    }
+}
+
+/// Get the value of a command line flag, either from the same argument or from
+/// the next one.
+fn value_of<I>(flag: &str, value: Option<String>, args: &mut I) -> Result<String, String>
+    where I: Iterator<Item = String>
+{
+    match value {
+        Some(value) => Ok(value),
+        None => args.next().ok_or(format!("Missing value for argument: {}", flag)),
+    }
+}
+
+/// Parse the value of a command line flag.
+fn parse_value<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value.parse::<T>().map_err(|_| format!("Invalid value for argument {}: {}", flag, value))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        GameSettings,
+    };
+    use crate::auto_shift::{
+        AutoShiftDelay,
+    };
+    use crate::game_mode::{
+        GameModeKind,
+    };
+    use crate::gravity::{
+        GravityCurveKind,
+    };
+    use crate::lock_delay::{
+        LockDelayKind,
+    };
+    use crate::randomizer::{
+        RandomizerKind,
+    };
+    use crate::rotation::{
+        RotationSystemKind,
+    };
+    use crate::score::{
+        ScoringSystemKind,
+    };
+
+
+    fn args(st: &[&str]) -> Vec<String> {
+        st.iter().map(|arg| String::from(*arg)).collect()
+    }
+
+
+    /// Parsing no arguments should yield the default settings.
+    #[test]
+    fn no_arguments_should_yield_the_default_settings() {
+        let expected = GameSettings::new();
+        let result = GameSettings::from_args(args(&[])).unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    /// The game mode should be selectable by name.
+    #[test]
+    fn the_game_mode_should_be_parsed_from_its_name() {
+        let result = GameSettings::from_args(args(&["--mode", "sprint"])).unwrap();
+
+        assert_eq!(GameSettings::new().game_mode, GameModeKind::Endless);
+        assert_eq!(result.game_mode, GameModeKind::Sprint);
+        assert!(GameSettings::from_args(args(&["--mode", "zen"])).is_err());
+    }
+
+    /// The seed can be passed either as a separate argument or after an equals sign.
+    #[test]
+    fn the_seed_should_be_parsed_in_either_form() {
+        let result1 = GameSettings::from_args(args(&["--seed", "42"])).unwrap();
+        let result2 = GameSettings::from_args(args(&["--seed=42"])).unwrap();
+
+        assert_eq!(result1.seed, Some(42));
+        assert_eq!(result2.seed, Some(42));
+    }
+
+    /// A seed that is not a number should be rejected.
+    #[test]
+    fn an_invalid_seed_should_be_rejected() {
+        assert!(GameSettings::from_args(args(&["--seed", "forty-two"])).is_err());
+        assert!(GameSettings::from_args(args(&["--seed"])).is_err());
+    }
+
+    /// The randomizer should be selectable by name.
+    #[test]
+    fn the_randomizer_should_be_parsed_from_its_name() {
+        let result = GameSettings::from_args(args(&["--randomizer", "bag", "--seed", "7"])).unwrap();
+
+        assert_eq!(result.randomizer, RandomizerKind::SevenBag);
+        assert_eq!(result.seed, Some(7));
+        assert!(GameSettings::from_args(args(&["--randomizer", "uniform"])).is_err());
+    }
+
+    /// The preview length should be parsed, and lengths outside the supported
+    /// range should be rejected.
+    #[test]
+    fn the_preview_count_should_be_parsed_and_range_checked() {
+        let result = GameSettings::from_args(args(&["--preview", "5"])).unwrap();
+
+        assert_eq!(result.preview_count, 5);
+        assert!(GameSettings::from_args(args(&["--preview", "0"])).is_err());
+        assert!(GameSettings::from_args(args(&["--preview", "7"])).is_err());
+    }
+
+    /// The rotation system should be selectable by name.
+    #[test]
+    fn the_rotation_system_should_be_parsed_from_its_name() {
+        let result = GameSettings::from_args(args(&["--rotation=srs"])).unwrap();
+
+        assert_eq!(GameSettings::new().rotation_system, RotationSystemKind::Classic);
+        assert_eq!(result.rotation_system, RotationSystemKind::Srs);
+        assert!(GameSettings::from_args(args(&["--rotation", "ars"])).is_err());
+    }
+
+    /// The gravity curve should be selectable by name.
+    #[test]
+    fn the_gravity_curve_should_be_parsed_from_its_name() {
+        let result = GameSettings::from_args(args(&["--gravity", "guideline"])).unwrap();
+
+        assert_eq!(GameSettings::new().gravity, GravityCurveKind::Nes);
+        assert_eq!(result.gravity, GravityCurveKind::Guideline);
+        assert!(GameSettings::from_args(args(&["--gravity", "fast"])).is_err());
+    }
+
+    /// The scoring system should be selectable by name.
+    #[test]
+    fn the_scoring_system_should_be_parsed_from_its_name() {
+        let result = GameSettings::from_args(args(&["--scoring", "guideline"])).unwrap();
+
+        assert_eq!(GameSettings::new().scoring_system, ScoringSystemKind::Nes);
+        assert_eq!(result.scoring_system, ScoringSystemKind::Guideline);
+        assert!(GameSettings::from_args(args(&["--scoring", "tgm"])).is_err());
+    }
+
+    /// The lock delay policy should be selectable by name.
+    #[test]
+    fn the_lock_delay_should_be_parsed_from_its_name() {
+        let result = GameSettings::from_args(args(&["--lock-delay", "move"])).unwrap();
+
+        assert_eq!(GameSettings::new().lock_delay, LockDelayKind::Classic);
+        assert_eq!(result.lock_delay, LockDelayKind::MoveReset);
+        assert!(GameSettings::from_args(args(&["--lock-delay", "infinite"])).is_err());
+    }
+
+    /// The sonic drop switch may be given on its own or with an explicit value.
+    #[test]
+    fn the_sonic_drop_switch_should_be_parsed() {
+        let result1 = GameSettings::from_args(args(&["--sonic-drop"])).unwrap();
+        let result2 = GameSettings::from_args(args(&["--sonic-drop=false"])).unwrap();
+
+        assert!(result1.sonic_drop);
+        assert!(!result2.sonic_drop);
+    }
+
+    /// The ghost piece should be shown unless it is switched off.
+    #[test]
+    fn the_ghost_piece_should_be_switched_off_on_request() {
+        let result = GameSettings::from_args(args(&["--no-ghost"])).unwrap();
+
+        assert!(GameSettings::new().ghost_piece);
+        assert!(!result.ghost_piece);
+    }
+
+    /// An unknown argument should be rejected.
+    #[test]
+    fn an_unknown_argument_should_be_rejected() {
+        assert!(GameSettings::from_args(args(&["--speed", "42"])).is_err());
+    }
+
+    /// The playing field dimensions should be parsed and range checked.
+    #[test]
+    fn the_playing_field_size_should_be_parsed_and_range_checked() {
+        let result = GameSettings::from_args(args(&["--width", "4", "--height=40"])).unwrap();
+
+        assert_eq!(GameSettings::new().columns, 10);
+        assert_eq!(GameSettings::new().rows, 20);
+        assert_eq!(result.columns, 4);
+        assert_eq!(result.rows, 40);
+        assert!(GameSettings::from_args(args(&["--width", "3"])).is_err());
+        assert!(GameSettings::from_args(args(&["--height", "41"])).is_err());
+    }
+
+    /// A piece set definition file should be parsed.
+    #[test]
+    fn a_piece_set_file_should_be_parsed() {
+        let result = GameSettings::from_args(args(&["--pieces", "assets/pieces/trominoes.toml"])).unwrap();
+
+        assert_eq!(GameSettings::new().piece_set, None);
+        assert_eq!(result.piece_set, Some(String::from("assets/pieces/trominoes.toml")));
+        assert!(GameSettings::from_args(args(&["--pieces"])).is_err());
+    }
+
+    /// A custom scoring system file should be parsed as a path, and the scoring 
+    /// system should not be selectable as custom without one.
+    #[test]
+    fn a_scoring_system_file_should_be_parsed() {
+        let result = GameSettings::from_args(args(&["--scoring-file", "assets/scoring/classic.toml"])).unwrap();
+
+        assert_eq!(GameSettings::new().scoring_file, None);
+        assert_eq!(result.scoring_file, Some(String::from("assets/scoring/classic.toml")));
+        assert!(GameSettings::from_args(args(&["--scoring-file"])).is_err());
+        assert!(GameSettings::from_args(args(&["--scoring", "custom"])).is_err());
+    }
+
+    /// The auto shift delays should be parsed in milliseconds or in frames.
+    #[test]
+    fn the_auto_shift_delays_should_be_parsed() {
+        let result = GameSettings::from_args(args(&["--das", "8f", "--arr=0", "--das-cut", "17ms"])).unwrap();
+
+        assert_eq!(GameSettings::new().delayed_auto_shift, AutoShiftDelay::Frames(10));
+        assert_eq!(result.delayed_auto_shift, AutoShiftDelay::Frames(8));
+        assert_eq!(result.auto_repeat_rate, AutoShiftDelay::Milliseconds(0));
+        assert_eq!(result.das_cut_delay, AutoShiftDelay::Milliseconds(17));
+        assert!(GameSettings::from_args(args(&["--das", "fast"])).is_err());
+    }
+
+    /// Auto shift delays longer than the longest allowed delay should be rejected.
+    #[test]
+    fn auto_shift_delays_that_are_too_long_should_be_rejected() {
+        assert!(GameSettings::from_args(args(&["--das", "20000000000000000f"])).is_err());
+        assert!(GameSettings::from_args(args(&["--arr", "5001ms"])).is_err());
+        assert!(GameSettings::from_args(args(&["--das-cut", "301f"])).is_err());
+        assert!(GameSettings::from_args(args(&["--das", "5000", "--arr", "300f"])).is_ok());
+    }
+}
@@ -0,0 +1,644 @@
+/*
+ *  Googly Blocks is a video game.
+ *  Copyright (C) 2018,2019,2020  Christopher Blanchard
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use crate::auto_shift::{
+    AutoShiftSpec,
+    DEFAULT_AUTO_REPEAT_RATE,
+    DEFAULT_DAS_CUT_DELAY,
+    DEFAULT_DELAYED_AUTO_SHIFT,
+};
+use crate::block::{
+    GooglyBlockPiece,
+};
+use crate::flashing_state_machine;
+use crate::flashing_state_machine::{
+    FlashAnimationStateMachine,
+    FlashAnimationStateMachineSpec,
+};
+use crate::game_mode::{
+    GameMode,
+    GameModeKind,
+    GameModeOutcome,
+};
+use crate::garbage::{
+    GarbageGen,
+};
+use crate::gravity::{
+    GravityCurve,
+};
+use crate::input::{
+    Input,
+};
+use crate::lock_delay::{
+    LockDelayKind,
+};
+use crate::next_block::{
+    NextBlockCell,
+};
+use crate::piece_set::{
+    PieceSet,
+};
+use crate::playing_field;
+use crate::playing_field::{
+    BlockPosition,
+    PlayingFieldContext,
+    PlayingFieldContextSpec,
+    TopOut,
+    DEFAULT_COLUMNS,
+    DEFAULT_ROWS,
+};
+use crate::randomizer::{
+    RandomizerKind,
+};
+use crate::rotation::{
+    RotationSystemKind,
+};
+use crate::playing_field_state_machine;
+use crate::playing_field_state_machine::{
+    PlayingFieldStateMachine,
+    PlayingFieldStateMachineSpec,
+    PlayingFieldTimerSpec,
+};
+use crate::score::{
+    ScoreBoard,
+    ScoringSystemKind,
+    Statistics,
+};
+use crate::timer::{
+    Interval,
+};
+
+use std::cell::{
+    Ref,
+    RefCell,
+};
+use std::collections::hash_map::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
+
+
+/// A specification type describing the parameters for constructing a
+/// headless game simulation.
+#[derive(Clone)]
+pub struct SimulationSpec {
+    pub game_mode: GameModeKind,
+    pub timers: PlayingFieldTimerSpec,
+    pub flash_timers: FlashAnimationStateMachineSpec,
+    pub perfect_clear_flash_timers: FlashAnimationStateMachineSpec,
+    /// The pieces the game is played with.
+    pub piece_set: Rc<PieceSet>,
+    pub starting_positions: HashMap<GooglyBlockPiece, BlockPosition>,
+    /// The number of rows in the playing field.
+    pub rows: usize,
+    /// The number of columns in the playing field.
+    pub columns: usize,
+    pub lines_per_level: usize,
+    /// The level the game starts at.
+    pub starting_level: usize,
+    pub seed: Option<u64>,
+    pub randomizer: RandomizerKind,
+    pub preview_count: usize,
+    pub rotation_system: RotationSystemKind,
+    pub scoring_system: ScoringSystemKind,
+}
+
+impl Default for SimulationSpec {
+    /// The same parameters the game uses.
+    fn default() -> SimulationSpec {
+        let piece_set = Rc::new(PieceSet::standard());
+        let starting_positions = playing_field::starting_positions(&piece_set, DEFAULT_COLUMNS);
+        let timers = PlayingFieldTimerSpec {
+            gravity: GravityCurve::nes(),
+            lock_delay_interval: Interval::Milliseconds(500),
+            lock_delay: LockDelayKind::Classic,
+            auto_shift: AutoShiftSpec::from_delays(
+                DEFAULT_DELAYED_AUTO_SHIFT, DEFAULT_AUTO_REPEAT_RATE, DEFAULT_DAS_CUT_DELAY
+            ),
+            down_hold_interval: Interval::Milliseconds(35),
+            rotate_interval: Interval::Milliseconds(100),
+            clearing_interval: Interval::Milliseconds(60),
+        };
+        let flash_timers = FlashAnimationStateMachineSpec {
+            flash_switch_interval: Interval::Milliseconds(50),
+            flash_stop_interval: Interval::Milliseconds(500),
+        };
+        let perfect_clear_flash_timers = FlashAnimationStateMachineSpec {
+            flash_switch_interval: Interval::Milliseconds(100),
+            flash_stop_interval: Interval::Milliseconds(1500),
+        };
+
+        SimulationSpec {
+            game_mode: GameModeKind::Endless,
+            timers: timers,
+            flash_timers: flash_timers,
+            perfect_clear_flash_timers: perfect_clear_flash_timers,
+            piece_set: piece_set,
+            starting_positions: starting_positions,
+            rows: DEFAULT_ROWS,
+            columns: DEFAULT_COLUMNS,
+            lines_per_level: 20,
+            starting_level: 0,
+            seed: None,
+            randomizer: RandomizerKind::Classic,
+            preview_count: 3,
+            rotation_system: RotationSystemKind::Classic,
+            scoring_system: ScoringSystemKind::Nes,
+        }
+    }
+}
+
+/// A factory method for creating a simulation from a specification.
+pub fn create(spec: SimulationSpec) -> Simulation {
+    let pieces = spec.piece_set.pieces();
+    let mut next_block_cell = match spec.seed {
+        Some(seed) => NextBlockCell::from_seed(seed, spec.randomizer, &pieces, spec.preview_count),
+        None => NextBlockCell::new(spec.randomizer, &pieces, spec.preview_count),
+    };
+    let starting_block = next_block_cell.current_block();
+    next_block_cell.update();
+    let mut garbage_gen = match spec.seed {
+        Some(seed) => GarbageGen::from_seed(seed, spec.columns),
+        None => GarbageGen::new(spec.columns),
+    };
+    let game_mode = spec.game_mode.create();
+    let playing_field_context_spec = PlayingFieldContextSpec {
+        starting_block: starting_block,
+        starting_positions: spec.starting_positions,
+        piece_set: spec.piece_set.clone(),
+        rotation_system: spec.rotation_system,
+        rows: spec.rows,
+        columns: spec.columns,
+    };
+    let mut playing_field_context = PlayingFieldContext::new(playing_field_context_spec);
+    playing_field_context.landed_blocks.push_garbage_rows(&garbage_gen.holes(game_mode.starting_garbage()));
+    let playing_field_context = Rc::new(RefCell::new(playing_field_context));
+    let next_block = Rc::new(RefCell::new(next_block_cell));
+    let statistics = Rc::new(RefCell::new(Statistics::new()));
+    let score_board = Rc::new(RefCell::new(ScoreBoard::new(spec.lines_per_level, spec.starting_level, spec.scoring_system.create())));
+    let flashing_state_machine = Rc::new(RefCell::new(
+        flashing_state_machine::create(spec.flash_timers)
+    ));
+    let perfect_clear_flashing_state_machine = Rc::new(RefCell::new(
+        flashing_state_machine::create(spec.perfect_clear_flash_timers)
+    ));
+    let playing_field_state_machine_spec = PlayingFieldStateMachineSpec {
+        timers: spec.timers,
+        flashing_state_machine: flashing_state_machine.clone(),
+        perfect_clear_flashing_state_machine: perfect_clear_flashing_state_machine.clone(),
+        playing_field_context: playing_field_context.clone(),
+        next_block: next_block.clone(),
+        statistics: statistics.clone(),
+        score_board: score_board.clone(),
+    };
+    let playing_field_state_machine = playing_field_state_machine::create(playing_field_state_machine_spec);
+
+    Simulation {
+        game_mode: game_mode,
+        garbage_gen: garbage_gen,
+        playing_field_state: playing_field_context,
+        playing_field_state_machine: playing_field_state_machine,
+        next_block: next_block,
+        statistics: statistics,
+        score_board: score_board,
+        flashing_state_machine: flashing_state_machine,
+        perfect_clear_flashing_state_machine: perfect_clear_flashing_state_machine,
+        elapsed: Duration::from_millis(0),
+    }
+}
+
+/// A game of Googly Blocks that runs without a window. The simulation advances
+/// in discrete ticks, each of which carries the time elapsed since the previous
+/// tick along with the player's inputs during that tick.
+pub struct Simulation {
+    game_mode: Box<dyn GameMode>,
+    garbage_gen: GarbageGen,
+    playing_field_state: Rc<RefCell<PlayingFieldContext>>,
+    playing_field_state_machine: PlayingFieldStateMachine,
+    next_block: Rc<RefCell<NextBlockCell>>,
+    statistics: Rc<RefCell<Statistics>>,
+    score_board: Rc<RefCell<ScoreBoard>>,
+    flashing_state_machine: Rc<RefCell<FlashAnimationStateMachine>>,
+    perfect_clear_flashing_state_machine: Rc<RefCell<FlashAnimationStateMachine>>,
+    elapsed: Duration,
+}
+
+impl Simulation {
+    /// Advance the simulation by one tick. The inputs are handled in order
+    /// before the playing field is updated, the same way the game loop does it.
+    pub fn step(&mut self, elapsed_milliseconds: Duration, inputs: Vec<Input>) {
+        for input in inputs.into_iter() {
+            self.playing_field_state_machine.handle_input(input, elapsed_milliseconds);
+        }
+        self.playing_field_state_machine.update(elapsed_milliseconds);
+        let top_out = self.playing_field_state_machine.top_out();
+        self.game_mode.update(
+            elapsed_milliseconds, &self.score_board.borrow(), &self.playing_field_state.borrow().landed_blocks, top_out
+        );
+        let garbage = self.game_mode.take_garbage();
+        if garbage > 0 {
+            self.playing_field_state_machine.add_garbage(&self.garbage_gen.holes(garbage));
+        }
+        self.elapsed += elapsed_milliseconds;
+    }
+
+    /// Run the simulation over a sequence of ticks. The simulation stops early
+    /// once the game is over. Returns the number of ticks taken.
+    pub fn run<I>(&mut self, ticks: I) -> usize
+        where I: IntoIterator<Item = (Duration, Vec<Input>)>
+    {
+        let mut steps = 0;
+        for (elapsed_milliseconds, inputs) in ticks.into_iter() {
+            if self.is_game_over() {
+                break;
+            }
+            self.step(elapsed_milliseconds, inputs);
+            steps += 1;
+        }
+
+        steps
+    }
+
+    /// The current state of the playing field.
+    pub fn playing_field_context(&self) -> Ref<'_, PlayingFieldContext> {
+        self.playing_field_state.borrow()
+    }
+
+    /// The next block cell.
+    pub fn next_block(&self) -> Ref<'_, NextBlockCell> {
+        self.next_block.borrow()
+    }
+
+    /// The score board.
+    pub fn score_board(&self) -> Ref<'_, ScoreBoard> {
+        self.score_board.borrow()
+    }
+
+    /// The player's score.
+    pub fn score(&self) -> usize {
+        self.score_board.borrow().score
+    }
+
+    /// The piece statistics.
+    pub fn statistics(&self) -> Ref<'_, Statistics> {
+        self.statistics.borrow()
+    }
+
+    /// The background flash animation state machine.
+    pub fn flashing_state_machine(&self) -> Ref<'_, FlashAnimationStateMachine> {
+        self.flashing_state_machine.borrow()
+    }
+
+    /// The perfect clear animation state machine.
+    pub fn perfect_clear_flashing_state_machine(&self) -> Ref<'_, FlashAnimationStateMachine> {
+        self.perfect_clear_flashing_state_machine.borrow()
+    }
+
+    /// The game mode.
+    pub fn game_mode(&self) -> &dyn GameMode {
+        &*self.game_mode
+    }
+
+    /// Determine whether the game is over, either because the player topped out
+    /// or because the game mode ended.
+    pub fn is_game_over(&self) -> bool {
+        self.playing_field_state_machine.is_game_over() || self.game_mode.outcome().is_some()
+    }
+
+    /// How the game mode ended, if it is over.
+    pub fn outcome(&self) -> Option<GameModeOutcome> {
+        self.game_mode.outcome()
+    }
+
+    /// How the game ended, if it is over.
+    pub fn top_out(&self) -> Option<TopOut> {
+        self.playing_field_state_machine.top_out()
+    }
+
+    /// The total time simulated so far.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        SimulationSpec,
+    };
+    use crate::game_mode::{
+        GameModeKind,
+        GameModeOutcome,
+        CHEESE_RACE_GARBAGE_ROWS,
+        ULTRA_TIME_LIMIT,
+    };
+    use crate::input::{
+        Input,
+        InputAction,
+        InputKind,
+    };
+    use crate::piece_set::{
+        PieceSet,
+    };
+    use crate::playing_field;
+    use crate::playing_field::{
+        BlockPosition,
+    };
+    use crate::timer::{
+        Interval,
+    };
+    use std::iter;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+
+    const FRAME: Duration = Duration::from_millis(16);
+
+    fn idle_ticks(count: usize) -> impl Iterator<Item = (Duration, Vec<Input>)> {
+        iter::repeat_with(|| (FRAME, vec![])).take(count)
+    }
+
+    fn statistics_total(simulation: &super::Simulation) -> usize {
+        let statistics = simulation.statistics();
+        statistics.t_pieces + statistics.j_pieces + statistics.z_pieces + statistics.o_pieces +
+            statistics.s_pieces + statistics.l_pieces + statistics.i_pieces + statistics.custom_pieces
+    }
+
+
+    /// A new simulation should start with an empty score and should not be over.
+    #[test]
+    fn a_new_simulation_should_not_be_over() {
+        let simulation = super::create(SimulationSpec::default());
+
+        assert!(!simulation.is_game_over());
+        assert_eq!(simulation.score(), 0);
+        assert_eq!(statistics_total(&simulation), 0);
+    }
+
+    /// The current block should fall one row every fall interval when there is no input.
+    #[test]
+    fn the_current_block_should_fall_without_input() {
+        let spec = SimulationSpec::default();
+        let fall_interval = match spec.timers.gravity.fall_interval(0) {
+            Interval::Milliseconds(millis) => Duration::from_millis(millis),
+        };
+        let mut simulation = super::create(spec);
+        let start = simulation.playing_field_context().current_position;
+        simulation.run(iter::once((fall_interval, vec![])));
+        let expected = BlockPosition::new(start.row + 1, start.column);
+        let result = simulation.playing_field_context().current_position;
+
+        assert_eq!(result, expected);
+    }
+
+    /// A game started at a higher level should fall at the speed of that level.
+    #[test]
+    fn a_game_started_at_a_higher_level_should_fall_faster() {
+        let spec = SimulationSpec { starting_level: 9, ..SimulationSpec::default() };
+        let fall_interval = match spec.timers.gravity.fall_interval(9) {
+            Interval::Milliseconds(millis) => Duration::from_millis(millis),
+        };
+        let mut simulation = super::create(spec);
+        let start = simulation.playing_field_context().current_position;
+        simulation.run(iter::repeat_with(|| (fall_interval, vec![])).take(3));
+        let expected = BlockPosition::new(start.row + 3, start.column);
+        let result = simulation.playing_field_context().current_position;
+
+        assert_eq!(simulation.score_board().level, 9);
+        assert_eq!(result, expected);
+    }
+
+    /// Holding down should move the current block down faster than gravity does.
+    #[test]
+    fn holding_down_should_move_the_current_block_faster_than_falling() {
+        let mut simulation = super::create(SimulationSpec::default());
+        let start = simulation.playing_field_context().current_position;
+        let down = Input::new(InputKind::Down, InputAction::Repeat);
+        let ticks = iter::repeat_with(|| (FRAME, vec![down])).take(20);
+        simulation.run(ticks);
+        let result = simulation.playing_field_context().current_position;
+
+        assert!(result.row >= start.row + 5);
+    }
+
+    /// A block left alone should eventually land and be counted in the statistics.
+    #[test]
+    fn a_falling_block_should_eventually_land() {
+        let mut simulation = super::create(SimulationSpec::default());
+        // Twenty three rows at 799 milliseconds per row, plus the collision interval.
+        simulation.run(idle_ticks(1500));
+
+        assert!(statistics_total(&simulation) >= 1);
+    }
+
+    /// Stacking blocks in the middle of the playing field without ever moving them
+    /// should eventually end the game.
+    #[test]
+    fn stacking_blocks_without_input_should_end_the_game() {
+        let mut simulation = super::create(SimulationSpec::default());
+        let down = Input::new(InputKind::Down, InputAction::Repeat);
+        let ticks = iter::repeat_with(|| (FRAME, vec![down])).take(100_000);
+        let steps = simulation.run(ticks);
+
+        assert!(simulation.is_game_over());
+        assert!(simulation.top_out().is_some());
+        assert!(steps < 100_000);
+        assert_eq!(simulation.score_board().lines, 0);
+    }
+
+    /// Two simulations with the same seed and the same inputs should play out 
+    /// the same way.
+    #[test]
+    fn simulations_with_the_same_seed_should_be_deterministic() {
+        let spec = SimulationSpec { seed: Some(2020), ..SimulationSpec::default() };
+        let mut simulation1 = super::create(spec.clone());
+        let mut simulation2 = super::create(spec);
+        let down = Input::new(InputKind::Down, InputAction::Repeat);
+        let steps1 = simulation1.run(iter::repeat_with(|| (FRAME, vec![down])).take(100_000));
+        let steps2 = simulation2.run(iter::repeat_with(|| (FRAME, vec![down])).take(100_000));
+
+        assert_eq!(steps1, steps2);
+        assert_eq!(
+            format!("{}", simulation1.playing_field_context().landed_blocks),
+            format!("{}", simulation2.playing_field_context().landed_blocks)
+        );
+    }
+
+    /// A simulation that is over should ignore any further ticks.
+    #[test]
+    fn a_simulation_that_is_over_should_not_advance() {
+        let mut simulation = super::create(SimulationSpec::default());
+        let down = Input::new(InputKind::Down, InputAction::Repeat);
+        let ticks = iter::repeat_with(|| (FRAME, vec![down])).take(100_000);
+        simulation.run(ticks);
+        let elapsed = simulation.elapsed();
+        let steps = simulation.run(idle_ticks(10));
+
+        assert_eq!(steps, 0);
+        assert_eq!(simulation.elapsed(), elapsed);
+    }
+
+    /// Holding for the first time should put the current block aside and bring
+    /// in the next block. Holding again before the block lands should do nothing.
+    #[test]
+    fn holding_should_put_the_current_block_aside_once_per_block() {
+        let mut simulation = super::create(SimulationSpec { seed: Some(7), ..SimulationSpec::default() });
+        let current_block = simulation.playing_field_context().current_block;
+        let next_block = simulation.next_block().current_block();
+        let after_next_block = simulation.next_block().peek(1).unwrap();
+        let hold = Input::new(InputKind::Hold, InputAction::Press);
+        simulation.step(FRAME, vec![hold]);
+
+        assert_eq!(simulation.playing_field_context().held_block, Some(current_block));
+        assert_eq!(simulation.playing_field_context().current_block, next_block);
+        assert_eq!(simulation.next_block().current_block(), after_next_block);
+
+        simulation.step(FRAME, vec![hold]);
+
+        assert_eq!(simulation.playing_field_context().held_block, Some(current_block));
+        assert_eq!(simulation.playing_field_context().current_block, next_block);
+    }
+
+    /// A hard drop should lock the block right away and award points for the rows
+    /// it dropped.
+    #[test]
+    fn hard_dropping_should_lock_the_block_immediately() {
+        let mut simulation = super::create(SimulationSpec::default());
+        let rows = simulation.playing_field_context().drop_distance() as usize;
+        let hard_drop = Input::new(InputKind::HardDrop, InputAction::Press);
+        simulation.step(FRAME, vec![hard_drop]);
+
+        assert_eq!(statistics_total(&simulation), 1);
+        assert_eq!(simulation.score(), 2 * rows);
+    }
+
+    /// Soft dropping should award the points of the scoring system for every row
+    /// the block moves down.
+    #[test]
+    fn soft_dropping_should_award_points_per_row() {
+        let mut simulation = super::create(SimulationSpec::default());
+        let start = simulation.playing_field_context().current_position;
+        let down = Input::new(InputKind::Down, InputAction::Repeat);
+        simulation.run(iter::repeat_with(|| (FRAME, vec![down])).take(20));
+        let rows = simulation.playing_field_context().current_position.row - start.row;
+
+        assert!(rows > 0);
+        assert_eq!(simulation.score(), rows as usize);
+    }
+
+    /// A sonic drop should move the block to the bottom without locking it.
+    #[test]
+    fn sonic_dropping_should_not_lock_the_block() {
+        let mut simulation = super::create(SimulationSpec::default());
+        let sonic_drop = Input::new(InputKind::SonicDrop, InputAction::Press);
+        simulation.step(FRAME, vec![sonic_drop]);
+
+        assert_eq!(simulation.playing_field_context().drop_distance(), 0);
+        assert_eq!(statistics_total(&simulation), 0);
+        assert_eq!(simulation.score(), 0);
+    }
+
+    /// Narrow and tall playing fields should play out until the game ends.
+    #[test]
+    fn playing_fields_of_other_sizes_should_play_until_the_game_ends() {
+        for (rows, columns) in [(20, 4), (40, 10), (10, 20)].iter() {
+            let spec = SimulationSpec {
+                seed: Some(11),
+                rows: *rows,
+                columns: *columns,
+                starting_positions: playing_field::starting_positions(&PieceSet::standard(), *columns),
+                ..SimulationSpec::default()
+            };
+            let mut simulation = super::create(spec);
+            let hard_drop = Input::new(InputKind::HardDrop, InputAction::Press);
+            simulation.run(iter::repeat_with(|| (FRAME, vec![hard_drop])).take(100_000));
+
+            assert!(simulation.is_game_over());
+            assert_eq!(simulation.playing_field_context().landed_blocks.rows(), *rows);
+            assert_eq!(simulation.playing_field_context().landed_blocks.columns(), *columns);
+        }
+    }
+
+    /// A game played with a custom piece set should deal only those pieces and
+    /// play out until the game ends.
+    #[test]
+    fn a_game_with_a_custom_piece_set_should_play_until_the_game_ends() {
+        let piece_set = Rc::new(PieceSet::from_toml(include_str!("../assets/pieces/trominoes.toml")).unwrap());
+        let spec = SimulationSpec {
+            seed: Some(11),
+            starting_positions: playing_field::starting_positions(&piece_set, 10),
+            piece_set: piece_set.clone(),
+            ..SimulationSpec::default()
+        };
+        let mut simulation = super::create(spec);
+        let hard_drop = Input::new(InputKind::HardDrop, InputAction::Press);
+        simulation.run(iter::repeat_with(|| (FRAME, vec![hard_drop])).take(100_000));
+
+        assert!(simulation.is_game_over());
+        assert!(statistics_total(&simulation) > 0);
+        assert_eq!(statistics_total(&simulation), simulation.statistics().custom_pieces);
+    }
+
+    /// An ultra game the player survives should end in victory when the time runs out.
+    #[test]
+    fn an_ultra_game_should_end_in_victory_when_the_time_runs_out() {
+        let spec = SimulationSpec { game_mode: GameModeKind::Ultra, ..SimulationSpec::default() };
+        let mut simulation = super::create(spec);
+        simulation.step(ULTRA_TIME_LIMIT - FRAME, vec![]);
+        assert!(!simulation.is_game_over());
+
+        let steps = simulation.run(idle_ticks(10));
+
+        assert_eq!(steps, 1);
+        assert!(simulation.is_game_over());
+        assert_eq!(simulation.outcome(), Some(GameModeOutcome::Victory));
+        assert_eq!(simulation.top_out(), None);
+    }
+
+    /// Topping out before clearing the sprint lines should end the sprint in defeat.
+    #[test]
+    fn topping_out_should_end_a_sprint_in_defeat() {
+        let spec = SimulationSpec { game_mode: GameModeKind::Sprint, ..SimulationSpec::default() };
+        let mut simulation = super::create(spec);
+        let hard_drop = Input::new(InputKind::HardDrop, InputAction::Press);
+        simulation.run(iter::repeat_with(|| (FRAME, vec![hard_drop])).take(100_000));
+
+        assert!(simulation.top_out().is_some());
+        assert_eq!(simulation.outcome(), Some(GameModeOutcome::Defeat));
+    }
+
+    /// A cheese race should start with the garbage rows already in the playing field.
+    #[test]
+    fn a_cheese_race_should_start_with_garbage() {
+        let spec = SimulationSpec { game_mode: GameModeKind::CheeseRace, seed: Some(5), ..SimulationSpec::default() };
+        let simulation = super::create(spec);
+
+        assert_eq!(simulation.playing_field_context().landed_blocks.garbage_rows(), CHEESE_RACE_GARBAGE_ROWS);
+        assert!(!simulation.is_game_over());
+    }
+
+    /// Garbage should rise into the playing field on a timer in a survival game.
+    #[test]
+    fn garbage_should_rise_during_a_survival_game() {
+        let spec = SimulationSpec { game_mode: GameModeKind::Survival, ..SimulationSpec::default() };
+        let mut simulation = super::create(spec);
+        simulation.step(Duration::from_millis(8000), vec![]);
+        simulation.step(FRAME, vec![]);
+
+        assert_eq!(simulation.playing_field_context().landed_blocks.garbage_rows(), 1);
+    }
+}
@@ -49,6 +49,15 @@ impl Timer {
         }
     }
 
+    /// Change the period between events without resetting the timer.
+    #[inline]
+    pub fn set_interval(&mut self, interval: Interval) {
+        self.event_interval = match interval {
+            Interval::Milliseconds(millis) => Duration::from_millis(millis)
+        };
+        self.event_count = self.time.as_millis() / self.event_interval.as_millis();
+    }
+
     /// Update the state of the timer.
     #[inline]
     pub fn update(&mut self, elapsed: Duration) {
@@ -62,6 +71,12 @@ impl Timer {
         self.event_count > 0
     }
 
+    /// The number of events triggered since the timer was reset.
+    #[inline]
+    pub fn event_count(&self) -> u128 {
+        self.event_count
+    }
+
     /// Reset the timer state.
     #[inline]
     pub fn reset(&mut self) {
@@ -98,4 +113,14 @@ mod tests {
 
         assert_eq!(timer.event_count, 10);
     }
+
+    #[test]
+    fn timer_should_count_events_with_a_new_interval() {
+        let mut timer = Timer::new(Interval::Milliseconds(500));
+        timer.update(Duration::from_millis(300));
+        assert!(!timer.event_triggered());
+        timer.set_interval(Interval::Milliseconds(100));
+
+        assert_eq!(timer.event_count, 3);
+    }
 }
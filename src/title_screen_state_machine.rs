@@ -15,6 +15,12 @@
  *  You should have received a copy of the GNU General Public License
  *  along with this program.  If not, see <http://www.gnu.org/licenses/>.
  */
+use crate::input::{
+    InputAction,
+};
+use crate::score::{
+    MAX_STARTING_LEVEL,
+};
 use crate::timer::{
     Timer,
     Interval,
@@ -29,6 +35,9 @@ pub struct TitleScreenStateMachineSpec {
     pub transition_interval: Interval,
     pub pressed_interval: Interval,
     pub unpressed_interval: Interval,
+    /// The time the player holds left or right to change the starting level by one
+    /// more after the first press.
+    pub level_select_interval: Interval,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -156,6 +165,9 @@ impl TitleScreenBlinkStateMachine {
 pub struct TitleScreenStateMachine {
     pub blink_state: TitleScreenBlinkStateMachine,
     pub transition_timer: Timer,
+    /// The level the player has chosen to start the game at.
+    starting_level: usize,
+    level_select_timer: Timer,
 }
 
 impl TitleScreenStateMachine {
@@ -165,6 +177,8 @@ impl TitleScreenStateMachine {
         TitleScreenStateMachine {
             blink_state: blink_state,
             transition_timer: Timer::new(spec.transition_interval),
+            starting_level: 0,
+            level_select_timer: Timer::new(spec.level_select_interval),
         }
     }
     
@@ -172,4 +186,174 @@ impl TitleScreenStateMachine {
     pub fn animation_is_on(&self) -> bool {
         self.blink_state.animation_is_on()
     }
+
+    /// The level the player has chosen to start the game at.
+    #[inline]
+    pub fn starting_level(&self) -> usize {
+        self.starting_level
+    }
+
+    /// Raise the starting level by one when the player presses right, and again 
+    /// each level select interval while they hold it. The starting level cannot 
+    /// be changed after the player presses start.
+    pub fn select_next_level(&mut self, action: InputAction, elapsed_milliseconds: Duration) {
+        if self.level_select_triggered(action, elapsed_milliseconds) {
+            self.starting_level = usize::min(self.starting_level + 1, MAX_STARTING_LEVEL);
+        }
+    }
+
+    /// Lower the starting level by one when the player presses left, and again 
+    /// each level select interval while they hold it. The starting level cannot 
+    /// be changed after the player presses start.
+    pub fn select_previous_level(&mut self, action: InputAction, elapsed_milliseconds: Duration) {
+        if self.level_select_triggered(action, elapsed_milliseconds) {
+            self.starting_level = self.starting_level.saturating_sub(1);
+        }
+    }
+
+    /// Return to the title screen after a game ends. The player keeps the
+    /// starting level they chose last time.
+    pub fn reset(&mut self) {
+        self.blink_state.unpressed();
+        self.transition_timer.reset();
+        self.level_select_timer.reset();
+    }
+
+    fn level_select_triggered(&mut self, action: InputAction, elapsed_milliseconds: Duration) -> bool {
+        if self.blink_state.is_pressed() {
+            return false;
+        }
+
+        match action {
+            InputAction::Press => {
+                self.level_select_timer.reset();
+                true
+            }
+            InputAction::Repeat => {
+                self.level_select_timer.update(elapsed_milliseconds);
+                if self.level_select_timer.event_triggered() {
+                    self.level_select_timer.reset();
+                    true
+                } else {
+                    false
+                }
+            }
+            InputAction::Release => {
+                self.level_select_timer.reset();
+                false
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        TitleScreenStateMachine,
+        TitleScreenStateMachineSpec,
+    };
+    use crate::input::{
+        InputAction,
+    };
+    use crate::score::{
+        MAX_STARTING_LEVEL,
+    };
+    use crate::timer::{
+        Interval,
+    };
+
+    use std::time::Duration;
+
+
+    const FRAME: Duration = Duration::from_millis(16);
+
+    fn title_screen() -> TitleScreenStateMachine {
+        TitleScreenStateMachine::new(TitleScreenStateMachineSpec {
+            transition_interval: Interval::Milliseconds(2000),
+            pressed_interval: Interval::Milliseconds(100),
+            unpressed_interval: Interval::Milliseconds(500),
+            level_select_interval: Interval::Milliseconds(150),
+        })
+    }
+
+    /// Tap right the given number of times, releasing the key after each press.
+    fn tap_right(title_screen: &mut TitleScreenStateMachine, taps: usize) {
+        for _ in 0..taps {
+            title_screen.select_next_level(InputAction::Press, FRAME);
+            title_screen.select_next_level(InputAction::Release, FRAME);
+        }
+    }
+
+
+    /// A single short press should change the starting level right away.
+    #[test]
+    fn tapping_right_should_raise_the_starting_level_right_away() {
+        let mut title_screen = title_screen();
+        tap_right(&mut title_screen, 1);
+        assert_eq!(title_screen.starting_level(), 1);
+
+        tap_right(&mut title_screen, 1);
+        assert_eq!(title_screen.starting_level(), 2);
+    }
+
+    /// Holding right should raise the starting level once every level select 
+    /// interval after the first press, up to the highest starting level.
+    #[test]
+    fn holding_right_should_raise_the_starting_level_up_to_the_maximum() {
+        let mut title_screen = title_screen();
+        title_screen.select_next_level(InputAction::Press, FRAME);
+        for _ in 0..9 {
+            title_screen.select_next_level(InputAction::Repeat, FRAME);
+        }
+        assert_eq!(title_screen.starting_level(), 1);
+
+        title_screen.select_next_level(InputAction::Repeat, FRAME);
+        assert_eq!(title_screen.starting_level(), 2);
+
+        for _ in 0..1000 {
+            title_screen.select_next_level(InputAction::Repeat, FRAME);
+        }
+        assert_eq!(title_screen.starting_level(), MAX_STARTING_LEVEL);
+    }
+
+    /// Holding left should lower the starting level, but not below zero.
+    #[test]
+    fn holding_left_should_lower_the_starting_level_down_to_zero() {
+        let mut title_screen = title_screen();
+        tap_right(&mut title_screen, 3);
+        assert_eq!(title_screen.starting_level(), 3);
+
+        title_screen.select_previous_level(InputAction::Press, FRAME);
+        for _ in 0..1000 {
+            title_screen.select_previous_level(InputAction::Repeat, FRAME);
+        }
+        assert_eq!(title_screen.starting_level(), 0);
+    }
+
+    /// The starting level should not change once the player has pressed start.
+    #[test]
+    fn the_starting_level_should_not_change_after_pressing_start() {
+        let mut title_screen = title_screen();
+        title_screen.blink_state.pressed();
+        tap_right(&mut title_screen, 3);
+
+        assert_eq!(title_screen.starting_level(), 0);
+    }
+
+    /// Returning to the title screen should wait for the player to press start
+    /// again, and should keep the starting level.
+    #[test]
+    fn returning_to_the_title_screen_should_wait_for_start_again() {
+        let mut title_screen = title_screen();
+        tap_right(&mut title_screen, 3);
+        title_screen.blink_state.pressed();
+        title_screen.transition_timer.update(Duration::from_millis(2000));
+        assert!(title_screen.transition_timer.event_triggered());
+        title_screen.reset();
+
+        assert!(!title_screen.blink_state.is_pressed());
+        assert!(!title_screen.transition_timer.event_triggered());
+        assert_eq!(title_screen.starting_level(), 3);
+    }
 }